@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 pub fn get_host_addr() -> String {
     let port = std::env::var("PORT").unwrap_or("8080".into());
     format!("[::]:{port}")
@@ -7,6 +9,57 @@ pub fn get_volume_path() -> String {
     std::env::var("VOLUME_PATH").unwrap_or_default()
 }
 
+pub fn get_storage_backend() -> String {
+    std::env::var("STORAGE_BACKEND").unwrap_or("s3".into())
+}
+
+/// Hours after which an idle multipart upload session is aborted
+pub fn get_upload_session_ttl_hours() -> u64 {
+    std::env::var("UPLOAD_SESSION_TTL_HOURS").ok().and_then(|v| v.parse().ok()).unwrap_or(24)
+}
+
+/// Hours an object without `media_files` row is kept before reconciliation treats it as orphan
+pub fn get_orphan_grace_hours() -> u64 {
+    std::env::var("ORPHAN_GRACE_HOURS").ok().and_then(|v| v.parse().ok()).unwrap_or(24)
+}
+
+/// Days a trashed file or album is kept before being purged
+pub fn get_trash_retention_days() -> u64 {
+    std::env::var("TRASH_RETENTION_DAYS").ok().and_then(|v| v.parse().ok()).unwrap_or(30)
+}
+
+/// Default storage quota in bytes for spaces without their own quota
+///
+/// Unset or `0` means unlimited
+pub fn get_default_space_quota() -> Option<i64> {
+    std::env::var("SPACE_STORAGE_QUOTA").ok().and_then(|v| v.parse().ok()).filter(|quota| *quota > 0)
+}
+
+/// How media is handed out to clients
+pub enum MediaDelivery {
+    /// Presigned storage URLs
+    Presign,
+
+    /// Signed backend URLs proxying storage, see `/v1/media/content/{id}`
+    Proxy,
+}
+
+pub fn get_media_delivery() -> MediaDelivery {
+    match std::env::var("MEDIA_DELIVERY").unwrap_or_default().as_str() {
+        "proxy" => MediaDelivery::Proxy,
+        _ => MediaDelivery::Presign,
+    }
+}
+
+/// Key signing proxied media URLs, required with `MEDIA_DELIVERY=proxy`
+pub fn get_media_signing_key() -> String {
+    std::env::var("MEDIA_SIGNING_KEY").unwrap_or_default()
+}
+
+pub fn get_backend_url() -> String {
+    std::env::var("SI_BACKEND_URL").unwrap_or_default().trim_end_matches('/').to_owned()
+}
+
 #[derive(Debug)]
 pub struct SIConfig {
     pub pub_pem: String,
@@ -30,14 +83,42 @@ pub struct DbConfig {
     pub url: String,
     pub username: String,
     pub password: String,
+
+    /// Maximum pooled connections
+    pub pool_size: usize,
+
+    /// Timeout for opening and health checking a connection
+    pub connect_timeout: Duration,
+
+    /// Timeout for waiting on a free pooled connection
+    pub pool_timeout: Duration,
 }
 impl DbConfig {
     pub fn new() -> Self {
-        Self {
+        let config = Self {
             url: std::env::var("DATABASE_URL").unwrap_or_default(),
             username: std::env::var("DATABASE_USERNAME").unwrap_or_default(),
             password: std::env::var("DATABASE_PASSWORD").unwrap_or_default(),
+            pool_size: parse_env("DATABASE_POOL_SIZE", 16),
+            connect_timeout: Duration::from_secs(parse_env("DATABASE_CONNECT_TIMEOUT_SECS", 5)),
+            pool_timeout: Duration::from_secs(parse_env("DATABASE_POOL_TIMEOUT_SECS", 10)),
+        };
+        if config.pool_size == 0 {
+            panic!("DATABASE_POOL_SIZE must be at least 1");
         }
+
+        config
+    }
+}
+
+/// Parses env var `key`, `default` when unset, panics on values that fail to parse
+fn parse_env<T: std::str::FromStr>(key: &str, default: T) -> T
+where
+    T::Err: std::fmt::Display,
+{
+    match std::env::var(key) {
+        Ok(value) => value.parse().unwrap_or_else(|err| panic!("Invalid {key} {value:?}: {err}")),
+        Err(_) => default,
     }
 }
 
@@ -62,6 +143,25 @@ impl S3Config {
     }
 }
 
+#[derive(Debug)]
+pub(crate) struct LocalStorageConfig {
+    pub volume_path: String,
+    pub signing_key: String,
+    pub base_url: String,
+}
+
+impl LocalStorageConfig {
+    pub(crate) fn new() -> Self {
+        Self {
+            volume_path: get_volume_path(),
+            signing_key: std::env::var("LOCAL_STORAGE_SIGNING_KEY").unwrap_or_default(),
+            base_url: std::env::var("LOCAL_STORAGE_URL")
+                .or_else(|_| std::env::var("SI_BACKEND_URL"))
+                .unwrap_or_default(),
+        }
+    }
+}
+
 pub(crate) struct ClerkConfig {
     pub aud: String,
     pub pem: String,
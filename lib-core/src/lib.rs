@@ -103,6 +103,8 @@ pub enum ErrType {
     ServerError,
     InvalidBody,
     TooManyRequests,
+    QuotaExceeded,
+    RangeNotSatisfiable,
 
     DbError,
     FsError,
@@ -192,6 +194,8 @@ impl Display for ErrType {
                 ErrType::ServerError => "ServerError",
                 ErrType::InvalidBody => "InvalidBody",
                 ErrType::TooManyRequests => "TooManyRequests",
+                ErrType::QuotaExceeded => "QuotaExceeded",
+                ErrType::RangeNotSatisfiable => "RangeNotSatisfiable",
 
                 ErrType::DbError => "DbError",
                 ErrType::FsError => "FileSystemError",
@@ -221,6 +225,10 @@ impl AppError {
         &self.err_msg
     }
 
+    pub fn err_type(&self) -> &ErrType {
+        &self._type
+    }
+
     #[track_caller]
     fn init(_type: ErrType, err: Option<Box<dyn Error>>, message: impl Into<String>) -> Self {
         let location = std::panic::Location::caller();
@@ -307,6 +315,8 @@ impl IntoResponse for ApiError {
             ErrType::NotFound => StatusCode::NOT_FOUND,
             ErrType::ServerError => StatusCode::INTERNAL_SERVER_ERROR,
             ErrType::TooManyRequests => StatusCode::TOO_MANY_REQUESTS,
+            ErrType::QuotaExceeded => StatusCode::PAYLOAD_TOO_LARGE,
+            ErrType::RangeNotSatisfiable => StatusCode::RANGE_NOT_SATISFIABLE,
 
             ErrType::DbError => StatusCode::INTERNAL_SERVER_ERROR,
             ErrType::FsError => StatusCode::FAILED_DEPENDENCY,
@@ -0,0 +1,515 @@
+use std::path::{Component, Path, PathBuf};
+
+use aws_sdk_s3::primitives::ByteStream;
+use axum::body::Body;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use tokio::io::AsyncWriteExt;
+use tokio_stream::StreamExt;
+
+use crate::{config::LocalStorageConfig, AppResult, ErrType};
+
+use super::{ObjectHead, ObjectInfo, ObjectPage, ObjectStream, StorageBackend, UploadedPart};
+
+/// Backend route serving presigned local URLs
+pub const LOCAL_STORAGE_ROUTE: &str = "/v1/storage";
+
+/// Volume folder holding parts of multipart uploads
+const MULTIPART_DIR: &str = ".uploads";
+
+/// Volume folder holding objects being written, outside of listed prefixes
+const TMP_DIR: &str = ".tmp";
+
+/// Objects returned per [`StorageBackend::list_objects`] page, mirrors S3
+const LIST_PAGE_SIZE: usize = 1000;
+
+/// Query of a presigned local URL
+#[derive(Deserialize)]
+pub struct SignedUrlQuery {
+    pub expires: i64,
+    pub signature: String,
+}
+
+/// Client for storing media on an attached volume
+///
+/// Presigned URLs point to [`LOCAL_STORAGE_ROUTE`] on the backend
+/// and are signed with HMAC-SHA256
+pub struct LocalStorage {
+    /// Root folder of the volume - `VOLUME_PATH`
+    root: PathBuf,
+
+    /// Base URL of the backend serving presigned URLs
+    base_url: String,
+
+    /// Key for signing URLs - user configured from secrets
+    signing_key: Vec<u8>,
+}
+
+impl LocalStorage {
+    pub fn new() -> Self {
+        let LocalStorageConfig {
+            volume_path,
+            signing_key,
+            base_url,
+        } = LocalStorageConfig::new();
+
+        if volume_path.is_empty() {
+            panic!("VOLUME_PATH is required for local storage");
+        }
+        if signing_key.is_empty() {
+            panic!("LOCAL_STORAGE_SIGNING_KEY is required for local storage");
+        }
+
+        Self {
+            root: PathBuf::from(volume_path),
+            base_url: base_url.trim_end_matches('/').to_owned(),
+            signing_key: signing_key.into_bytes(),
+        }
+    }
+
+    /// Resolves object key inside volume root
+    ///
+    /// Rejects keys escaping the root
+    fn resolve(&self, path: &str) -> AppResult<PathBuf> {
+        let path = Path::new(path.trim_matches('/'));
+        if !path.components().all(|c| matches!(c, Component::Normal(_))) {
+            return Err(ErrType::FsError.msg("Invalid object key"));
+        }
+        Ok(self.root.join(path))
+    }
+
+    fn sign(&self, method: &str, path: &str, expires: i64) -> AppResult<String> {
+        super::hmac_sign(&self.signing_key, &format!("{method}\n{}\n{expires}", path.trim_matches('/')))
+    }
+
+    fn signed_url(&self, method: &str, path: &str, expires_in: i64) -> AppResult<String> {
+        let expires = Utc::now().timestamp() + expires_in;
+        let signature = self.sign(method, path, expires)?;
+        let key = path.trim_matches('/').split('/').map(urlencoding::encode).collect::<Vec<_>>().join("/");
+
+        Ok(format!("{}{LOCAL_STORAGE_ROUTE}/{key}?expires={expires}&signature={signature}", self.base_url))
+    }
+
+    /// Verifies signature of a presigned URL generated by [`LocalStorage`]
+    pub fn verify_signature(&self, method: &str, path: &str, query: &SignedUrlQuery) -> AppResult<()> {
+        let expected = self.sign(method, path, query.expires)?;
+        super::verify_signature(&expected, query.expires, &query.signature)
+    }
+
+    /// Writes request body to object key
+    ///
+    /// Body is streamed to a temporary file and moved in place once complete
+    pub async fn write_stream(&self, path: &str, body: Body) -> AppResult<()> {
+        let dst = self.resolve(path)?;
+        let mut tmp = self.create_temp_file().await?;
+
+        let mut stream = body.into_data_stream();
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.map_err(|err| ErrType::BadRequest.err(err, "Failed to read upload body"))?;
+            tmp.file.write_all(&chunk).await.map_err(|err| ErrType::FsError.err(err, "Failed to write"))?;
+        }
+
+        tmp.persist(&dst).await
+    }
+
+    /// Creates file under [`TMP_DIR`], removed unless persisted
+    ///
+    /// Dropped writes, like a client disconnecting mid upload, leave nothing behind
+    async fn create_temp_file(&self) -> AppResult<TempFile> {
+        let path = self.resolve(&format!("{TMP_DIR}/{}", nanoid::nanoid!(24, &nanoid::alphabet::SAFE)))?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|err| ErrType::FsError.err(err, "Failed to create temp dir"))?;
+        }
+
+        let file =
+            tokio::fs::File::create(&path).await.map_err(|err| ErrType::FsError.err(err, "Failed to create object"))?;
+        Ok(TempFile {
+            path,
+            file,
+            persisted: false,
+        })
+    }
+
+    fn part_path(upload_id: &str, part_number: i32) -> String {
+        format!("{MULTIPART_DIR}/{upload_id}/{part_number}")
+    }
+}
+
+/// Temporary file of [`LocalStorage::create_temp_file`]
+struct TempFile {
+    path: PathBuf,
+    file: tokio::fs::File,
+    persisted: bool,
+}
+
+impl TempFile {
+    /// Moves file in place of `dst`
+    async fn persist(mut self, dst: &Path) -> AppResult<()> {
+        if let Some(parent) = dst.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|err| ErrType::FsError.err(err, "Failed to create object dir"))?;
+        }
+
+        self.file.flush().await.map_err(|err| ErrType::FsError.err(err, "Failed to flush object"))?;
+        tokio::fs::rename(&self.path, dst).await.map_err(|err| ErrType::FsError.err(err, "Failed to move object"))?;
+        self.persisted = true;
+        Ok(())
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        if !self.persisted {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Parses single `bytes=` range into inclusive `(start, end)`
+///
+/// Returns `None` for ranges that are ignored and served in full (multipart, other units, invalid syntax),
+/// errors only when a valid range starts past the end of the object
+fn parse_range(range: &str, size: u64) -> AppResult<Option<(u64, u64)>> {
+    let Some(spec) = range.trim().strip_prefix("bytes=") else {
+        return Ok(None);
+    };
+    let Some((start, end)) = spec.split_once('-') else {
+        return Ok(None);
+    };
+
+    let unsatisfiable = || Err(ErrType::RangeNotSatisfiable.msg(format!("Range not satisfiable: {range}")));
+    let (start, end) = match (start.trim(), end.trim()) {
+        ("", suffix) => match suffix.parse::<u64>() {
+            Ok(0) => return unsatisfiable(),
+            Ok(suffix) => (size.saturating_sub(suffix), size.saturating_sub(1)),
+            Err(_) => return Ok(None),
+        },
+        (start, "") => match start.parse() {
+            Ok(start) => (start, size.saturating_sub(1)),
+            Err(_) => return Ok(None),
+        },
+        (start, end) => match (start.parse::<u64>(), end.parse::<u64>()) {
+            (Ok(start), Ok(end)) if start <= end => (start, end.min(size.saturating_sub(1))),
+            _ => return Ok(None),
+        },
+    };
+
+    if start >= size {
+        return unsatisfiable();
+    }
+    Ok(Some((start, end)))
+}
+
+/// Entries of dir in object key order, folders compare by name with the trailing `/`
+///
+/// Missing dir lists as empty
+async fn read_dir_sorted(dir: &Path) -> AppResult<std::vec::IntoIter<(PathBuf, std::fs::Metadata)>> {
+    let mut entries = match tokio::fs::read_dir(dir).await {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new().into_iter()),
+        Err(err) => return Err(ErrType::FsError.err(err, "Failed to list objects")),
+    };
+
+    let mut sorted = Vec::new();
+    while let Some(entry) =
+        entries.next_entry().await.map_err(|err| ErrType::FsError.err(err, "Failed to list objects"))?
+    {
+        let meta = entry.metadata().await.map_err(|err| ErrType::FsError.err(err, "Failed to stat object"))?;
+        let Some(mut name) = entry.file_name().to_str().map(str::to_owned) else {
+            continue;
+        };
+        if meta.is_dir() {
+            name.push('/');
+        }
+        sorted.push((name, entry.path(), meta));
+    }
+    sorted.sort_by(|a, b| a.0.cmp(&b.0));
+
+    Ok(sorted.into_iter().map(|(_, path, meta)| (path, meta)).collect::<Vec<_>>().into_iter())
+}
+
+impl Default for LocalStorage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StorageBackend for LocalStorage {
+    async fn create_folder(&self, path: &str) -> AppResult<()> {
+        let dir = self.resolve(path)?;
+        tokio::fs::create_dir_all(dir).await.map_err(|err| ErrType::FsError.err(err, "Failed to create dir"))
+    }
+
+    async fn generate_upload_signed_url(&self, path: &str) -> AppResult<String> {
+        self.signed_url("PUT", path, 60 * 60)
+    }
+
+    async fn generate_stream_signed_url(&self, path: &str) -> AppResult<String> {
+        self.signed_url("GET", path, 3 * 60 * 60)
+    }
+
+    async fn upload_photo(&self, path_key: &str, bytes: Vec<u8>) -> AppResult<()> {
+        self.write_stream(path_key, Body::from(bytes)).await
+    }
+
+    async fn download_media(&self, path: &str) -> AppResult<ByteStream> {
+        let src = self.resolve(path)?;
+        ByteStream::from_path(src).await.map_err(|err| ErrType::FsError.err(err, "Failed to download media"))
+    }
+
+    async fn head_object(&self, path: &str) -> AppResult<ObjectHead> {
+        let src = self.resolve(path)?;
+        let meta = tokio::fs::metadata(src).await.map_err(|err| match err.kind() {
+            std::io::ErrorKind::NotFound => ErrType::NotFound.err(err, "Failed to head object"),
+            _ => ErrType::FsError.err(err, "Failed to head object"),
+        })?;
+        let last_modified = meta.modified().ok().map(DateTime::<Utc>::from);
+
+        Ok(ObjectHead {
+            content_length: Some(meta.len() as i64),
+            checksum_sha256: None,
+            e_tag: last_modified.map(|dt| format!("\"{:x}-{:x}\"", meta.len(), dt.timestamp_micros())),
+            last_modified,
+        })
+    }
+
+    async fn get_object(&self, path: &str, range: Option<&str>) -> AppResult<ObjectStream> {
+        let head = self.head_object(path).await?;
+        let size = head.content_length.unwrap_or_default() as u64;
+        let src = self.resolve(path)?;
+
+        let range = match range {
+            Some(range) => parse_range(range, size)?,
+            None => None,
+        };
+        let (offset, length) = range.map(|(start, end)| (start, end - start + 1)).unwrap_or((0, size));
+
+        let mut magic = Vec::with_capacity(8192);
+        if let Ok(file) = tokio::fs::File::open(&src).await {
+            let _ = tokio::io::AsyncReadExt::read_buf(&mut tokio::io::AsyncReadExt::take(file, 8192), &mut magic).await;
+        }
+
+        let body = ByteStream::read_from()
+            .path(&src)
+            .offset(offset)
+            .length(aws_sdk_s3::primitives::Length::Exact(length))
+            .build()
+            .await
+            .map_err(|err| ErrType::FsError.err(err, "Failed to read object"))?;
+
+        Ok(ObjectStream {
+            body,
+            content_length: length as i64,
+            content_range: range.map(|(start, end)| format!("bytes {start}-{end}/{size}")),
+            content_type: infer::get(&magic).map(|kind| kind.mime_type().to_owned()),
+            e_tag: head.e_tag,
+            last_modified: head.last_modified,
+        })
+    }
+
+    async fn delete_folder(&self, path: &str) -> AppResult<()> {
+        let dir = self.resolve(path)?;
+        match tokio::fs::remove_dir_all(dir).await {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+                Err(ErrType::FsError.err(err, "Failed to delete folder"))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    async fn delete_key(&self, path: &str) -> AppResult<()> {
+        let file = self.resolve(path)?;
+        match tokio::fs::remove_file(file).await {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+                Err(ErrType::FsError.err(err, "Failed to delete object"))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    async fn copy_object(&self, src_path: &str, dst_path: &str) -> AppResult<()> {
+        let src = self.resolve(src_path)?;
+        let dst = self.resolve(dst_path)?;
+        if let Some(parent) = dst.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|err| ErrType::FsError.err(err, "Failed to create object dir"))?;
+        }
+
+        tokio::fs::copy(src, dst).await.map_err(|err| match err.kind() {
+            std::io::ErrorKind::NotFound => ErrType::NotFound.err(err, "Object not found"),
+            _ => ErrType::FsError.err(err, "Failed to copy object"),
+        })?;
+        Ok(())
+    }
+
+    /// Walks prefix dir in key order, continuation token is the last key of previous page
+    ///
+    /// Folders entirely before the token are skipped and the walk stops once a page is filled
+    async fn list_objects(&self, prefix: &str, continuation_token: Option<String>) -> AppResult<ObjectPage> {
+        let after = |key: &str| continuation_token.as_ref().is_none_or(|token| key > token.as_str());
+
+        let mut stack = vec![read_dir_sorted(&self.resolve(prefix)?).await?];
+        let mut objects = Vec::new();
+
+        while objects.len() <= LIST_PAGE_SIZE {
+            let Some(entries) = stack.last_mut() else {
+                break;
+            };
+            let Some((path, meta)) = entries.next() else {
+                stack.pop();
+                continue;
+            };
+
+            let Some(key) = path.strip_prefix(&self.root).ok().and_then(|p| p.to_str()).map(str::to_owned) else {
+                continue;
+            };
+
+            if meta.is_dir() {
+                let dir_prefix = format!("{key}/");
+                let skipped = continuation_token
+                    .as_ref()
+                    .is_some_and(|token| !after(&dir_prefix) && !token.starts_with(&dir_prefix));
+                if !skipped {
+                    stack.push(read_dir_sorted(&path).await?);
+                }
+                continue;
+            }
+
+            if !after(&key) {
+                continue;
+            }
+
+            objects.push(ObjectInfo {
+                key,
+                size: meta.len() as i64,
+                last_modified: meta.modified().ok().map(DateTime::<Utc>::from),
+            });
+        }
+
+        let continuation_token = (objects.len() > LIST_PAGE_SIZE).then(|| objects[LIST_PAGE_SIZE - 1].key.clone());
+        objects.truncate(LIST_PAGE_SIZE);
+
+        Ok(ObjectPage {
+            objects,
+            continuation_token,
+        })
+    }
+
+    async fn delete_keys(&self, keys: Vec<String>) -> AppResult<usize> {
+        for key in keys.iter() {
+            self.delete_key(key).await?;
+        }
+        Ok(keys.len())
+    }
+
+    async fn create_multipart_upload(&self, _path: &str) -> AppResult<String> {
+        let upload_id = nanoid::nanoid!(24, &nanoid::alphabet::SAFE);
+        let dir = self.resolve(&format!("{MULTIPART_DIR}/{upload_id}"))?;
+        tokio::fs::create_dir_all(dir).await.map_err(|err| ErrType::FsError.err(err, "Failed to create upload dir"))?;
+        Ok(upload_id)
+    }
+
+    async fn generate_upload_part_signed_url(
+        &self,
+        _path: &str,
+        upload_id: &str,
+        part_number: i32,
+    ) -> AppResult<String> {
+        self.signed_url("PUT", &Self::part_path(upload_id, part_number), 60 * 60)
+    }
+
+    async fn list_parts(&self, _path: &str, upload_id: &str) -> AppResult<Vec<UploadedPart>> {
+        let dir = self.resolve(&format!("{MULTIPART_DIR}/{upload_id}"))?;
+        let mut entries = tokio::fs::read_dir(dir).await.map_err(|err| match err.kind() {
+            std::io::ErrorKind::NotFound => ErrType::NotFound.err(err, "Multipart upload not found"),
+            _ => ErrType::FsError.err(err, "Failed to list parts"),
+        })?;
+
+        let mut parts = Vec::new();
+        while let Some(entry) =
+            entries.next_entry().await.map_err(|err| ErrType::FsError.err(err, "Failed to list parts"))?
+        {
+            // skip in-flight temporary files
+            let Some(part_number) = entry.file_name().to_str().and_then(|name| name.parse::<i32>().ok()) else {
+                continue;
+            };
+            let meta = entry.metadata().await.map_err(|err| ErrType::FsError.err(err, "Failed to stat part"))?;
+            parts.push(UploadedPart {
+                part_number,
+                size: meta.len() as i64,
+                e_tag: None,
+            });
+        }
+        parts.sort_by_key(|part| part.part_number);
+
+        Ok(parts)
+    }
+
+    async fn complete_multipart_upload(&self, path: &str, upload_id: &str, parts: Vec<UploadedPart>) -> AppResult<()> {
+        let dst = self.resolve(path)?;
+        let mut tmp = self.create_temp_file().await?;
+
+        for part in parts.iter() {
+            let src = self.resolve(&Self::part_path(upload_id, part.part_number))?;
+            let mut part_file = tokio::fs::File::open(&src)
+                .await
+                .map_err(|err| ErrType::BadRequest.err(err, format!("Missing part {}", part.part_number)))?;
+            tokio::io::copy(&mut part_file, &mut tmp.file)
+                .await
+                .map_err(|err| ErrType::FsError.err(err, "Failed to write part"))?;
+        }
+
+        tmp.persist(&dst).await?;
+
+        self.abort_multipart_upload(path, upload_id).await
+    }
+
+    async fn abort_multipart_upload(&self, _path: &str, upload_id: &str) -> AppResult<()> {
+        self.delete_folder(&format!("{MULTIPART_DIR}/{upload_id}")).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(spec: &str, size: u64) -> Option<(u64, u64)> {
+        parse_range(spec, size).expect("range should be satisfiable")
+    }
+
+    #[test]
+    fn parse_range_bounds() {
+        assert_eq!(range("bytes=0-99", 1000), Some((0, 99)));
+        assert_eq!(range("bytes=100-", 1000), Some((100, 999)));
+        assert_eq!(range("bytes=-100", 1000), Some((900, 999)));
+        assert_eq!(range("bytes=999-999", 1000), Some((999, 999)));
+    }
+
+    #[test]
+    fn parse_range_clamps_to_size() {
+        assert_eq!(range("bytes=900-5000", 1000), Some((900, 999)));
+        assert_eq!(range("bytes=-5000", 1000), Some((0, 999)));
+    }
+
+    #[test]
+    fn parse_range_ignores_invalid_syntax() {
+        assert_eq!(range("bytes=abc", 1000), None);
+        assert_eq!(range("bytes=a-b", 1000), None);
+        assert_eq!(range("bytes=-x", 1000), None);
+        assert_eq!(range("bytes=50-10", 1000), None);
+        assert_eq!(range("bytes=0-1,5-6", 1000), None);
+        assert_eq!(range("items=0-10", 1000), None);
+    }
+
+    #[test]
+    fn parse_range_rejects_unsatisfiable() {
+        assert!(parse_range("bytes=1000-", 1000).is_err());
+        assert!(parse_range("bytes=1000-2000", 1000).is_err());
+        assert!(parse_range("bytes=-0", 1000).is_err());
+        assert!(parse_range("bytes=0-", 0).is_err());
+    }
+}
@@ -1,28 +1,356 @@
 use std::path::PathBuf;
 
-use super::{AppResult, ErrType};
+use async_zip::{base::write::ZipFileWriter, Compression, ZipEntryBuilder};
+use aws_sdk_s3::primitives::ByteStream;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use openssl::{hash::MessageDigest, pkey::PKey, sign::Signer};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tokio_util::compat::FuturesAsyncWriteCompatExt;
 
+use super::{config, AppResult, ErrType};
+
+pub mod local;
 pub mod s3;
 
 const ROOT_FOLDER: &str = "somarift-data";
 const SPACES_PATH: &str = "spaces";
 
+/// Object keeping an otherwise empty folder alive on S3
+pub const FOLDER_MARKER: &str = "fd.dat";
+
+/// Largest object accepted for upload, the S3 object size limit of 5 TiB
+pub const MAX_OBJECT_SIZE: i64 = 5 * 1024 * 1024 * 1024 * 1024;
+
+/// Backend route proxying media, authorized by URL signature, see [`Storage::generate_content_signed_url`]
+pub const MEDIA_CONTENT_ROUTE: &str = "/v1/media/content";
+
+/// Seconds a proxied media URL stays valid, kept short since the URL works without auth headers
+pub const CONTENT_URL_EXPIRY: i64 = 15 * 60;
+
+/// Archive entry listing files that could not be added, see [`Storage::write_zip`]
+///
+/// Reserved, entries must not use this name
+pub const ARCHIVE_MANIFEST: &str = "MANIFEST.txt";
+
+/// Object info returned by [`StorageBackend::head_object`]
+pub struct ObjectHead {
+    pub content_length: Option<i64>,
+
+    /// Base64 encoded SHA-256 checksum, if stored by the backend
+    pub checksum_sha256: Option<String>,
+    pub e_tag: Option<String>,
+    pub last_modified: Option<DateTime<Utc>>,
+}
+
+/// Object body returned by [`StorageBackend::get_object`]
+pub struct ObjectStream {
+    pub body: ByteStream,
+
+    /// Length of `body`, the requested range if any
+    pub content_length: i64,
+
+    /// `bytes <start>-<end>/<size>` when a range was served
+    pub content_range: Option<String>,
+    pub content_type: Option<String>,
+    pub e_tag: Option<String>,
+    pub last_modified: Option<DateTime<Utc>>,
+}
+impl ObjectStream {
+    pub fn into_body(self) -> axum::body::Body {
+        axum::body::Body::from_stream(tokio_util::io::ReaderStream::new(self.body.into_async_read()))
+    }
+}
+
+/// Object read by [`Storage::get_object`]
+pub enum ObjectContent {
+    /// E-tag matched `If-None-Match`, nothing was read
+    NotModified(String),
+    /// Range starts past the end of the object, holds the object size
+    RangeNotSatisfiable(i64),
+    Stream(ObjectStream),
+}
+
+/// Reads object with optional HTTP `Range`, heading it for its size when the range is not satisfiable
+pub async fn get_object_content(
+    backend: &impl StorageBackend,
+    path: &str,
+    range: Option<&str>,
+) -> AppResult<ObjectContent> {
+    match backend.get_object(path, range).await {
+        Err(err) if matches!(err.err_type(), ErrType::RangeNotSatisfiable) => {
+            let size = backend.head_object(path).await?.content_length.unwrap_or_default();
+            Ok(ObjectContent::RangeNotSatisfiable(size))
+        }
+        result => result.map(ObjectContent::Stream),
+    }
+}
+
+/// Object listed by [`StorageBackend::list_objects`]
+pub struct ObjectInfo {
+    pub key: String,
+    pub size: i64,
+    pub last_modified: Option<DateTime<Utc>>,
+}
+
+/// Page of objects, `continuation_token` is set while more objects remain
+pub struct ObjectPage {
+    pub objects: Vec<ObjectInfo>,
+    pub continuation_token: Option<String>,
+}
+
+/// File added to archive by [`Storage::write_zip`]
+pub struct ArchiveEntry {
+    /// Unique name inside the archive
+    pub name: String,
+    pub path: String,
+}
+
+/// Part of a multipart upload stored by the backend
+pub struct UploadedPart {
+    pub part_number: i32,
+    pub size: i64,
+    pub e_tag: Option<String>,
+}
+
+/// Operations required from a storage provider
+///
+/// All paths are full object keys, see [`Storage`] for space scoped paths
+pub trait StorageBackend: Send + Sync {
+    fn create_folder(&self, path: &str) -> impl Future<Output = AppResult<()>> + Send;
+    fn generate_upload_signed_url(&self, path: &str) -> impl Future<Output = AppResult<String>> + Send;
+    fn generate_stream_signed_url(&self, path: &str) -> impl Future<Output = AppResult<String>> + Send;
+    fn upload_photo(&self, path_key: &str, bytes: Vec<u8>) -> impl Future<Output = AppResult<()>> + Send;
+    fn download_media(&self, path: &str) -> impl Future<Output = AppResult<ByteStream>> + Send;
+    fn head_object(&self, path: &str) -> impl Future<Output = AppResult<ObjectHead>> + Send;
+
+    /// Reads object, `range` is the HTTP `Range` header value
+    fn get_object(&self, path: &str, range: Option<&str>) -> impl Future<Output = AppResult<ObjectStream>> + Send;
+    fn delete_folder(&self, path: &str) -> impl Future<Output = AppResult<()>> + Send;
+    fn delete_key(&self, path: &str) -> impl Future<Output = AppResult<()>> + Send;
+
+    /// Copies object server-side, overwriting `dst_path`
+    fn copy_object(&self, src_path: &str, dst_path: &str) -> impl Future<Output = AppResult<()>> + Send;
+
+    /// Lists one page of objects under prefix
+    fn list_objects(
+        &self,
+        prefix: &str,
+        continuation_token: Option<String>,
+    ) -> impl Future<Output = AppResult<ObjectPage>> + Send;
+
+    /// Deletes keys in batches, returns number of deleted keys
+    fn delete_keys(&self, keys: Vec<String>) -> impl Future<Output = AppResult<usize>> + Send;
+
+    /// Starts multipart upload, returns upload id
+    fn create_multipart_upload(&self, path: &str) -> impl Future<Output = AppResult<String>> + Send;
+    fn generate_upload_part_signed_url(
+        &self,
+        path: &str,
+        upload_id: &str,
+        part_number: i32,
+    ) -> impl Future<Output = AppResult<String>> + Send;
+    fn list_parts(&self, path: &str, upload_id: &str) -> impl Future<Output = AppResult<Vec<UploadedPart>>> + Send;
+    fn complete_multipart_upload(
+        &self,
+        path: &str,
+        upload_id: &str,
+        parts: Vec<UploadedPart>,
+    ) -> impl Future<Output = AppResult<()>> + Send;
+    fn abort_multipart_upload(&self, path: &str, upload_id: &str) -> impl Future<Output = AppResult<()>> + Send;
+}
+
+/// Storage backend selected by `STORAGE_BACKEND`
+///
+/// * `s3` (default): [`s3::S3Storage`]
+/// * `local`: [`local::LocalStorage`] rooted at `VOLUME_PATH`
+pub enum Backend {
+    S3(s3::S3Storage),
+    Local(local::LocalStorage),
+}
+
+impl Backend {
+    pub fn new() -> Self {
+        match config::get_storage_backend().as_str() {
+            "local" => Self::Local(local::LocalStorage::new()),
+            _ => Self::S3(s3::S3Storage::new()),
+        }
+    }
+}
+
+impl Default for Backend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StorageBackend for Backend {
+    async fn create_folder(&self, path: &str) -> AppResult<()> {
+        match self {
+            Self::S3(s3) => s3.create_folder(path).await,
+            Self::Local(local) => local.create_folder(path).await,
+        }
+    }
+
+    async fn generate_upload_signed_url(&self, path: &str) -> AppResult<String> {
+        match self {
+            Self::S3(s3) => s3.generate_upload_signed_url(path).await,
+            Self::Local(local) => local.generate_upload_signed_url(path).await,
+        }
+    }
+
+    async fn generate_stream_signed_url(&self, path: &str) -> AppResult<String> {
+        match self {
+            Self::S3(s3) => s3.generate_stream_signed_url(path).await,
+            Self::Local(local) => local.generate_stream_signed_url(path).await,
+        }
+    }
+
+    async fn upload_photo(&self, path_key: &str, bytes: Vec<u8>) -> AppResult<()> {
+        match self {
+            Self::S3(s3) => s3.upload_photo(path_key, bytes).await,
+            Self::Local(local) => local.upload_photo(path_key, bytes).await,
+        }
+    }
+
+    async fn download_media(&self, path: &str) -> AppResult<ByteStream> {
+        match self {
+            Self::S3(s3) => s3.download_media(path).await,
+            Self::Local(local) => local.download_media(path).await,
+        }
+    }
+
+    async fn head_object(&self, path: &str) -> AppResult<ObjectHead> {
+        match self {
+            Self::S3(s3) => s3.head_object(path).await,
+            Self::Local(local) => local.head_object(path).await,
+        }
+    }
+
+    async fn get_object(&self, path: &str, range: Option<&str>) -> AppResult<ObjectStream> {
+        match self {
+            Self::S3(s3) => s3.get_object(path, range).await,
+            Self::Local(local) => local.get_object(path, range).await,
+        }
+    }
+
+    async fn copy_object(&self, src_path: &str, dst_path: &str) -> AppResult<()> {
+        match self {
+            Self::S3(s3) => s3.copy_object(src_path, dst_path).await,
+            Self::Local(local) => local.copy_object(src_path, dst_path).await,
+        }
+    }
+
+    async fn delete_folder(&self, path: &str) -> AppResult<()> {
+        match self {
+            Self::S3(s3) => s3.delete_folder(path).await,
+            Self::Local(local) => local.delete_folder(path).await,
+        }
+    }
+
+    async fn delete_key(&self, path: &str) -> AppResult<()> {
+        match self {
+            Self::S3(s3) => s3.delete_key(path).await,
+            Self::Local(local) => local.delete_key(path).await,
+        }
+    }
+
+    async fn list_objects(&self, prefix: &str, continuation_token: Option<String>) -> AppResult<ObjectPage> {
+        match self {
+            Self::S3(s3) => s3.list_objects(prefix, continuation_token).await,
+            Self::Local(local) => local.list_objects(prefix, continuation_token).await,
+        }
+    }
+
+    async fn delete_keys(&self, keys: Vec<String>) -> AppResult<usize> {
+        match self {
+            Self::S3(s3) => s3.delete_keys(keys).await,
+            Self::Local(local) => local.delete_keys(keys).await,
+        }
+    }
+
+    async fn create_multipart_upload(&self, path: &str) -> AppResult<String> {
+        match self {
+            Self::S3(s3) => s3.create_multipart_upload(path).await,
+            Self::Local(local) => local.create_multipart_upload(path).await,
+        }
+    }
+
+    async fn generate_upload_part_signed_url(
+        &self,
+        path: &str,
+        upload_id: &str,
+        part_number: i32,
+    ) -> AppResult<String> {
+        match self {
+            Self::S3(s3) => s3.generate_upload_part_signed_url(path, upload_id, part_number).await,
+            Self::Local(local) => local.generate_upload_part_signed_url(path, upload_id, part_number).await,
+        }
+    }
+
+    async fn list_parts(&self, path: &str, upload_id: &str) -> AppResult<Vec<UploadedPart>> {
+        match self {
+            Self::S3(s3) => s3.list_parts(path, upload_id).await,
+            Self::Local(local) => local.list_parts(path, upload_id).await,
+        }
+    }
+
+    async fn complete_multipart_upload(&self, path: &str, upload_id: &str, parts: Vec<UploadedPart>) -> AppResult<()> {
+        match self {
+            Self::S3(s3) => s3.complete_multipart_upload(path, upload_id, parts).await,
+            Self::Local(local) => local.complete_multipart_upload(path, upload_id, parts).await,
+        }
+    }
+
+    async fn abort_multipart_upload(&self, path: &str, upload_id: &str) -> AppResult<()> {
+        match self {
+            Self::S3(s3) => s3.abort_multipart_upload(path, upload_id).await,
+            Self::Local(local) => local.abort_multipart_upload(path, upload_id).await,
+        }
+    }
+}
+
 /// Manage storage operations
 ///
-/// Mimic the file structure from [`S3Storage`] in attached volume
+/// Mimic the file structure from [`s3::S3Storage`] in attached volume
 pub struct Storage {
     /// Root folder for S3: [`ROOT_FOLDER`]/[`SPACES_PATH`],
     spaces_path: PathBuf,
 
-    /// S3 client
-    s3: s3::S3Storage,
+    /// Storage provider
+    backend: Backend,
+
+    /// Key for signing proxied media URLs - `MEDIA_SIGNING_KEY`
+    content_signing_key: Vec<u8>,
 }
 
 impl Storage {
     pub async fn new() -> Self {
+        let content_signing_key = config::get_media_signing_key();
+        if let config::MediaDelivery::Proxy = config::get_media_delivery()
+            && content_signing_key.is_empty()
+        {
+            panic!("MEDIA_SIGNING_KEY is required for proxied media delivery");
+        }
+
         Self {
             spaces_path: PathBuf::from(ROOT_FOLDER).join(SPACES_PATH),
-            s3: s3::S3Storage::new(),
+            backend: Backend::new(),
+            content_signing_key: content_signing_key.into_bytes(),
+        }
+    }
+
+    pub fn backend(&self) -> &Backend {
+        &self.backend
+    }
+
+    /// Local backend, if configured
+    ///
+    /// Used by backend routes serving presigned local URLs
+    pub fn local(&self) -> Option<&local::LocalStorage> {
+        match &self.backend {
+            Backend::Local(local) => Some(local),
+            Backend::S3(_) => None,
         }
     }
 
@@ -41,7 +369,7 @@ impl Storage {
     pub async fn create_space_folder(&self, space_id: &str) -> AppResult<()> {
         let remote_path = self.spaces_path.join(space_id);
         let remote_path = remote_path.to_str().ok_or(ErrType::FsError.msg("Failed to get str from folder path"))?;
-        self.s3.create_folder(remote_path).await
+        self.backend.create_folder(remote_path).await
     }
 
     /// Generate presigned URL for uploading media
@@ -53,7 +381,7 @@ impl Storage {
         let file_path = self.spaces_path.join(space_id).join(file_path);
         let file_path = file_path.to_str().ok_or(ErrType::FsError.msg("Failed to get str from file path"))?;
 
-        self.s3.generate_upload_signed_url(file_path).await
+        self.backend.generate_upload_signed_url(file_path).await
     }
 
     /// Generate presigned URL for steaming media
@@ -62,7 +390,79 @@ impl Storage {
     pub async fn generate_stream_signed_url(&self, space_id: &str, path: &str) -> AppResult<String> {
         let path = self.clean_path(path)?;
         let path = self.spaces_path.join(space_id).join(path);
-        self.s3.generate_stream_signed_url(path.to_str().unwrap()).await
+        self.backend.generate_stream_signed_url(path.to_str().unwrap()).await
+    }
+
+    /// Generate signed URL of [`MEDIA_CONTENT_ROUTE`] for a file variant
+    ///
+    /// Media elements cannot send auth headers, so the signature authorizes the user, space and file.
+    /// Membership of the user is still checked when the URL is served.
+    pub fn generate_content_signed_url(
+        &self,
+        space_id: &str,
+        user_id: &str,
+        file_id: &str,
+        variant: &str,
+    ) -> AppResult<String> {
+        let expires = Utc::now().timestamp() + CONTENT_URL_EXPIRY;
+        let signature = sign_content(&self.content_signing_key, space_id, user_id, file_id, variant, expires)?;
+
+        Ok(format!(
+            "{}{MEDIA_CONTENT_ROUTE}/{file_id}?space_id={space_id}&user_id={user_id}&variant={variant}&expires={expires}&signature={signature}",
+            config::get_backend_url()
+        ))
+    }
+
+    /// Verifies signature of a URL generated by [`Storage::generate_content_signed_url`]
+    pub fn verify_content_signature(
+        &self,
+        space_id: &str,
+        user_id: &str,
+        file_id: &str,
+        variant: &str,
+        expires: i64,
+        signature: &str,
+    ) -> AppResult<()> {
+        let expected = sign_content(&self.content_signing_key, space_id, user_id, file_id, variant, expires)?;
+        verify_signature(&expected, expires, signature)
+    }
+
+    /// Reads media with optional HTTP `Range`
+    ///
+    /// Used by backend route proxying media. When `if_none_match` matches the e-tag
+    /// the object is only headed, not opened.
+    pub async fn get_object(
+        &self,
+        space_id: &str,
+        path: &str,
+        range: Option<&str>,
+        if_none_match: Option<&str>,
+    ) -> AppResult<ObjectContent> {
+        let remote_path = self.get_remote_path(space_id, path)?;
+
+        if let Some(if_none_match) = if_none_match
+            && let Some(e_tag) = self.backend.head_object(&remote_path).await?.e_tag
+            && if_none_match.split(',').any(|tag| tag.trim() == e_tag || tag.trim() == "*")
+        {
+            return Ok(ObjectContent::NotModified(e_tag));
+        }
+
+        get_object_content(&self.backend, &remote_path, range).await
+    }
+
+    /// Copies object to the same path in another space
+    pub async fn copy_to_space(&self, src_space_id: &str, dst_space_id: &str, path: &str) -> AppResult<()> {
+        let src_path = self.get_remote_path(src_space_id, path)?;
+        let dst_path = self.get_remote_path(dst_space_id, path)?;
+        self.backend.copy_object(&src_path, &dst_path).await
+    }
+
+    /// Moves object to another path of the same space
+    pub async fn move_object(&self, space_id: &str, src_path: &str, dst_path: &str) -> AppResult<()> {
+        let src_path = self.get_remote_path(space_id, src_path)?;
+        let dst_path = self.get_remote_path(space_id, dst_path)?;
+        self.backend.copy_object(&src_path, &dst_path).await?;
+        self.backend.delete_key(&src_path).await
     }
 
     pub fn get_remote_path(&self, space_id: &str, path: &str) -> AppResult<String> {
@@ -75,6 +475,75 @@ impl Storage {
             .ok_or(ErrType::FsError.msg("Failed to get remote path"))
     }
 
+    /// Starts multipart upload for media, returns upload id
+    pub async fn create_multipart_upload(&self, space_id: &str, file_path: &str) -> AppResult<String> {
+        let remote_path = self.get_remote_path(space_id, file_path)?;
+        self.backend.create_multipart_upload(&remote_path).await
+    }
+
+    /// Generate presigned URL for uploading a part of multipart upload
+    ///
+    /// To be used by frontend
+    pub async fn generate_upload_part_signed_url(
+        &self,
+        space_id: &str,
+        file_path: &str,
+        upload_id: &str,
+        part_number: i32,
+    ) -> AppResult<String> {
+        let remote_path = self.get_remote_path(space_id, file_path)?;
+        self.backend.generate_upload_part_signed_url(&remote_path, upload_id, part_number).await
+    }
+
+    pub async fn list_uploaded_parts(
+        &self,
+        space_id: &str,
+        file_path: &str,
+        upload_id: &str,
+    ) -> AppResult<Vec<UploadedPart>> {
+        let remote_path = self.get_remote_path(space_id, file_path)?;
+        self.backend.list_parts(&remote_path, upload_id).await
+    }
+
+    pub async fn complete_multipart_upload(
+        &self,
+        space_id: &str,
+        file_path: &str,
+        upload_id: &str,
+        parts: Vec<UploadedPart>,
+    ) -> AppResult<()> {
+        let remote_path = self.get_remote_path(space_id, file_path)?;
+        self.backend.complete_multipart_upload(&remote_path, upload_id, parts).await
+    }
+
+    pub async fn abort_multipart_upload(&self, space_id: &str, file_path: &str, upload_id: &str) -> AppResult<()> {
+        let remote_path = self.get_remote_path(space_id, file_path)?;
+        self.backend.abort_multipart_upload(&remote_path, upload_id).await
+    }
+
+    /// Lists one page of objects stored for space
+    pub async fn list_space_objects(
+        &self,
+        space_id: &str,
+        continuation_token: Option<String>,
+    ) -> AppResult<ObjectPage> {
+        let prefix = self.spaces_path.join(space_id);
+        let prefix = prefix.to_str().ok_or(ErrType::FsError.msg("Failed to get str from folder path"))?;
+        self.backend.list_objects(&format!("{prefix}/"), continuation_token).await
+    }
+
+    /// Deletes full object keys as listed by [`Storage::list_space_objects`]
+    pub async fn delete_keys(&self, keys: Vec<String>) -> AppResult<usize> {
+        self.backend.delete_keys(keys).await
+    }
+
+    /// Removes space folder along with anything left in it
+    pub async fn delete_space_folder(&self, space_id: &str) -> AppResult<()> {
+        let remote_path = self.spaces_path.join(space_id);
+        let remote_path = remote_path.to_str().ok_or(ErrType::FsError.msg("Failed to get str from folder path"))?;
+        self.backend.delete_folder(&format!("{remote_path}/")).await
+    }
+
     pub async fn delete_folder(&self, space_id: &str, dir_path: &str) -> AppResult<()> {
         let path = self.clean_path(dir_path)?;
 
@@ -84,7 +553,127 @@ impl Storage {
         }
         let remote_path = remote_path.to_str().ok_or(ErrType::FsError.msg("Failed to get str from folder path"))?;
 
-        self.s3.delete_folder(remote_path).await
+        self.backend.delete_folder(remote_path).await
+    }
+
+    /// Checks uploaded object against the size and SHA-256 hex digest declared by client
+    ///
+    /// Only heads the object. Returns `false` when the backend stores no checksum of the full
+    /// object, the hash is then left to [`Storage::verify_sha256`]. On mismatch the object is
+    /// deleted, so it must be a staging key owned by the uploader rather than a key other files may share.
+    pub async fn check_upload(
+        &self,
+        space_id: &str,
+        file_path: &str,
+        expected_hex: &str,
+        expected_size: i64,
+    ) -> AppResult<bool> {
+        let remote_path = self.get_remote_path(space_id, file_path)?;
+        let head = self.backend.head_object(&remote_path).await?;
+
+        if head.content_length != Some(expected_size) {
+            self.backend.delete_key(&remote_path).await?;
+            return Err(ErrType::BadRequest.msg("Uploaded object does not match declared size"));
+        }
+
+        // composite checksums of multipart uploads are suffixed with `-<parts>`
+        let stored = head
+            .checksum_sha256
+            .filter(|checksum| !checksum.contains('-'))
+            .and_then(|checksum| base64::engine::general_purpose::STANDARD.decode(checksum).ok());
+
+        match stored {
+            Some(digest) => self.match_sha256(&remote_path, &digest, expected_hex).await.map(|_| true),
+            None => Ok(false),
+        }
+    }
+
+    /// Verifies uploaded object matches the SHA-256 hex digest declared by client by hashing it
+    ///
+    /// Reads the full object, so it runs in the background rather than in a request.
+    /// On mismatch the object is deleted, see [`Storage::check_upload`].
+    pub async fn verify_sha256(&self, space_id: &str, file_path: &str, expected_hex: &str) -> AppResult<()> {
+        let remote_path = self.get_remote_path(space_id, file_path)?;
+
+        let mut stream = self.backend.download_media(&remote_path).await?;
+        let mut hasher = openssl::sha::Sha256::new();
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.map_err(|err| ErrType::S3Error.err(err, "Failed to read object for hashing"))?;
+            hasher.update(&chunk);
+        }
+
+        self.match_sha256(&remote_path, &hasher.finish(), expected_hex).await
+    }
+
+    async fn match_sha256(&self, remote_path: &str, digest: &[u8], expected_hex: &str) -> AppResult<()> {
+        let actual_hex = digest.iter().map(|b| format!("{b:02x}")).collect::<String>();
+        if actual_hex.eq_ignore_ascii_case(expected_hex) {
+            return Ok(());
+        }
+
+        self.backend.delete_key(remote_path).await?;
+        Err(ErrType::BadRequest.msg("Uploaded object does not match declared hash"))
+    }
+
+    /// Streams ZIP of entries into writer, one object at a time
+    ///
+    /// Entries are stored uncompressed since media is already compressed. Objects failing to
+    /// download are listed in [`ARCHIVE_MANIFEST`] instead of failing the archive.
+    pub async fn write_zip<W: AsyncWrite + Unpin>(
+        &self,
+        space_id: &str,
+        entries: Vec<ArchiveEntry>,
+        writer: W,
+    ) -> AppResult<()> {
+        let zip_err = |err| ErrType::ServerError.err(err, "Failed to write archive");
+
+        let mut zip = ZipFileWriter::with_tokio(writer);
+        let mut failed = Vec::new();
+        for entry in entries {
+            let stream = match self.get_remote_path(space_id, &entry.path) {
+                Ok(remote_path) => self.backend.download_media(&remote_path).await,
+                Err(err) => Err(err),
+            };
+            let mut stream = match stream {
+                Ok(stream) => stream,
+                Err(err) => {
+                    failed.push(format!("{}: {}", entry.name, err.err_message()));
+                    continue;
+                }
+            };
+
+            let builder = ZipEntryBuilder::new(entry.name.clone().into(), Compression::Stored);
+            let mut writer = zip.write_entry_stream(builder).await.map_err(zip_err)?.compat_write();
+
+            // entry is closed even on a failed read to keep the archive readable
+            let mut read_err = None;
+            while let Some(chunk) = stream.next().await {
+                match chunk {
+                    Ok(chunk) => writer
+                        .write_all(&chunk)
+                        .await
+                        .map_err(|err| ErrType::ServerError.err(err, "Failed to write archive"))?,
+                    Err(err) => {
+                        read_err = Some(err.to_string());
+                        break;
+                    }
+                }
+            }
+            writer.into_inner().close().await.map_err(zip_err)?;
+
+            if let Some(err) = read_err {
+                failed.push(format!("{}: incomplete, {err}", entry.name));
+            }
+        }
+
+        if !failed.is_empty() {
+            let manifest = format!("Files that could not be downloaded:\n{}\n", failed.join("\n"));
+            let builder = ZipEntryBuilder::new(ARCHIVE_MANIFEST.to_owned().into(), Compression::Stored);
+            zip.write_entry_whole(builder, manifest.as_bytes()).await.map_err(zip_err)?;
+        }
+
+        let mut writer = zip.close().await.map_err(zip_err)?.into_inner();
+        writer.shutdown().await.map_err(|err| ErrType::ServerError.err(err, "Failed to write archive"))
     }
 
     pub async fn delete_file(
@@ -96,23 +685,59 @@ impl Storage {
     ) -> AppResult<()> {
         let remote_file = self.clean_path(&remote_file)?;
         let remote_file = self.spaces_path.join(space_id).join(remote_file);
-        self.s3.delete_key(remote_file.to_str().unwrap()).await?;
+        self.backend.delete_key(remote_file.to_str().unwrap()).await?;
 
         if let Some(remote_thumbnail) = remote_thumbnail {
             let remote_thumbnail = self.clean_path(&remote_thumbnail)?;
             let remote_thumbnail = self.spaces_path.join(space_id).join(remote_thumbnail);
-            self.s3.delete_key(remote_thumbnail.to_str().unwrap()).await?;
+            self.backend.delete_key(remote_thumbnail.to_str().unwrap()).await?;
         }
 
         if let Some(remote_preview) = remote_preview {
             let remote_preview = self.clean_path(&remote_preview)?;
             let remote_preview = self.spaces_path.join(space_id).join(remote_preview);
-            self.s3.delete_key(remote_preview.to_str().unwrap()).await?;
+            self.backend.delete_key(remote_preview.to_str().unwrap()).await?;
         }
         Ok(())
     }
 }
 
+/// Signature of a media content URL, see [`Storage::generate_content_signed_url`]
+fn sign_content(
+    key: &[u8],
+    space_id: &str,
+    user_id: &str,
+    file_id: &str,
+    variant: &str,
+    expires: i64,
+) -> AppResult<String> {
+    hmac_sign(key, &format!("GET\n{space_id}/{file_id}/{variant}\n{user_id}\n{expires}"))
+}
+
+/// URL safe base64 HMAC-SHA256 of message, signing backend URLs
+fn hmac_sign(key: &[u8], message: &str) -> AppResult<String> {
+    let key = PKey::hmac(key).map_err(|err| ErrType::ServerError.err(err, "Failed to create signing key"))?;
+    let mut signer = Signer::new(MessageDigest::sha256(), &key)
+        .map_err(|err| ErrType::ServerError.err(err, "Failed to create signer"))?;
+    signer.update(message.as_bytes()).map_err(|err| ErrType::ServerError.err(err, "Failed to sign url"))?;
+    let signature = signer.sign_to_vec().map_err(|err| ErrType::ServerError.err(err, "Failed to sign url"))?;
+
+    Ok(base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(signature))
+}
+
+/// Rejects expired URLs and signatures not matching `expected` in constant time
+fn verify_signature(expected: &str, expires: i64, signature: &str) -> AppResult<()> {
+    if expires < Utc::now().timestamp() {
+        return Err(ErrType::Unauthorized.msg("Signed URL expired"));
+    }
+
+    if expected.len() != signature.len() || !openssl::memcmp::eq(expected.as_bytes(), signature.as_bytes()) {
+        return Err(ErrType::Unauthorized.msg("Invalid URL signature"));
+    }
+
+    Ok(())
+}
+
 pub fn sha256_hex(bytes: &[u8]) -> AppResult<String> {
     let digest = openssl::sha::sha256(bytes);
     let hex = openssl::bn::BigNum::from_slice(&digest)
@@ -120,3 +745,59 @@ pub fn sha256_hex(bytes: &[u8]) -> AppResult<String> {
         .map_err(|err| ErrType::ServerError.err(err, "Failed to get hex for sha256 hash"))?;
     Ok(hex.to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &[u8] = b"content-signing-key";
+
+    fn sign(key: &[u8], user_id: &str, variant: &str, expires: i64) -> String {
+        sign_content(key, "space", user_id, "file", variant, expires).unwrap()
+    }
+
+    fn verify(user_id: &str, variant: &str, expires: i64, signature: &str) -> AppResult<()> {
+        verify_signature(&sign(KEY, user_id, variant, expires), expires, signature)
+    }
+
+    #[test]
+    fn content_signature_verifies() {
+        let expires = Utc::now().timestamp() + CONTENT_URL_EXPIRY;
+        let signature = sign(KEY, "user", "thumbnail", expires);
+        assert!(verify("user", "thumbnail", expires, &signature).is_ok());
+    }
+
+    #[test]
+    fn expired_content_signature_is_rejected() {
+        let expires = Utc::now().timestamp() - 1;
+        let signature = sign(KEY, "user", "thumbnail", expires);
+        assert!(verify("user", "thumbnail", expires, &signature).is_err());
+    }
+
+    #[test]
+    fn tampered_content_signature_is_rejected() {
+        let expires = Utc::now().timestamp() + CONTENT_URL_EXPIRY;
+        let signature = sign(KEY, "user", "thumbnail", expires);
+
+        let mut tampered = signature.clone();
+        let last = tampered.pop().unwrap();
+        tampered.push(if last == 'A' {
+            'B'
+        } else {
+            'A'
+        });
+        assert!(verify("user", "thumbnail", expires, &tampered).is_err());
+        assert!(verify("user", "thumbnail", expires, &signature[1..]).is_err());
+        assert!(verify("user", "thumbnail", expires, "").is_err());
+    }
+
+    #[test]
+    fn content_signature_is_bound_to_url_fields() {
+        let expires = Utc::now().timestamp() + CONTENT_URL_EXPIRY;
+        let signature = sign(KEY, "user", "thumbnail", expires);
+        assert!(verify("other", "thumbnail", expires, &signature).is_err());
+        assert!(verify("user", "original", expires, &signature).is_err());
+        assert!(verify("user", "thumbnail", expires + 60, &signature).is_err());
+        assert_ne!(signature, sign(b"other-key", "user", "thumbnail", expires));
+    }
+}
@@ -1,15 +1,18 @@
 use aws_config::Region;
 use aws_sdk_s3::{
     config::Credentials,
-    operation::head_object::HeadObjectOutput,
+    operation::abort_multipart_upload::AbortMultipartUploadError,
     presigning::PresigningConfig,
     primitives::ByteStream,
-    types::{Delete, ObjectIdentifier},
+    types::{CompletedMultipartUpload, CompletedPart, Delete, ObjectIdentifier},
     Client, Config,
 };
+use chrono::DateTime;
 
 use crate::{config::S3Config, AppResult, ErrType};
 
+use super::{ObjectHead, ObjectInfo, ObjectPage, ObjectStream, StorageBackend, UploadedPart, FOLDER_MARKER};
+
 /// Client for handling functions for S3
 /// storage providers
 pub struct S3Storage {
@@ -51,19 +54,30 @@ impl S3Storage {
 
         Self {
             client: Client::from_conf(client_config),
-            bucket_name: bucket_name,
+            bucket_name,
         }
     }
+}
+
+/// Max keys accepted by a single `DeleteObjects` request
+const DELETE_BATCH_SIZE: usize = 1000;
+
+impl Default for S3Storage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-    pub async fn create_folder(&self, path: &str) -> AppResult<()> {
+impl StorageBackend for S3Storage {
+    async fn create_folder(&self, path: &str) -> AppResult<()> {
         let stream = ByteStream::from("fd".as_bytes().to_vec());
         let builder = self.client.put_object().bucket(&self.bucket_name);
-        let result = builder.key(format!("{path}/fd.dat")).body(stream).send().await;
+        let result = builder.key(format!("{path}/{FOLDER_MARKER}")).body(stream).send().await;
         result.map_err(|err| ErrType::s3_put(err, "Failed to create dir"))?;
         Ok(())
     }
 
-    pub async fn generate_upload_signed_url(&self, path: &str) -> AppResult<String> {
+    async fn generate_upload_signed_url(&self, path: &str) -> AppResult<String> {
         let config = PresigningConfig::expires_in(std::time::Duration::from_secs(60 * 60))
             .map_err(|err| ErrType::S3Error.err(err, "Failed to generate presign config"))?;
 
@@ -80,7 +94,7 @@ impl S3Storage {
         Ok(request.uri().to_string())
     }
 
-    pub async fn generate_stream_signed_url(&self, path: &str) -> AppResult<String> {
+    async fn generate_stream_signed_url(&self, path: &str) -> AppResult<String> {
         let config = PresigningConfig::expires_in(std::time::Duration::from_secs(3 * 60 * 60))
             .map_err(|err| ErrType::S3Error.err(err, "Failed to generate presign config"))?;
 
@@ -96,7 +110,7 @@ impl S3Storage {
         Ok(request.uri().to_string())
     }
 
-    pub async fn upload_photo(&self, path_key: &str, bytes: Vec<u8>) -> AppResult<()> {
+    async fn upload_photo(&self, path_key: &str, bytes: Vec<u8>) -> AppResult<()> {
         let stream = ByteStream::from(bytes);
         let builder = self.client.put_object().bucket(&self.bucket_name);
         let result = builder.key(path_key).body(stream).send().await;
@@ -104,64 +118,261 @@ impl S3Storage {
         Ok(())
     }
 
-    pub async fn download_media(&self, path: &str) -> AppResult<ByteStream> {
+    async fn download_media(&self, path: &str) -> AppResult<ByteStream> {
         let builder = self.client.get_object().bucket(&self.bucket_name);
         let result = builder.key(path).send().await.map_err(|err| ErrType::s3_get(err, "Failed to download media"))?;
         Ok(result.body)
     }
 
-    pub async fn head_object(&self, path: &str) -> AppResult<HeadObjectOutput> {
-        self.client
+    async fn head_object(&self, path: &str) -> AppResult<ObjectHead> {
+        let head = self
+            .client
             .head_object()
             .bucket(&self.bucket_name)
             .key(path)
             .checksum_mode(aws_sdk_s3::types::ChecksumMode::Enabled)
             .send()
             .await
-            .map_err(|err| ErrType::s3_head(err, "Failed to head object"))
+            .map_err(|err| ErrType::s3_head(err, "Failed to head object"))?;
+
+        Ok(ObjectHead {
+            content_length: head.content_length,
+            checksum_sha256: head.checksum_sha256,
+            e_tag: head.e_tag,
+            last_modified: head.last_modified.and_then(|dt| DateTime::from_timestamp(dt.secs(), dt.subsec_nanos())),
+        })
+    }
+
+    async fn get_object(&self, path: &str, range: Option<&str>) -> AppResult<ObjectStream> {
+        let result = self
+            .client
+            .get_object()
+            .bucket(&self.bucket_name)
+            .key(path)
+            .set_range(range.map(str::to_owned))
+            .send()
+            .await;
+
+        let object = match result {
+            Ok(object) => object,
+            Err(err) if err.raw_response().is_some_and(|res| res.status().as_u16() == 416) => {
+                return Err(ErrType::RangeNotSatisfiable.msg("Requested range not satisfiable"));
+            }
+            Err(err) => return Err(ErrType::s3_get(err, "Failed to get object")),
+        };
+
+        Ok(ObjectStream {
+            content_length: object.content_length.unwrap_or_default(),
+            content_range: object.content_range,
+            content_type: object.content_type,
+            e_tag: object.e_tag,
+            last_modified: object.last_modified.and_then(|dt| DateTime::from_timestamp(dt.secs(), dt.subsec_nanos())),
+            body: object.body,
+        })
+    }
+
+    async fn delete_folder(&self, path: &str) -> AppResult<()> {
+        let mut continuation_token = None;
+        loop {
+            let page = self.list_objects(path, continuation_token).await?;
+            self.delete_keys(page.objects.into_iter().map(|obj| obj.key).collect()).await?;
+
+            continuation_token = page.continuation_token;
+            if continuation_token.is_none() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    async fn delete_key(&self, path: &str) -> AppResult<()> {
+        let builder = self.client.delete_object().bucket(&self.bucket_name);
+        let _ = builder.key(path).send().await.map_err(|err| ErrType::s3_delete(err, "Failed to delete object"))?;
+        Ok(())
+    }
+
+    async fn copy_object(&self, src_path: &str, dst_path: &str) -> AppResult<()> {
+        let src_key = src_path.split('/').map(|segment| urlencoding::encode(segment)).collect::<Vec<_>>().join("/");
+        let _ = self
+            .client
+            .copy_object()
+            .bucket(&self.bucket_name)
+            .copy_source(format!("{}/{src_key}", self.bucket_name))
+            .key(dst_path)
+            .send()
+            .await
+            .map_err(|err| ErrType::S3Error.err(err.into_service_error(), "Failed to copy object"))?;
+        Ok(())
     }
 
-    pub async fn delete_folder(&self, path: &str) -> AppResult<()> {
-        let objects = self
+    async fn list_objects(&self, prefix: &str, continuation_token: Option<String>) -> AppResult<ObjectPage> {
+        let page = self
             .client
             .list_objects_v2()
             .bucket(&self.bucket_name)
-            .prefix(path)
+            .prefix(prefix)
+            .set_continuation_token(continuation_token)
             .send()
             .await
             .map_err(|err| ErrType::s3_list_err(err, "Failed to list objects"))?;
 
-        let mut delete_objects = Vec::<ObjectIdentifier>::new();
-        for obj in objects.contents().iter() {
-            if let Some(key) = obj.key() {
+        let objects = page
+            .contents()
+            .iter()
+            .filter_map(|obj| {
+                obj.key().map(|key| ObjectInfo {
+                    key: key.to_owned(),
+                    size: obj.size.unwrap_or_default(),
+                    last_modified: obj
+                        .last_modified
+                        .and_then(|dt| DateTime::from_timestamp(dt.secs(), dt.subsec_nanos())),
+                })
+            })
+            .collect();
+
+        Ok(ObjectPage {
+            objects,
+            continuation_token: page.is_truncated.unwrap_or_default().then_some(page.next_continuation_token).flatten(),
+        })
+    }
+
+    async fn delete_keys(&self, keys: Vec<String>) -> AppResult<usize> {
+        let mut deleted = 0;
+        for batch in keys.chunks(DELETE_BATCH_SIZE) {
+            let mut delete_objects = Vec::<ObjectIdentifier>::with_capacity(batch.len());
+            for key in batch {
                 let id = ObjectIdentifier::builder()
                     .key(key)
                     .build()
                     .map_err(|err| ErrType::S3Error.err(err, "Failed to build object identifier"))?;
                 delete_objects.push(id);
             }
-        }
 
-        if !delete_objects.is_empty() {
             let delete = Delete::builder()
                 .set_objects(Some(delete_objects))
+                .quiet(true)
                 .build()
                 .map_err(|err| ErrType::S3Error.err(err, "Failed to create delete param"))?;
-            let _ = self
+            let result = self
                 .client
                 .delete_objects()
                 .bucket(&self.bucket_name)
                 .delete(delete)
                 .send()
                 .await
-                .map_err(|err| ErrType::S3Error.err(err.into_service_error(), "Failed to delete folder objects"))?;
+                .map_err(|err| ErrType::S3Error.err(err.into_service_error(), "Failed to delete objects"))?;
+
+            if let Some(err) = result.errors().first() {
+                return Err(ErrType::S3Error.msg(format!(
+                    "Failed to delete {} objects, {:?}: {:?}",
+                    result.errors().len(),
+                    err.key(),
+                    err.message()
+                )));
+            }
+            deleted += batch.len();
         }
-        Ok(())
+
+        Ok(deleted)
     }
 
-    pub async fn delete_key(&self, path: &str) -> AppResult<()> {
-        let builder = self.client.delete_object().bucket(&self.bucket_name);
-        let _ = builder.key(path).send().await.map_err(|err| ErrType::s3_delete(err, "Failed to delete object"))?;
+    async fn create_multipart_upload(&self, path: &str) -> AppResult<String> {
+        let upload = self
+            .client
+            .create_multipart_upload()
+            .bucket(&self.bucket_name)
+            .key(path)
+            .send()
+            .await
+            .map_err(|err| ErrType::S3Error.err(err.into_service_error(), "Failed to create multipart upload"))?;
+
+        upload.upload_id.ok_or(ErrType::S3Error.msg("Missing multipart upload id"))
+    }
+
+    async fn generate_upload_part_signed_url(
+        &self,
+        path: &str,
+        upload_id: &str,
+        part_number: i32,
+    ) -> AppResult<String> {
+        let config = PresigningConfig::expires_in(std::time::Duration::from_secs(60 * 60))
+            .map_err(|err| ErrType::S3Error.err(err, "Failed to generate presign config"))?;
+
+        let request = self
+            .client
+            .upload_part()
+            .bucket(&self.bucket_name)
+            .key(path)
+            .upload_id(upload_id)
+            .part_number(part_number)
+            .presigned(config)
+            .await
+            .map_err(|err| ErrType::S3Error.err(err.into_service_error(), "Failed to generate part presigned URL"))?;
+
+        Ok(request.uri().to_string())
+    }
+
+    async fn list_parts(&self, path: &str, upload_id: &str) -> AppResult<Vec<UploadedPart>> {
+        let mut parts = Vec::new();
+        let mut marker: Option<String> = None;
+
+        loop {
+            let page = self
+                .client
+                .list_parts()
+                .bucket(&self.bucket_name)
+                .key(path)
+                .upload_id(upload_id)
+                .set_part_number_marker(marker)
+                .send()
+                .await
+                .map_err(|err| ErrType::S3Error.err(err.into_service_error(), "Failed to list parts"))?;
+
+            for part in page.parts() {
+                if let Some(part_number) = part.part_number {
+                    parts.push(UploadedPart {
+                        part_number,
+                        size: part.size.unwrap_or_default(),
+                        e_tag: part.e_tag.clone(),
+                    });
+                }
+            }
+
+            marker = page.next_part_number_marker;
+            if !page.is_truncated.unwrap_or_default() || marker.is_none() {
+                break;
+            }
+        }
+
+        Ok(parts)
+    }
+
+    async fn complete_multipart_upload(&self, path: &str, upload_id: &str, parts: Vec<UploadedPart>) -> AppResult<()> {
+        let parts = parts
+            .into_iter()
+            .map(|part| CompletedPart::builder().part_number(part.part_number).set_e_tag(part.e_tag).build())
+            .collect::<Vec<_>>();
+
+        let _ = self
+            .client
+            .complete_multipart_upload()
+            .bucket(&self.bucket_name)
+            .key(path)
+            .upload_id(upload_id)
+            .multipart_upload(CompletedMultipartUpload::builder().set_parts(Some(parts)).build())
+            .send()
+            .await
+            .map_err(|err| ErrType::S3Error.err(err.into_service_error(), "Failed to complete multipart upload"))?;
         Ok(())
     }
+
+    async fn abort_multipart_upload(&self, path: &str, upload_id: &str) -> AppResult<()> {
+        let result =
+            self.client.abort_multipart_upload().bucket(&self.bucket_name).key(path).upload_id(upload_id).send().await;
+
+        match result.map_err(|err| err.into_service_error()) {
+            Ok(_) | Err(AbortMultipartUploadError::NoSuchUpload(_)) => Ok(()),
+            Err(err) => Err(ErrType::S3Error.err(err, "Failed to abort multipart upload")),
+        }
+    }
 }
@@ -0,0 +1,253 @@
+use chrono::{DateTime, Utc};
+use lib_core::{AppError, AppResult, ErrType};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::{Datastore, Keyset};
+
+#[derive(Debug, ToSchema, Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    CreateAlbum,
+    UpdateAlbum,
+    DeleteAlbum,
+    LinkAlbumFiles,
+    UnlinkAlbumFiles,
+    MoveAlbumFiles,
+    ReorderAlbumFiles,
+    UploadFile,
+    DeleteFiles,
+    RestoreFiles,
+    RestoreAlbum,
+    EmptyTrash,
+    CopyFiles,
+    MoveFiles,
+    CreateTag,
+    UpdateTag,
+    MergeTag,
+    DeleteTag,
+    TagFiles,
+    UntagFiles,
+    CreateComment,
+    UpdateComment,
+    DeleteComment,
+    AddMember,
+    UpdateMemberRole,
+    RemoveMember,
+    LeaveSpace,
+    /// Recorded in the deleted space, out of reach of the audit log endpoint once members are gone
+    DeleteSpace,
+    ReconcileSpace,
+}
+impl AuditAction {
+    pub fn value(&self) -> i16 {
+        match self {
+            AuditAction::CreateAlbum => 0,
+            AuditAction::UpdateAlbum => 1,
+            AuditAction::DeleteAlbum => 2,
+            AuditAction::LinkAlbumFiles => 3,
+            AuditAction::UnlinkAlbumFiles => 4,
+            AuditAction::MoveAlbumFiles => 5,
+            AuditAction::ReorderAlbumFiles => 6,
+            AuditAction::UploadFile => 7,
+            AuditAction::DeleteFiles => 8,
+            AuditAction::RestoreFiles => 9,
+            AuditAction::RestoreAlbum => 10,
+            AuditAction::EmptyTrash => 11,
+            AuditAction::CopyFiles => 12,
+            AuditAction::MoveFiles => 13,
+            AuditAction::CreateTag => 14,
+            AuditAction::UpdateTag => 15,
+            AuditAction::MergeTag => 16,
+            AuditAction::DeleteTag => 17,
+            AuditAction::TagFiles => 18,
+            AuditAction::UntagFiles => 19,
+            AuditAction::CreateComment => 20,
+            AuditAction::UpdateComment => 21,
+            AuditAction::DeleteComment => 22,
+            AuditAction::AddMember => 23,
+            AuditAction::UpdateMemberRole => 24,
+            AuditAction::RemoveMember => 25,
+            AuditAction::LeaveSpace => 26,
+            AuditAction::DeleteSpace => 27,
+            AuditAction::ReconcileSpace => 28,
+        }
+    }
+}
+impl TryFrom<i16> for AuditAction {
+    type Error = AppError;
+
+    fn try_from(value: i16) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(AuditAction::CreateAlbum),
+            1 => Ok(AuditAction::UpdateAlbum),
+            2 => Ok(AuditAction::DeleteAlbum),
+            3 => Ok(AuditAction::LinkAlbumFiles),
+            4 => Ok(AuditAction::UnlinkAlbumFiles),
+            5 => Ok(AuditAction::MoveAlbumFiles),
+            6 => Ok(AuditAction::ReorderAlbumFiles),
+            7 => Ok(AuditAction::UploadFile),
+            8 => Ok(AuditAction::DeleteFiles),
+            9 => Ok(AuditAction::RestoreFiles),
+            10 => Ok(AuditAction::RestoreAlbum),
+            11 => Ok(AuditAction::EmptyTrash),
+            12 => Ok(AuditAction::CopyFiles),
+            13 => Ok(AuditAction::MoveFiles),
+            14 => Ok(AuditAction::CreateTag),
+            15 => Ok(AuditAction::UpdateTag),
+            16 => Ok(AuditAction::MergeTag),
+            17 => Ok(AuditAction::DeleteTag),
+            18 => Ok(AuditAction::TagFiles),
+            19 => Ok(AuditAction::UntagFiles),
+            20 => Ok(AuditAction::CreateComment),
+            21 => Ok(AuditAction::UpdateComment),
+            22 => Ok(AuditAction::DeleteComment),
+            23 => Ok(AuditAction::AddMember),
+            24 => Ok(AuditAction::UpdateMemberRole),
+            25 => Ok(AuditAction::RemoveMember),
+            26 => Ok(AuditAction::LeaveSpace),
+            27 => Ok(AuditAction::DeleteSpace),
+            28 => Ok(AuditAction::ReconcileSpace),
+            x => Err(ErrType::DbError.msg(format!("Invalid audit action literal: {x}"))),
+        }
+    }
+}
+impl<'a> tokio_postgres::types::FromSql<'a> for AuditAction {
+    fn from_sql(
+        ty: &tokio_postgres::types::Type,
+        raw: &'a [u8],
+    ) -> Result<Self, Box<dyn std::error::Error + Sync + Send>> {
+        let action_literal = i16::from_sql(ty, raw)?;
+        let action = AuditAction::try_from(action_literal)?;
+        Ok(action)
+    }
+
+    fn accepts(ty: &tokio_postgres::types::Type) -> bool {
+        matches!(*ty, tokio_postgres::types::Type::INT2)
+    }
+}
+
+pub struct AuditEntry {
+    pub id: Uuid,
+    pub created_at: DateTime<Utc>,
+
+    pub space_id: Uuid,
+    pub actor_id: Uuid,
+    pub req_id: String,
+    pub action: AuditAction,
+
+    /// Album, tag, comment, member or job acted on
+    pub target_id: Option<Uuid>,
+    pub file_ids: Vec<Uuid>,
+}
+impl TryFrom<tokio_postgres::Row> for AuditEntry {
+    type Error = tokio_postgres::error::Error;
+
+    fn try_from(value: tokio_postgres::Row) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: value.try_get(0)?,
+            created_at: value.try_get(1)?,
+            space_id: value.try_get(2)?,
+            actor_id: value.try_get(3)?,
+            req_id: value.try_get(4)?,
+            action: value.try_get(5)?,
+            target_id: value.try_get(6)?,
+            file_ids: value.try_get(7)?,
+        })
+    }
+}
+
+/// Mutation to append to the audit log
+pub struct AuditRecord<'a> {
+    pub space_id: Uuid,
+    pub actor_id: Uuid,
+    pub req_id: &'a str,
+    pub action: AuditAction,
+    pub target_id: Option<Uuid>,
+    pub file_ids: &'a [Uuid],
+}
+
+/// Audit filters combined with AND, unset filters match every entry
+#[derive(Default)]
+pub struct AuditFilter {
+    pub actor_id: Option<Uuid>,
+    pub action: Option<AuditAction>,
+
+    /// Entries acting on the object, as target or among their files
+    pub target_id: Option<Uuid>,
+
+    /// `created_at` range, end exclusive
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+}
+
+pub trait AuditDs: Send + Sync {
+    fn record_audit(&self, record: AuditRecord<'_>) -> impl Future<Output = AppResult<()>> + Send;
+
+    /// Audit entries of the space matching `filter`, latest first
+    fn list_audit_entries(
+        &self,
+        space_id: &Uuid,
+        filter: &AuditFilter,
+        after: Option<Keyset<DateTime<Utc>>>,
+        limit: i64,
+    ) -> impl Future<Output = AppResult<Vec<AuditEntry>>> + Send;
+}
+
+impl AuditDs for Datastore {
+    async fn record_audit(&self, record: AuditRecord<'_>) -> AppResult<()> {
+        self.execute(
+            &self.audit_stmts.insert,
+            &[
+                &Uuid::now_v7(),
+                &record.space_id,
+                &record.actor_id,
+                &record.req_id,
+                &record.action.value(),
+                &record.target_id,
+                &record.file_ids,
+            ],
+        )
+        .await
+        .map_err(|err| ErrType::DbError.err(err, "Failed to record audit entry"))?;
+
+        Ok(())
+    }
+
+    async fn list_audit_entries(
+        &self,
+        space_id: &Uuid,
+        filter: &AuditFilter,
+        after: Option<Keyset<DateTime<Utc>>>,
+        limit: i64,
+    ) -> AppResult<Vec<AuditEntry>> {
+        let (created_at, id) = Keyset::desc(after);
+        let action = filter.action.map(|action| action.value());
+        let rows = self
+            .query(
+                &self.audit_stmts.list,
+                &[
+                    space_id,
+                    &created_at,
+                    &id,
+                    &limit,
+                    &filter.actor_id,
+                    &action,
+                    &filter.target_id,
+                    &filter.created_after,
+                    &filter.created_before,
+                ],
+            )
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to get audit entries"))?;
+
+        let size = rows.len();
+        rows.into_iter().try_fold(Vec::with_capacity(size), |mut acc, row| {
+            let entry =
+                AuditEntry::try_from(row).map_err(|err| ErrType::DbError.err(err, "Failed to parse audit entries"))?;
+            acc.push(entry);
+            Ok(acc)
+        })
+    }
+}
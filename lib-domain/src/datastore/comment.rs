@@ -0,0 +1,136 @@
+use chrono::{DateTime, Utc};
+use lib_core::{AppResult, ErrType};
+use uuid::Uuid;
+
+use super::{Datastore, Keyset};
+
+pub struct Comment {
+    pub id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+
+    pub media_file_id: Uuid,
+    pub user_id: Uuid,
+    pub body: String,
+}
+impl TryFrom<tokio_postgres::Row> for Comment {
+    type Error = tokio_postgres::error::Error;
+
+    fn try_from(value: tokio_postgres::Row) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: value.try_get(0)?,
+            created_at: value.try_get(1)?,
+            updated_at: value.try_get(2)?,
+            media_file_id: value.try_get(3)?,
+            user_id: value.try_get(4)?,
+            body: value.try_get(5)?,
+        })
+    }
+}
+
+pub trait CommentDs: Send + Sync {
+    /// Comments file of the space out of trash, `None` if the file is not found
+    fn create_comment(
+        &self,
+        space_id: &Uuid,
+        file_id: &Uuid,
+        user_id: &Uuid,
+        body: &str,
+    ) -> impl Future<Output = AppResult<Option<Comment>>> + Send;
+
+    /// Comment of a file out of trash, comments of trashed files are kept until restored or purged
+    fn get_comment(
+        &self,
+        space_id: &Uuid,
+        file_id: &Uuid,
+        comment_id: &Uuid,
+    ) -> impl Future<Output = AppResult<Option<Comment>>> + Send;
+
+    fn update_comment(&self, comment_id: &Uuid, body: &str) -> impl Future<Output = AppResult<Comment>> + Send;
+
+    fn delete_comment(&self, comment_id: &Uuid) -> impl Future<Output = AppResult<()>> + Send;
+
+    /// Comments of the file oldest first
+    fn list_comments(
+        &self,
+        space_id: &Uuid,
+        file_id: &Uuid,
+        after: Option<Keyset<DateTime<Utc>>>,
+        limit: i64,
+    ) -> impl Future<Output = AppResult<Vec<Comment>>> + Send;
+}
+
+impl CommentDs for Datastore {
+    async fn create_comment(
+        &self,
+        space_id: &Uuid,
+        file_id: &Uuid,
+        user_id: &Uuid,
+        body: &str,
+    ) -> AppResult<Option<Comment>> {
+        let rows = self
+            .query(&self.comment_stmts.insert, &[&Uuid::now_v7(), file_id, space_id, user_id, &body])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to create comment"))?;
+
+        match rows.into_iter().next() {
+            Some(row) => Comment::try_from(row)
+                .map(Some)
+                .map_err(|err| ErrType::DbError.err(err, "Failed to parse created comment")),
+            None => Ok(None),
+        }
+    }
+
+    async fn get_comment(&self, space_id: &Uuid, file_id: &Uuid, comment_id: &Uuid) -> AppResult<Option<Comment>> {
+        let rows = self
+            .query(&self.comment_stmts.get, &[comment_id, file_id, space_id])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to get comment"))?;
+
+        match rows.into_iter().next() {
+            Some(row) => {
+                Comment::try_from(row).map(Some).map_err(|err| ErrType::DbError.err(err, "Failed to parse comment"))
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn update_comment(&self, comment_id: &Uuid, body: &str) -> AppResult<Comment> {
+        let row = self
+            .query_one(&self.comment_stmts.update, &[comment_id, &body])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to update comment"))?;
+
+        Comment::try_from(row).map_err(|err| ErrType::DbError.err(err, "Failed to parse updated comment"))
+    }
+
+    async fn delete_comment(&self, comment_id: &Uuid) -> AppResult<()> {
+        self.execute(&self.comment_stmts.delete, &[comment_id])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to delete comment"))?;
+
+        Ok(())
+    }
+
+    async fn list_comments(
+        &self,
+        space_id: &Uuid,
+        file_id: &Uuid,
+        after: Option<Keyset<DateTime<Utc>>>,
+        limit: i64,
+    ) -> AppResult<Vec<Comment>> {
+        let (created_at, id) = Keyset::asc(after);
+        let rows = self
+            .query(&self.comment_stmts.list, &[file_id, space_id, &created_at, &id, &limit])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to get comments"))?;
+
+        let size = rows.len();
+        rows.into_iter().try_fold(Vec::with_capacity(size), |mut acc, row| {
+            let comment =
+                Comment::try_from(row).map_err(|err| ErrType::DbError.err(err, "Failed to parse comments"))?;
+            acc.push(comment);
+            Ok(acc)
+        })
+    }
+}
@@ -0,0 +1,92 @@
+use chrono::{DateTime, Utc};
+use lib_core::{AppResult, ErrType};
+use uuid::Uuid;
+
+use super::{storage::FileMeta, Datastore, Keyset};
+
+/// Favorite file with the space it belongs to
+pub struct FavoriteFileMeta {
+    pub file: FileMeta,
+    pub space_id: Uuid,
+    pub favorited_at: DateTime<Utc>,
+}
+impl TryFrom<tokio_postgres::Row> for FavoriteFileMeta {
+    type Error = tokio_postgres::error::Error;
+
+    fn try_from(value: tokio_postgres::Row) -> Result<Self, Self::Error> {
+        let space_id = value.try_get(10)?;
+        let favorited_at = value.try_get(11)?;
+        Ok(Self {
+            file: FileMeta::try_from(value)?,
+            space_id,
+            favorited_at,
+        })
+    }
+}
+
+pub trait FavoriteDs: Send + Sync {
+    /// Stars file for `user_id`, `false` if the file is not in the space
+    fn add_favorite(
+        &self,
+        user_id: &Uuid,
+        space_id: &Uuid,
+        file_id: &Uuid,
+    ) -> impl Future<Output = AppResult<bool>> + Send;
+
+    fn remove_favorite(
+        &self,
+        user_id: &Uuid,
+        space_id: &Uuid,
+        file_id: &Uuid,
+    ) -> impl Future<Output = AppResult<()>> + Send;
+
+    /// Favorites of `user_id` in spaces they belong to, or `space_id` only, latest starred first
+    fn list_favorites(
+        &self,
+        user_id: &Uuid,
+        space_id: Option<Uuid>,
+        after: Option<Keyset<DateTime<Utc>>>,
+        limit: i64,
+    ) -> impl Future<Output = AppResult<Vec<FavoriteFileMeta>>> + Send;
+}
+
+impl FavoriteDs for Datastore {
+    async fn add_favorite(&self, user_id: &Uuid, space_id: &Uuid, file_id: &Uuid) -> AppResult<bool> {
+        let rows = self
+            .query(&self.favorite_stmts.insert, &[user_id, space_id, file_id])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to add favorite"))?;
+
+        Ok(!rows.is_empty())
+    }
+
+    async fn remove_favorite(&self, user_id: &Uuid, space_id: &Uuid, file_id: &Uuid) -> AppResult<()> {
+        self.execute(&self.favorite_stmts.delete, &[user_id, space_id, file_id])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to remove favorite"))?;
+
+        Ok(())
+    }
+
+    async fn list_favorites(
+        &self,
+        user_id: &Uuid,
+        space_id: Option<Uuid>,
+        after: Option<Keyset<DateTime<Utc>>>,
+        limit: i64,
+    ) -> AppResult<Vec<FavoriteFileMeta>> {
+        let (favorited_at, id) = Keyset::desc(after);
+        let rows = self
+            .query(&self.favorite_stmts.list, &[user_id, &space_id, &favorited_at, &id, &limit])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to get favorites"))?;
+
+        let size = rows.len();
+        rows.into_iter().try_fold(Vec::with_capacity(size), |mut acc, row| {
+            let f = FavoriteFileMeta::try_from(row)
+                .map_err(|err| ErrType::DbError.err(err, "Failed to parse favorite files"))?;
+            acc.push(f);
+            Ok(acc)
+        })
+    }
+}
@@ -0,0 +1,72 @@
+use lib_core::{AppResult, ErrType};
+use uuid::Uuid;
+
+use super::{storage::GeoBounds, Datastore};
+
+/// Located files of one grid cell
+pub struct MapCluster {
+    pub count: i64,
+
+    /// Mean position of the files
+    pub lat: f64,
+    pub lon: f64,
+
+    /// Latest taken file of the cluster
+    pub file_id: Uuid,
+
+    /// Extent of the files, listing files within it expands the cluster
+    pub bounds: GeoBounds,
+}
+impl TryFrom<tokio_postgres::Row> for MapCluster {
+    type Error = tokio_postgres::error::Error;
+
+    fn try_from(value: tokio_postgres::Row) -> Result<Self, Self::Error> {
+        Ok(Self {
+            count: value.try_get(0)?,
+            lat: value.try_get(1)?,
+            lon: value.try_get(2)?,
+            file_id: value.try_get(3)?,
+            bounds: GeoBounds {
+                min_lat: value.try_get(4)?,
+                max_lat: value.try_get(5)?,
+                min_lon: value.try_get(6)?,
+                max_lon: value.try_get(7)?,
+            },
+        })
+    }
+}
+
+pub trait GeoDs: Send + Sync {
+    /// Located files within `bounds` grouped into square cells of `cell_size` degrees, largest first
+    fn list_map_clusters(
+        &self,
+        space_id: &Uuid,
+        bounds: &GeoBounds,
+        cell_size: f64,
+    ) -> impl Future<Output = AppResult<Vec<MapCluster>>> + Send;
+}
+
+impl GeoDs for Datastore {
+    async fn list_map_clusters(
+        &self,
+        space_id: &Uuid,
+        bounds: &GeoBounds,
+        cell_size: f64,
+    ) -> AppResult<Vec<MapCluster>> {
+        let rows = self
+            .query(
+                &self.geo_stmts.list_map_clusters,
+                &[space_id, &bounds.min_lat, &bounds.max_lat, &bounds.min_lon, &bounds.max_lon, &cell_size],
+            )
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to get map clusters"))?;
+
+        let size = rows.len();
+        rows.into_iter().try_fold(Vec::with_capacity(size), |mut acc, row| {
+            let cluster =
+                MapCluster::try_from(row).map_err(|err| ErrType::DbError.err(err, "Failed to parse map cluster"))?;
+            acc.push(cluster);
+            Ok(acc)
+        })
+    }
+}
@@ -0,0 +1,211 @@
+use chrono::{DateTime, Utc};
+use lib_core::{AppError, AppResult, ErrType};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::Datastore;
+
+#[derive(Debug, ToSchema, Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    SpacePurge,
+    Reconcile,
+}
+impl JobKind {
+    pub fn value(&self) -> i16 {
+        match self {
+            JobKind::SpacePurge => 0,
+            JobKind::Reconcile => 1,
+        }
+    }
+}
+impl TryFrom<i16> for JobKind {
+    type Error = AppError;
+
+    fn try_from(value: i16) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(JobKind::SpacePurge),
+            1 => Ok(JobKind::Reconcile),
+            x => Err(ErrType::DbError.msg(format!("Invalid job kind literal: {x}"))),
+        }
+    }
+}
+impl<'a> tokio_postgres::types::FromSql<'a> for JobKind {
+    fn from_sql(
+        ty: &tokio_postgres::types::Type,
+        raw: &'a [u8],
+    ) -> Result<Self, Box<dyn std::error::Error + Sync + Send>> {
+        let kind_literal = i16::from_sql(ty, raw)?;
+        let kind = JobKind::try_from(kind_literal)?;
+        Ok(kind)
+    }
+
+    fn accepts(ty: &tokio_postgres::types::Type) -> bool {
+        matches!(*ty, tokio_postgres::types::Type::INT2)
+    }
+}
+
+#[derive(Debug, ToSchema, Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+}
+impl JobStatus {
+    pub fn value(&self) -> i16 {
+        match self {
+            JobStatus::Pending => 0,
+            JobStatus::Running => 1,
+            JobStatus::Completed => 2,
+            JobStatus::Failed => 3,
+        }
+    }
+}
+impl TryFrom<i16> for JobStatus {
+    type Error = AppError;
+
+    fn try_from(value: i16) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(JobStatus::Pending),
+            1 => Ok(JobStatus::Running),
+            2 => Ok(JobStatus::Completed),
+            3 => Ok(JobStatus::Failed),
+            x => Err(ErrType::DbError.msg(format!("Invalid job status literal: {x}"))),
+        }
+    }
+}
+impl<'a> tokio_postgres::types::FromSql<'a> for JobStatus {
+    fn from_sql(
+        ty: &tokio_postgres::types::Type,
+        raw: &'a [u8],
+    ) -> Result<Self, Box<dyn std::error::Error + Sync + Send>> {
+        let status_literal = i16::from_sql(ty, raw)?;
+        let status = JobStatus::try_from(status_literal)?;
+        Ok(status)
+    }
+
+    fn accepts(ty: &tokio_postgres::types::Type) -> bool {
+        matches!(*ty, tokio_postgres::types::Type::INT2)
+    }
+}
+
+/// Long running job, progress is reported by the running task
+pub struct Job {
+    pub id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+
+    pub user_id: Uuid,
+    pub space_id: Uuid,
+    pub kind: JobKind,
+    pub status: JobStatus,
+
+    /// Number of items processed so far
+    pub progress: i64,
+    pub report: serde_json::Value,
+    pub error: Option<String>,
+}
+impl TryFrom<tokio_postgres::Row> for Job {
+    type Error = tokio_postgres::Error;
+
+    fn try_from(value: tokio_postgres::Row) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: value.try_get(0)?,
+            created_at: value.try_get(1)?,
+            updated_at: value.try_get(2)?,
+            user_id: value.try_get(3)?,
+            space_id: value.try_get(4)?,
+            kind: value.try_get(5)?,
+            status: value.try_get(6)?,
+            progress: value.try_get(7)?,
+            report: value.try_get(8)?,
+            error: value.try_get(9)?,
+        })
+    }
+}
+
+pub trait JobDs: Send + Sync {
+    fn insert_job(&self, user_id: &Uuid, space_id: &Uuid, kind: JobKind)
+        -> impl Future<Output = AppResult<Job>> + Send;
+    fn get_job(&self, user_id: &Uuid, job_id: &Uuid) -> impl Future<Output = AppResult<Option<Job>>> + Send;
+    fn update_job_progress(
+        &self,
+        job_id: &Uuid,
+        status: JobStatus,
+        progress: i64,
+        report: &serde_json::Value,
+    ) -> impl Future<Output = AppResult<()>> + Send;
+    fn fail_job(&self, job_id: &Uuid, error: String) -> impl Future<Output = AppResult<()>> + Send;
+
+    /// Jobs of `kind` in any of `statuses` oldest first, across every user
+    fn list_jobs_by_status(
+        &self,
+        kind: JobKind,
+        statuses: &[JobStatus],
+    ) -> impl Future<Output = AppResult<Vec<Job>>> + Send;
+}
+
+impl JobDs for Datastore {
+    async fn insert_job(&self, user_id: &Uuid, space_id: &Uuid, kind: JobKind) -> AppResult<Job> {
+        let row = self
+            .query_one(&self.job_stmts.insert, &[&Uuid::now_v7(), user_id, space_id, &kind.value()])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to insert job"))?;
+
+        Job::try_from(row).map_err(|err| ErrType::DbError.err(err, "Failed to parse inserted job"))
+    }
+
+    async fn get_job(&self, user_id: &Uuid, job_id: &Uuid) -> AppResult<Option<Job>> {
+        let rows = self
+            .query(&self.job_stmts.get, &[job_id, user_id])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to get job"))?;
+
+        match rows.into_iter().next() {
+            Some(row) => Job::try_from(row).map(Some).map_err(|err| ErrType::DbError.err(err, "Failed to parse job")),
+            None => Ok(None),
+        }
+    }
+
+    async fn update_job_progress(
+        &self,
+        job_id: &Uuid,
+        status: JobStatus,
+        progress: i64,
+        report: &serde_json::Value,
+    ) -> AppResult<()> {
+        let _ = self
+            .query(&self.job_stmts.update_progress, &[job_id, &status.value(), &progress, report])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to update job progress"))?;
+
+        Ok(())
+    }
+
+    async fn fail_job(&self, job_id: &Uuid, error: String) -> AppResult<()> {
+        let _ = self
+            .query(&self.job_stmts.fail, &[job_id, &JobStatus::Failed.value(), &error])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to mark job failed"))?;
+
+        Ok(())
+    }
+
+    async fn list_jobs_by_status(&self, kind: JobKind, statuses: &[JobStatus]) -> AppResult<Vec<Job>> {
+        let statuses = statuses.iter().map(|status| status.value()).collect::<Vec<_>>();
+        let rows = self
+            .query(&self.job_stmts.list_by_status, &[&kind.value(), &statuses])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to get jobs by status"))?;
+
+        let size = rows.len();
+        rows.into_iter().try_fold(Vec::with_capacity(size), |mut acc, row| {
+            let job = Job::try_from(row).map_err(|err| ErrType::DbError.err(err, "Failed to parse jobs"))?;
+            acc.push(job);
+            Ok(acc)
+        })
+    }
+}
@@ -1,19 +1,61 @@
+use std::{
+    ops::Deref,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize},
+        Arc,
+    },
+};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use deadpool_postgres::{Manager, ManagerConfig, Object, Pool, PoolError, RecyclingMethod, Runtime};
 use lib_core::config;
+use statements::Stmt;
+use tokio_postgres::{types::ToSql, Row, Statement};
+use uuid::Uuid;
 
+pub mod audit;
+pub mod comment;
+pub mod favorite;
+pub mod geo;
+pub mod job;
 pub mod native_app;
+pub mod search;
 pub mod space;
 pub mod storage;
+pub mod tag;
+pub mod transaction;
+pub mod trash;
+pub mod upload_session;
+pub mod usage;
 pub mod user;
 pub mod user_space;
 
 pub struct Datastore {
-    db: tokio_postgres::Client,
-    user_stmts: statements::UserStatements,
-    space_stmts: statements::SpaceStatements,
-    default_space_stmts: statements::DefaultSpaceStatements,
-    user_space_stmts: statements::UsersSpacesStatements,
-    storage_stmts: statements::StorageStatements,
-    native_app_stmts: statements::NativeAppStatements,
+    pool: Pool,
+
+    /// Open transaction every call runs in, see [`transaction::TransactionDs`]
+    tx: Option<Arc<TxConn>>,
+
+    /// Savepoint of a nested transaction
+    savepoint: Option<String>,
+
+    user_stmts: Arc<statements::UserStatements>,
+    space_stmts: Arc<statements::SpaceStatements>,
+    default_space_stmts: Arc<statements::DefaultSpaceStatements>,
+    user_space_stmts: Arc<statements::UsersSpacesStatements>,
+    storage_stmts: Arc<statements::StorageStatements>,
+    native_app_stmts: Arc<statements::NativeAppStatements>,
+    upload_session_stmts: Arc<statements::UploadSessionStatements>,
+    job_stmts: Arc<statements::JobStatements>,
+    usage_stmts: Arc<statements::UsageStatements>,
+    trash_stmts: Arc<statements::TrashStatements>,
+    search_stmts: Arc<statements::SearchStatements>,
+    geo_stmts: Arc<statements::GeoStatements>,
+    favorite_stmts: Arc<statements::FavoriteStatements>,
+    tag_stmts: Arc<statements::TagStatements>,
+    comment_stmts: Arc<statements::CommentStatements>,
+    audit_stmts: Arc<statements::AuditStatements>,
 }
 
 impl Datastore {
@@ -22,31 +64,239 @@ impl Datastore {
 
         lib_migrations::migrate_schema(&db_config.url).await;
 
-        let (db, connection) = tokio_postgres::connect(&db_config.url, tokio_postgres::NoTls)
-            .await
-            .expect("Failed to connect to postgres");
+        let mut pg_config = tokio_postgres::Config::from_str(&db_config.url).expect("Invalid postgres url");
+        pg_config.connect_timeout(db_config.connect_timeout);
+
+        // connections are checked before reuse, dead ones are dropped and replaced on demand
+        let manager = Manager::from_config(
+            pg_config,
+            tokio_postgres::NoTls,
+            ManagerConfig {
+                recycling_method: RecyclingMethod::Verified,
+            },
+        );
+        let pool = Pool::builder(manager)
+            .max_size(db_config.pool_size)
+            .wait_timeout(Some(db_config.pool_timeout))
+            .create_timeout(Some(db_config.connect_timeout))
+            .recycle_timeout(Some(db_config.connect_timeout))
+            .runtime(Runtime::Tokio1)
+            .build()
+            .expect("Failed to build postgres pool");
+
+        let datastore = Self {
+            pool,
+            tx: None,
+            savepoint: None,
+            user_stmts: Arc::new(statements::UserStatements::new()),
+            space_stmts: Arc::new(statements::SpaceStatements::new()),
+            default_space_stmts: Arc::new(statements::DefaultSpaceStatements::new()),
+            user_space_stmts: Arc::new(statements::UsersSpacesStatements::new()),
+            storage_stmts: Arc::new(statements::StorageStatements::new()),
+            native_app_stmts: Arc::new(statements::NativeAppStatements::new()),
+            upload_session_stmts: Arc::new(statements::UploadSessionStatements::new()),
+            job_stmts: Arc::new(statements::JobStatements::new()),
+            usage_stmts: Arc::new(statements::UsageStatements::new()),
+            trash_stmts: Arc::new(statements::TrashStatements::new()),
+            search_stmts: Arc::new(statements::SearchStatements::new()),
+            geo_stmts: Arc::new(statements::GeoStatements::new()),
+            favorite_stmts: Arc::new(statements::FavoriteStatements::new()),
+            tag_stmts: Arc::new(statements::TagStatements::new()),
+            comment_stmts: Arc::new(statements::CommentStatements::new()),
+            audit_stmts: Arc::new(statements::AuditStatements::new()),
+        };
+        datastore.prepare_all().await;
+
+        datastore
+    }
+
+    /// Prepares every statement once so invalid SQL fails startup instead of its first request
+    async fn prepare_all(&self) {
+        let client = self.pool.get().await.expect("Failed to get postgres connection");
+        let groups = [
+            self.user_stmts.all(),
+            self.space_stmts.all(),
+            self.default_space_stmts.all(),
+            self.user_space_stmts.all(),
+            self.storage_stmts.all(),
+            self.native_app_stmts.all(),
+            self.upload_session_stmts.all(),
+            self.job_stmts.all(),
+            self.usage_stmts.all(),
+            self.trash_stmts.all(),
+            self.search_stmts.all(),
+            self.geo_stmts.all(),
+            self.favorite_stmts.all(),
+            self.tag_stmts.all(),
+            self.comment_stmts.all(),
+            self.audit_stmts.all(),
+        ];
 
-        tokio::spawn(async move {
-            if let Err(err) = connection.await {
-                eprintln!("Pg connection error: {err}");
+        for stmt in groups.iter().flatten() {
+            if let Err(err) = client.prepare_typed_cached(stmt.sql, &stmt.types).await {
+                panic!("Failed to prepare statement {}: {err}", stmt.sql);
             }
-        });
+        }
+    }
+
+    /// Same datastore bound to transaction `tx`
+    fn with_tx(&self, tx: Arc<TxConn>, savepoint: Option<String>) -> Self {
+        Self {
+            pool: self.pool.clone(),
+            tx: Some(tx),
+            savepoint,
+            user_stmts: self.user_stmts.clone(),
+            space_stmts: self.space_stmts.clone(),
+            default_space_stmts: self.default_space_stmts.clone(),
+            user_space_stmts: self.user_space_stmts.clone(),
+            storage_stmts: self.storage_stmts.clone(),
+            native_app_stmts: self.native_app_stmts.clone(),
+            upload_session_stmts: self.upload_session_stmts.clone(),
+            job_stmts: self.job_stmts.clone(),
+            usage_stmts: self.usage_stmts.clone(),
+            trash_stmts: self.trash_stmts.clone(),
+            search_stmts: self.search_stmts.clone(),
+            geo_stmts: self.geo_stmts.clone(),
+            favorite_stmts: self.favorite_stmts.clone(),
+            tag_stmts: self.tag_stmts.clone(),
+            comment_stmts: self.comment_stmts.clone(),
+            audit_stmts: self.audit_stmts.clone(),
+        }
+    }
+
+    /// Transaction connection or a pooled one with `stmt` prepared on it, cached per connection
+    async fn prepare(&self, stmt: &Stmt) -> Result<(Conn<'_>, Statement), PoolError> {
+        let client = match &self.tx {
+            Some(tx) => Conn::Tx(tx),
+            None => Conn::Pooled(Box::new(self.pool.get().await?)),
+        };
+        let statement = client.prepare_typed_cached(stmt.sql, &stmt.types).await?;
+        Ok((client, statement))
+    }
+
+    async fn query(&self, stmt: &Stmt, params: &[&(dyn ToSql + Sync)]) -> Result<Vec<Row>, PoolError> {
+        let (client, statement) = self.prepare(stmt).await?;
+        Ok(client.query(&statement, params).await?)
+    }
+
+    async fn query_one(&self, stmt: &Stmt, params: &[&(dyn ToSql + Sync)]) -> Result<Row, PoolError> {
+        let (client, statement) = self.prepare(stmt).await?;
+        Ok(client.query_one(&statement, params).await?)
+    }
+
+    async fn execute(&self, stmt: &Stmt, params: &[&(dyn ToSql + Sync)]) -> Result<u64, PoolError> {
+        let (client, statement) = self.prepare(stmt).await?;
+        Ok(client.execute(&statement, params).await?)
+    }
+}
+
+/// Keyset pagination position, listing continues after `(key, id)` in sort order
+pub struct Keyset<K> {
+    pub key: K,
+    pub id: Uuid,
+}
+
+/// First pages compare against a position before every row rather than skipping the keyset
+/// condition, so a single generic plan keeps using the keyset index for every page
+impl<K> Keyset<K> {
+    /// Key and id to list ascending after, the first page starts below every stored key
+    fn asc(after: Option<Self>) -> (K, Uuid)
+    where
+        K: MinKey,
+    {
+        after.map_or_else(|| (K::min_key(), Uuid::nil()), |keyset| (keyset.key, keyset.id))
+    }
+
+    /// Key and id to list descending after, the first page starts above every stored key
+    fn desc(after: Option<Self>) -> (K, Uuid)
+    where
+        K: MaxKey,
+    {
+        after.map_or_else(|| (K::max_key(), Uuid::max()), |keyset| (keyset.key, keyset.id))
+    }
+}
 
-        let user_stmts = statements::UserStatements::new(&db).await;
-        let space_stmts = statements::SpaceStatements::new(&db).await;
-        let default_space_stmts = statements::DefaultSpaceStatements::new(&db).await;
-        let user_space_stmts = statements::UsersSpacesStatements::new(&db).await;
-        let storage_stmts = statements::StorageStatements::new(&db).await;
-        let native_app_stmts = statements::NativeAppStatements::new(&db).await;
+/// Key sorting before every stored key
+trait MinKey {
+    fn min_key() -> Self;
+}
+impl MinKey for String {
+    fn min_key() -> Self {
+        String::new()
+    }
+}
+impl MinKey for i64 {
+    fn min_key() -> Self {
+        i64::MIN
+    }
+}
+impl MinKey for DateTime<Utc> {
+    /// Earliest timestamp accepted by postgres, chrono reaches further back
+    fn min_key() -> Self {
+        NaiveDate::from_ymd_opt(-4713, 12, 1).and_then(|date| date.and_hms_opt(0, 0, 0)).unwrap_or_default().and_utc()
+    }
+}
+
+/// Key sorting after every stored key
+trait MaxKey {
+    fn max_key() -> Self;
+}
+impl MaxKey for f32 {
+    fn max_key() -> Self {
+        f32::INFINITY
+    }
+}
+impl MaxKey for DateTime<Utc> {
+    fn max_key() -> Self {
+        DateTime::<Utc>::MAX_UTC
+    }
+}
 
+/// Connection pinned to an open transaction
+///
+/// Dropped without commit or rollback it is closed instead of going back to the pool.
+struct TxConn {
+    client: Option<Object>,
+    finished: AtomicBool,
+    savepoints: AtomicUsize,
+}
+impl TxConn {
+    fn new(client: Object) -> Self {
         Self {
-            db,
-            user_stmts,
-            space_stmts,
-            default_space_stmts,
-            user_space_stmts,
-            storage_stmts,
-            native_app_stmts,
+            client: Some(client),
+            finished: AtomicBool::new(false),
+            savepoints: AtomicUsize::new(0),
+        }
+    }
+}
+impl Deref for TxConn {
+    type Target = Object;
+
+    fn deref(&self) -> &Self::Target {
+        self.client.as_ref().expect("Transaction connection taken")
+    }
+}
+impl Drop for TxConn {
+    fn drop(&mut self) {
+        if !self.finished.load(std::sync::atomic::Ordering::Acquire)
+            && let Some(client) = self.client.take()
+        {
+            drop(Object::take(client));
+        }
+    }
+}
+
+enum Conn<'a> {
+    Pooled(Box<Object>),
+    Tx(&'a TxConn),
+}
+impl Deref for Conn<'_> {
+    type Target = Object;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Conn::Pooled(client) => client,
+            Conn::Tx(tx) => tx,
         }
     }
 }
@@ -54,427 +304,1721 @@ impl Datastore {
 mod statements {
     use tokio_postgres::types::Type;
 
+    /// Query with its parameter types, prepared lazily on each pooled connection
+    ///
+    /// Every statement group lists its statements in `all`, destructuring `Self` so a new
+    /// statement cannot be left out of [`super::Datastore::prepare_all`].
+    pub struct Stmt {
+        pub sql: &'static str,
+        pub types: Vec<Type>,
+    }
+    impl Stmt {
+        pub fn new(sql: &'static str, types: &[Type]) -> Self {
+            Self {
+                sql,
+                types: types.to_vec(),
+            }
+        }
+    }
+
     pub struct UserStatements {
         /// SELECT * FROM users WHERE clerk_id = $1
-        pub get_by_clerk_id: tokio_postgres::Statement,
+        pub get_by_clerk_id: Stmt,
 
         /// SELECT * FROM users WHERE id = $1
-        pub get_by_id: tokio_postgres::Statement,
+        pub get_by_id: Stmt,
 
         /// SELECT * FROM users WHERE allowed = true
-        pub get_allowed: tokio_postgres::Statement,
+        pub get_allowed: Stmt,
 
         /// INSERT INTO users
         /// (id, clerk_id, email, first_name, last_name, picture_url)
         /// VALUES ($1, $2, $3, $4, $5, $6) RETURNING *
-        pub insert: tokio_postgres::Statement,
+        pub insert: Stmt,
 
         /// UPDATE users SET first_name = $2, last_name = $3, picture_url = $4
         /// WHERE id = $1 RETURNING *
-        pub update: tokio_postgres::Statement,
+        pub update: Stmt,
     }
     impl UserStatements {
-        pub async fn new(db: &tokio_postgres::Client) -> Self {
+        pub fn new() -> Self {
             Self {
-                get_by_clerk_id: db
-                    .prepare_typed(r#"SELECT * FROM users WHERE clerk_id = $1"#, &[Type::BPCHAR])
-                    .await
-                    .unwrap(),
-                get_by_id: db.prepare_typed(r#"SELECT * FROM users WHERE id = $1"#, &[Type::UUID]).await.unwrap(),
-                get_allowed: db.prepare_typed(r#"SELECT * FROM users WHERE allowed = true"#, &[]).await.unwrap(),
-                insert: db
-                    .prepare_typed(
-                        r#"INSERT INTO users
+                get_by_clerk_id: Stmt::new(r#"SELECT * FROM users WHERE clerk_id = $1"#, &[Type::BPCHAR]),
+                get_by_id: Stmt::new(r#"SELECT * FROM users WHERE id = $1"#, &[Type::UUID]),
+                get_allowed: Stmt::new(r#"SELECT * FROM users WHERE allowed = true"#, &[]),
+                insert: Stmt::new(
+                    r#"INSERT INTO users
                         (id, clerk_id, email, first_name, last_name, picture_url)
                         VALUES ($1, $2, $3, $4, $5, $6) RETURNING *"#,
-                        &[Type::UUID, Type::BPCHAR, Type::VARCHAR, Type::VARCHAR, Type::VARCHAR, Type::VARCHAR],
-                    )
-                    .await
-                    .unwrap(),
-                update: db
-                    .prepare_typed(
-                        r#"UPDATE users SET first_name = $2, last_name = $3, picture_url = $4
+                    &[Type::UUID, Type::BPCHAR, Type::VARCHAR, Type::VARCHAR, Type::VARCHAR, Type::VARCHAR],
+                ),
+                update: Stmt::new(
+                    r#"UPDATE users SET first_name = $2, last_name = $3, picture_url = $4
                         WHERE id = $1 RETURNING *"#,
-                        &[Type::UUID, Type::VARCHAR, Type::VARCHAR, Type::VARCHAR],
-                    )
-                    .await
-                    .unwrap(),
+                    &[Type::UUID, Type::VARCHAR, Type::VARCHAR, Type::VARCHAR],
+                ),
             }
         }
+
+        pub fn all(&self) -> Vec<&Stmt> {
+            let Self {
+                get_by_clerk_id,
+                get_by_id,
+                get_allowed,
+                insert,
+                update,
+            } = self;
+            vec![get_by_clerk_id, get_by_id, get_allowed, insert, update]
+        }
     }
 
     pub struct SpaceStatements {
         /// SELECT * FROM spaces WHERE id = $1
-        pub get_by_id: tokio_postgres::Statement,
+        pub get_by_id: Stmt,
 
         /// INSERT INTO spaces
         /// (id, name, description, picture_url)
         /// VALUES ($1, $2, $3, $4) RETURNING *
-        pub insert: tokio_postgres::Statement,
+        pub insert: Stmt,
 
         /// UPDATE spaces SET name = $2, description = $3
         /// WHERE id = $1 RETURNING *
-        pub update: tokio_postgres::Statement,
+        pub update: Stmt,
+
+        /// Deletes space with everything referencing it in one statement
+        ///
+        /// WITH ... DELETE FROM album_media_files, media_file_tags, media_files, albums, tags,
+        /// space_usage, users_spaces, default_space
+        /// DELETE FROM spaces WHERE id = $1
+        ///
+        /// Upload sessions are left out, their multipart uploads must be aborted first
+        pub delete: Stmt,
     }
     impl SpaceStatements {
-        pub async fn new(db: &tokio_postgres::Client) -> Self {
+        pub fn new() -> Self {
             Self {
-                get_by_id: db.prepare_typed(r#"SELECT * FROM spaces WHERE id = $1"#, &[Type::UUID]).await.unwrap(),
-                insert: db
-                    .prepare_typed(
-                        r#"INSERT INTO spaces
+                get_by_id: Stmt::new(r#"SELECT * FROM spaces WHERE id = $1"#, &[Type::UUID]),
+                insert: Stmt::new(
+                    r#"INSERT INTO spaces
                         (id, name, description, picture_url)
                         VALUES ($1, $2, $3, $4) RETURNING *"#,
-                        &[Type::UUID, Type::VARCHAR, Type::VARCHAR, Type::VARCHAR],
-                    )
-                    .await
-                    .unwrap(),
-                update: db
-                    .prepare_typed(
-                        r#"UPDATE spaces SET name = $2, description = $3
+                    &[Type::UUID, Type::VARCHAR, Type::VARCHAR, Type::VARCHAR],
+                ),
+                update: Stmt::new(
+                    r#"UPDATE spaces SET name = $2, description = $3
                         WHERE id = $1 RETURNING *"#,
-                        &[Type::UUID, Type::VARCHAR, Type::VARCHAR],
-                    )
-                    .await
-                    .unwrap(),
+                    &[Type::UUID, Type::VARCHAR, Type::VARCHAR],
+                ),
+                delete: Stmt::new(
+                    r#"WITH deleted_links AS (
+                            DELETE FROM album_media_files
+                            WHERE album_id IN (SELECT id FROM albums WHERE space_id = $1)
+                        ), deleted_tag_links AS (
+                            DELETE FROM media_file_tags
+                            WHERE tag_id IN (SELECT id FROM tags WHERE space_id = $1)
+                        ), deleted_media AS (
+                            DELETE FROM media_files WHERE space_id = $1
+                        ), deleted_albums AS (
+                            DELETE FROM albums WHERE space_id = $1
+                        ), deleted_tags AS (
+                            DELETE FROM tags WHERE space_id = $1
+                        ), deleted_usage AS (
+                            DELETE FROM space_usage WHERE space_id = $1
+                        ), deleted_members AS (
+                            DELETE FROM users_spaces WHERE space_id = $1
+                        ), deleted_default AS (
+                            DELETE FROM default_space WHERE space_fk_id = $1
+                        )
+                        DELETE FROM spaces WHERE id = $1"#,
+                    &[Type::UUID],
+                ),
             }
         }
+
+        pub fn all(&self) -> Vec<&Stmt> {
+            let Self {
+                get_by_id,
+                insert,
+                update,
+                delete,
+            } = self;
+            vec![get_by_id, insert, update, delete]
+        }
     }
 
     pub struct DefaultSpaceStatements {
         /// INSERT INTO default_space (space_fk_id, user_fk_id) VALUES ($1, $2) RETURNING *
-        pub set_default_space: tokio_postgres::Statement,
+        pub set_default_space: Stmt,
 
         /// SELECT * FROM spaces
         /// WHERE id = (SELECT space_fk_id FROM default_space WHERE user_fk_id = $1)
-        pub get_default_space: tokio_postgres::Statement,
+        pub get_default_space: Stmt,
     }
     impl DefaultSpaceStatements {
-        pub async fn new(db: &tokio_postgres::Client) -> Self {
+        pub fn new() -> Self {
             Self {
-                set_default_space: db
-                    .prepare_typed(
-                        r#"INSERT INTO default_space (space_fk_id, user_fk_id) VALUES ($1, $2) RETURNING *"#,
-                        &[Type::UUID, Type::UUID],
-                    )
-                    .await
-                    .unwrap(),
-                get_default_space: db
-                    .prepare_typed(
-                        r#"SELECT * FROM spaces
+                set_default_space: Stmt::new(
+                    r#"INSERT INTO default_space (space_fk_id, user_fk_id) VALUES ($1, $2) RETURNING *"#,
+                    &[Type::UUID, Type::UUID],
+                ),
+                get_default_space: Stmt::new(
+                    r#"SELECT * FROM spaces
                         WHERE id = (SELECT space_fk_id FROM default_space WHERE user_fk_id = $1)"#,
-                        &[Type::UUID],
-                    )
-                    .await
-                    .unwrap(),
+                    &[Type::UUID],
+                ),
             }
         }
+
+        pub fn all(&self) -> Vec<&Stmt> {
+            let Self {
+                set_default_space,
+                get_default_space,
+            } = self;
+            vec![set_default_space, get_default_space]
+        }
     }
 
     pub struct UsersSpacesStatements {
         /// SELECT * FROM users_spaces WHERE user_id = $1 AND space_id = $2
-        pub get_user_space: tokio_postgres::Statement,
+        pub get_user_space: Stmt,
 
         /// SELECT us.*, spaces.*
         /// FROM spaces
         /// INNER JOIN (SELECT * FROM users_spaces WHERE user_id = $1) us
         /// ON spaces.id = us.space_id
-        pub get_all_spaces_for_user: tokio_postgres::Statement,
+        pub get_all_spaces_for_user: Stmt,
 
         /// SELECT us.*, users.*
         /// FROM users
         /// INNER JOIN (SELECT * FROM users_spaces WHERE space_id = $1) us
         /// ON users.id = us.user_id
-        pub get_all_users_for_space: tokio_postgres::Statement,
+        /// WHERE (us.created_at, us.id) > ($2, $3)
+        /// ORDER BY us.created_at, us.id LIMIT $4
+        pub get_all_users_for_space: Stmt,
 
         /// INSERT INTO users_spaces
         /// (id, user_id, space_id, role)
         /// VALUES ($1, $2, $3, $4) RETURNING *
-        pub insert: tokio_postgres::Statement,
+        pub insert: Stmt,
 
         /// UPDATE users_spaces SET role = $2 WHERE id = $1 RETURNING *
-        pub update: tokio_postgres::Statement,
+        pub update: Stmt,
 
         /// DELETE FROM users_spaces WHERE id = $1
-        pub delete: tokio_postgres::Statement,
+        pub delete: Stmt,
     }
     impl UsersSpacesStatements {
-        pub async fn new(db: &tokio_postgres::Client) -> Self {
+        pub fn new() -> Self {
             Self {
-                get_user_space: db
-                    .prepare_typed(
-                        r#"SELECT * FROM users_spaces WHERE user_id = $1 AND space_id = $2"#,
-                        &[Type::UUID, Type::UUID],
-                    )
-                    .await
-                    .unwrap(),
-                get_all_spaces_for_user: db
-                    .prepare_typed(
-                        r#"SELECT us.*, spaces.*
+                get_user_space: Stmt::new(
+                    r#"SELECT * FROM users_spaces WHERE user_id = $1 AND space_id = $2"#,
+                    &[Type::UUID, Type::UUID],
+                ),
+                get_all_spaces_for_user: Stmt::new(
+                    r#"SELECT us.*, spaces.*
                         FROM spaces
                         INNER JOIN (SELECT * FROM users_spaces WHERE user_id = $1) us
                         ON spaces.id = us.space_id"#,
-                        &[Type::UUID],
-                    )
-                    .await
-                    .unwrap(),
-                get_all_users_for_space: db
-                    .prepare_typed(
-                        r#"SELECT us.*, users.*
+                    &[Type::UUID],
+                ),
+                get_all_users_for_space: Stmt::new(
+                    r#"SELECT us.*, users.*
                         FROM users
                         INNER JOIN (SELECT * FROM users_spaces WHERE space_id = $1) us
-                        ON users.id = us.user_id"#,
-                        &[Type::UUID],
-                    )
-                    .await
-                    .unwrap(),
-                insert: db
-                    .prepare_typed(
-                        r#"INSERT INTO users_spaces (id, user_id, space_id, role) VALUES ($1, $2, $3, $4) RETURNING *"#,
-                        &[Type::UUID, Type::UUID, Type::UUID, Type::INT2],
-                    )
-                    .await
-                    .unwrap(),
-                update: db
-                    .prepare_typed(
-                        r#"UPDATE users_spaces SET role = $2 WHERE id = $1 RETURNING *"#,
-                        &[Type::UUID, Type::INT2],
-                    )
-                    .await
-                    .unwrap(),
-                delete: db.prepare_typed(r#"DELETE FROM users_spaces WHERE id = $1"#, &[Type::UUID]).await.unwrap(),
+                        ON users.id = us.user_id
+                        WHERE (us.created_at, us.id) > ($2, $3)
+                        ORDER BY us.created_at, us.id
+                        LIMIT $4"#,
+                    &[Type::UUID, Type::TIMESTAMPTZ, Type::UUID, Type::INT8],
+                ),
+                insert: Stmt::new(
+                    r#"INSERT INTO users_spaces (id, user_id, space_id, role) VALUES ($1, $2, $3, $4) RETURNING *"#,
+                    &[Type::UUID, Type::UUID, Type::UUID, Type::INT2],
+                ),
+                update: Stmt::new(
+                    r#"UPDATE users_spaces SET role = $2 WHERE id = $1 RETURNING *"#,
+                    &[Type::UUID, Type::INT2],
+                ),
+                delete: Stmt::new(r#"DELETE FROM users_spaces WHERE id = $1"#, &[Type::UUID]),
             }
         }
+
+        pub fn all(&self) -> Vec<&Stmt> {
+            let Self {
+                get_user_space,
+                get_all_spaces_for_user,
+                get_all_users_for_space,
+                insert,
+                update,
+                delete,
+            } = self;
+            vec![get_user_space, get_all_spaces_for_user, get_all_users_for_space, insert, update, delete]
+        }
     }
 
     pub struct StorageStatements {
         /// INSERT INTO media_files
-        /// (id, updated_at, user_id, space_id, hash, file_name, object_key, node_size, metadata)
-        /// VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        /// (id, updated_at, user_id, space_id, hash, file_name, object_key, node_size, metadata,
+        ///     taken_at, media_type, width, height, duration_secs, latitude, longitude)
+        /// VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
         /// ON CONFLICT (space_id, hash) DO UPDATE SET updated_at = excluded.updated_at
+        /// WHERE media_files.deleted_at IS NULL
         /// RETURNING *
-        pub upsert_media_file: tokio_postgres::Statement,
+        pub upsert_media_file: Stmt,
 
         /// SELECT * FROM media_files WHERE id = $1 AND space_id = $2
-        pub get_media_file: tokio_postgres::Statement,
+        pub get_media_file: Stmt,
 
-        /// SELECT * FROM media_files
+        /// INSERT INTO media_files
+        /// (id, created_at, updated_at, user_id, space_id, hash, file_name, object_key, thumbnail_key, preview_key,
+        ///     node_size, metadata, taken_at, media_type, width, height, duration_secs, latitude, longitude)
+        /// SELECT $1, created_at, updated_at, $2, $3, hash, file_name, object_key, thumbnail_key, preview_key,
+        ///     node_size, metadata, taken_at, media_type, width, height, duration_secs, latitude, longitude
+        /// FROM media_files WHERE id = $4 AND space_id = $5
+        /// ON CONFLICT (space_id, hash) DO NOTHING
+        /// RETURNING *
+        pub copy_media_file: Stmt,
+
+        /// SELECT * FROM media_files WHERE space_id = $1 AND hash = $2
+        pub get_media_file_by_hash: Stmt,
+
+        /// SELECT id, updated_at, taken_at, user_id, file_name, media_type, width, height, favorite of $6,
+        ///     comment count, position
+        /// FROM media_files
         /// INNER JOIN album_media_files ON album_media_files.media_file_id = media_files.id
-        /// WHERE album_media_files.album_id = $1 AND media_files.space_id = $2
-        pub list_album_media_files: tokio_postgres::Statement,
+        /// WHERE album_media_files.album_id = $1 AND media_files.space_id = $2 AND media_files.deleted_at IS NULL
+        ///     AND (file_name, id) > ($3, $4)
+        /// ORDER BY file_name, id LIMIT $5
+        pub list_album_media_files: Stmt,
 
-        /// SELECT id, updated_at, user_id, file_name, metadata->>'media_type' as media_type,
-        ///     metadata->'thumbnail_meta'->>'width' as width, metadata->'thumbnail_meta'->>'height' as height
+        /// SELECT id, updated_at, taken_at, user_id, file_name, media_type, width, height, favorite of $6,
+        ///     comment count, position
         /// FROM media_files
-        /// WHERE space_id = $1
-        /// ORDER BY updated_at DESC
-        pub list_media_files_gallery: tokio_postgres::Statement,
+        /// INNER JOIN album_media_files ON album_media_files.media_file_id = media_files.id
+        /// WHERE album_media_files.album_id = $1 AND media_files.space_id = $2 AND media_files.deleted_at IS NULL
+        ///     AND (taken_at, id) > ($3, $4)
+        /// ORDER BY taken_at, id LIMIT $5
+        pub list_album_media_files_by_taken_at: Stmt,
+
+        /// SELECT id, updated_at, taken_at, user_id, file_name, media_type, width, height, favorite of $6,
+        ///     comment count, position
+        /// FROM media_files
+        /// INNER JOIN album_media_files ON album_media_files.media_file_id = media_files.id
+        /// WHERE album_media_files.album_id = $1 AND media_files.space_id = $2 AND media_files.deleted_at IS NULL
+        ///     AND (position, id) > ($3, $4)
+        /// ORDER BY position, id LIMIT $5
+        pub list_album_media_files_by_position: Stmt,
+
+        /// SELECT id, updated_at, taken_at, user_id, file_name, media_type, width, height, favorite of $17,
+        ///     comment count
+        /// FROM media_files
+        /// WHERE space_id = $1 AND deleted_at IS NULL AND (taken_at, id) < ($2, $3)
+        ///     AND taken_at >= $5 AND taken_at < $6 AND media_type = $7
+        ///     AND lower(make) = lower($8) AND lower(model) = lower($9) AND user_id = $10
+        ///     AND orientation = $11 AND (latitude IS NOT NULL) = $12
+        ///     AND point(longitude, latitude) <@ box(point($15, $13), point($16, $14)) AND tagged with $18
+        /// ORDER BY taken_at DESC, id DESC LIMIT $4
+        pub list_media_files_gallery: Stmt,
+
+        /// SELECT date_trunc($2, taken_at, $3) AS start, start + 1 $2 in $3, count(*)
+        /// FROM media_files
+        /// WHERE space_id = $1 AND deleted_at IS NULL AND in album $4
+        /// GROUP BY start ORDER BY start DESC
+        pub list_media_timeline: Stmt,
+
+        /// SELECT EXISTS (SELECT 1 FROM pg_timezone_names WHERE name = $1)
+        pub timezone_exists: Stmt,
 
         /// SELECT thumbnail_key, preview_key FROM media_files
-        /// WHERE id = $1 AND space_id = $2
-        pub get_media_stream_keys: tokio_postgres::Statement,
+        /// WHERE id = $1 AND space_id = $2 AND deleted_at IS NULL
+        pub get_media_stream_keys: Stmt,
+
+        /// SELECT object_key FROM media_files WHERE id = $1 AND space_id = $2 AND deleted_at IS NULL
+        pub get_media_object_key: Stmt,
+
+        /// SELECT id, object_key, thumbnail_key, preview_key FROM media_files WHERE space_id = $1
+        pub list_space_media_keys: Stmt,
+
+        /// UPDATE media_files
+        /// SET broken_at = CASE WHEN id = ANY($2) THEN coalesce(broken_at, $3) END
+        /// WHERE space_id = $1 AND (id = ANY($2) OR broken_at IS NOT NULL)
+        pub set_broken_media_files: Stmt,
+
+        /// SELECT media_files.id, media_files.file_name, media_files.object_key
+        /// FROM media_files
+        /// INNER JOIN album_media_files amf ON amf.media_file_id = media_files.id
+        /// WHERE amf.album_id = $1 AND media_files.space_id = $2 AND media_files.deleted_at IS NULL
+        /// ORDER BY media_files.created_at
+        pub list_album_archive_files: Stmt,
 
-        /// SELECT object_key FROM media_files WHERE id = $1 AND space_id = $2
-        pub get_media_object_key: tokio_postgres::Statement,
+        /// SELECT id, file_name, object_key FROM media_files
+        /// WHERE space_id = $1 AND id = ANY($2) AND deleted_at IS NULL
+        /// ORDER BY created_at
+        pub list_archive_files: Stmt,
 
         /// UPDATE media_files
-        /// SET file_name = $3, node_size = $4, metadata = $5, updated_at = $6, thumbnail_key = $7, preview_key = $8
+        /// SET file_name = $3, node_size = $4, metadata = $5, updated_at = $6, thumbnail_key = $7, preview_key = $8,
+        ///     taken_at = $9, media_type = $10, width = $11, height = $12, duration_secs = $13, latitude = $14,
+        ///     longitude = $15
         /// WHERE id = $1 AND space_id = $2
         /// RETURNING *
-        pub update_media_file: tokio_postgres::Statement,
+        pub update_media_file: Stmt,
 
-        /// DELETE FROM media_files WHERE id = $1 AND space_id = $2
-        pub delete_media_file: tokio_postgres::Statement,
+        /// DELETE FROM media_files WHERE id = $1 AND space_id = $2 AND deleted_at IS NOT NULL
+        /// RETURNING id
+        pub delete_media_file: Stmt,
 
         /// INSERT INTO albums (id, user_id, space_id, name, legacy_path)
         /// VALUES ($1, $2, $3, $4, $5) RETURNING *
-        pub insert_album: tokio_postgres::Statement,
+        pub insert_album: Stmt,
+
+        /// SELECT * FROM albums WHERE id = $1 AND space_id = $2 AND deleted_at IS NULL
+        pub get_album: Stmt,
+
+        /// SELECT albums.*, count, total size, taken_at range, cover id and thumbnail key
+        /// FROM albums WHERE id = $1 AND space_id = $2 AND deleted_at IS NULL
+        pub get_album_details: Stmt,
+
+        /// SELECT albums.*, count, total size, taken_at range, cover id and thumbnail key
+        /// FROM albums WHERE space_id = $1 AND deleted_at IS NULL AND (name, id) > ($2, $3)
+        /// ORDER BY name, id LIMIT $4
+        pub list_albums: Stmt,
 
-        /// SELECT * FROM albums WHERE id = $1 AND space_id = $2
-        pub get_album: tokio_postgres::Statement,
+        /// UPDATE albums SET name = coalesce($3, name), description = $5 if $4, cover_file_id = $7 if $6,
+        ///     sort_mode = coalesce($8, sort_mode), updated_at = now()
+        /// WHERE id = $1 AND space_id = $2 AND deleted_at IS NULL AND $7 is a file of the album
+        /// RETURNING *
+        pub update_album: Stmt,
 
-        /// SELECT * FROM albums WHERE space_id = $1 ORDER BY name ASC, created_at ASC
-        pub list_albums: tokio_postgres::Statement,
+        /// WITH found AS (SELECT m.id FROM UNNEST($2) INNER JOIN media_files m, albums a ...)
+        /// INSERT INTO album_media_files (album_id, media_file_id, position)
+        ///     SELECT $1, id, positions after the last file in the order of $2 FROM found
+        /// ON CONFLICT DO NOTHING
+        /// SELECT id FROM found
+        pub link_album_media_files: Stmt,
 
-        /// DELETE FROM albums WHERE id = $1 AND space_id = $2
-        pub delete_album: tokio_postgres::Statement,
+        /// DELETE FROM album_media_files amf USING albums a, media_files m, UNNEST($2)
+        /// WHERE a.id = $1 AND a.space_id = $3 AND m.space_id = $3 ...
+        /// RETURNING amf.media_file_id
+        pub unlink_album_media_files: Stmt,
 
-        /// INSERT INTO album_media_files (album_id, media_file_id)
-        /// SELECT a.id, m.id FROM albums a
-        /// INNER JOIN media_files m ON m.id = $2
-        /// WHERE a.id = $1 AND a.space_id = $3 AND m.space_id = $3
+        /// WITH moved AS (DELETE FROM album_media_files ... WHERE album_id = $1 ... RETURNING media_file_id)
+        /// INSERT INTO album_media_files (album_id, media_file_id, position)
+        ///     SELECT $2, media_file_id, positions after the last file in source order FROM moved
         /// ON CONFLICT DO NOTHING
-        pub link_album_media_file: tokio_postgres::Statement,
+        /// SELECT media_file_id FROM moved
+        pub move_album_media_files: Stmt,
+
+        /// WITH anchor AS (file $3 of album $1 in space $5), moving AS (files $2 of album $1 in order),
+        ///     bounds AS (anchor position and its neighbour before it if $4, after it otherwise)
+        /// UPDATE album_media_files SET position = evenly spread between bounds WHERE enough room
+        /// SELECT anchor found, enough room, ARRAY(updated media_file_id)
+        pub reorder_album_media_files: Stmt,
+
+        /// UPDATE album_media_files SET position = row_number() OVER (ORDER BY position, media_file_id) * $2
+        /// WHERE album_id = $1
+        pub renumber_album_media_files: Stmt,
 
-        /// DELETE FROM album_media_files amf USING albums a, media_files m
-        /// WHERE amf.album_id = a.id AND amf.media_file_id = m.id
-        /// AND a.id = $1 AND m.id = $2 AND a.space_id = $3 AND m.space_id = $3
-        pub unlink_album_media_file: tokio_postgres::Statement,
+        /// SELECT id, user_id FROM media_files
+        /// WHERE space_id = $1 AND id = ANY($2) AND (deleted_at IS NOT NULL) = $3
+        pub list_media_file_owners: Stmt,
     }
     impl StorageStatements {
-        pub async fn new(db: &tokio_postgres::Client) -> Self {
+        pub fn new() -> Self {
             Self {
-                upsert_media_file: db
-                    .prepare_typed(
-                        r#"INSERT INTO media_files
-                        (id, updated_at, user_id, space_id, hash, file_name, object_key, node_size, metadata)
-                        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                upsert_media_file: Stmt::new(
+                    r#"INSERT INTO media_files
+                        (id, updated_at, user_id, space_id, hash, file_name, object_key, node_size, metadata,
+                            taken_at, media_type, width, height, duration_secs, latitude, longitude)
+                        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
                         ON CONFLICT (space_id, hash)
                         DO UPDATE SET updated_at = EXCLUDED.updated_at
+                        WHERE media_files.deleted_at IS NULL
                         RETURNING *"#,
-                        &[
-                            Type::UUID,
-                            Type::TIMESTAMPTZ,
-                            Type::UUID,
-                            Type::UUID,
-                            Type::BPCHAR,
-                            Type::VARCHAR,
-                            Type::VARCHAR,
-                            Type::INT8,
-                            Type::JSONB,
-                        ],
-                    )
-                    .await
-                    .unwrap(),
-                get_media_file: db
-                    .prepare_typed(
-                        r#"SELECT * FROM media_files WHERE id = $1 AND space_id = $2"#,
-                        &[Type::UUID, Type::UUID],
-                    )
-                    .await
-                    .unwrap(),
-                list_album_media_files: db
-                    .prepare_typed(
-                        r#"SELECT media_files.*
+                    &[
+                        Type::UUID,
+                        Type::TIMESTAMPTZ,
+                        Type::UUID,
+                        Type::UUID,
+                        Type::BPCHAR,
+                        Type::VARCHAR,
+                        Type::VARCHAR,
+                        Type::INT8,
+                        Type::JSONB,
+                        Type::TIMESTAMPTZ,
+                        Type::VARCHAR,
+                        Type::INT4,
+                        Type::INT4,
+                        Type::FLOAT8,
+                        Type::FLOAT8,
+                        Type::FLOAT8,
+                    ],
+                ),
+                get_media_file: Stmt::new(
+                    r#"SELECT * FROM media_files WHERE id = $1 AND space_id = $2"#,
+                    &[Type::UUID, Type::UUID],
+                ),
+                copy_media_file: Stmt::new(
+                    r#"INSERT INTO media_files
+                        (id, created_at, updated_at, user_id, space_id, hash, file_name, object_key, thumbnail_key,
+                            preview_key, node_size, metadata, taken_at, media_type, width, height, duration_secs,
+                            latitude, longitude)
+                        SELECT $1, created_at, updated_at, $2, $3, hash, file_name, object_key, thumbnail_key,
+                            preview_key, node_size, metadata, taken_at, media_type, width, height, duration_secs,
+                            latitude, longitude
+                        FROM media_files WHERE id = $4 AND space_id = $5
+                        ON CONFLICT (space_id, hash) DO NOTHING
+                        RETURNING *"#,
+                    &[Type::UUID, Type::UUID, Type::UUID, Type::UUID, Type::UUID],
+                ),
+                get_media_file_by_hash: Stmt::new(
+                    r#"SELECT * FROM media_files WHERE space_id = $1 AND hash = $2"#,
+                    &[Type::UUID, Type::BPCHAR],
+                ),
+                list_album_media_files: Stmt::new(
+                    r#"SELECT media_files.id, media_files.updated_at, media_files.taken_at, media_files.user_id,
+                            media_files.file_name, media_files.media_type, media_files.width, media_files.height,
+                            EXISTS (SELECT 1 FROM favorites f WHERE f.user_id = $6 AND f.media_file_id = media_files.id),
+                            (SELECT count(*) FROM comments c WHERE c.media_file_id = media_files.id), amf.position
                         FROM media_files
                         INNER JOIN album_media_files amf ON amf.media_file_id = media_files.id
-                        WHERE amf.album_id = $1 AND media_files.space_id = $2"#,
-                        &[Type::UUID, Type::UUID],
-                    )
-                    .await
-                    .unwrap(),
-                list_media_files_gallery: db
-                    .prepare_typed(
-                        r#"SELECT id, updated_at, user_id, file_name, metadata->>'media_type' as media_type,
-                            (metadata->'thumbnail_meta'->>'width')::int4 as width, (metadata->'thumbnail_meta'->>'height')::int4 as height
+                        WHERE amf.album_id = $1 AND media_files.space_id = $2 AND media_files.deleted_at IS NULL
+                          AND (media_files.file_name, media_files.id) > ($3, $4)
+                        ORDER BY media_files.file_name, media_files.id
+                        LIMIT $5"#,
+                    &[Type::UUID, Type::UUID, Type::VARCHAR, Type::UUID, Type::INT8, Type::UUID],
+                ),
+                list_album_media_files_by_taken_at: Stmt::new(
+                    r#"SELECT media_files.id, media_files.updated_at, media_files.taken_at, media_files.user_id,
+                            media_files.file_name, media_files.media_type, media_files.width, media_files.height,
+                            EXISTS (SELECT 1 FROM favorites f WHERE f.user_id = $6 AND f.media_file_id = media_files.id),
+                            (SELECT count(*) FROM comments c WHERE c.media_file_id = media_files.id), amf.position
                         FROM media_files
-                        WHERE space_id = $1
-                        ORDER BY updated_at DESC"#,
-                        &[Type::UUID],
-                    )
-                    .await
-                    .unwrap(),
-                get_media_stream_keys: db
-                    .prepare_typed(
-                        r#"SELECT thumbnail_key, preview_key
-                        FROM media_files WHERE id = $1 AND space_id = $2"#,
-                        &[Type::UUID, Type::UUID],
-                    )
-                    .await
-                    .unwrap(),
-                get_media_object_key: db
-                    .prepare_typed(
-                        r#"SELECT object_key
-                        FROM media_files WHERE id = $1 AND space_id = $2"#,
-                        &[Type::UUID, Type::UUID],
-                    )
-                    .await
-                    .unwrap(),
-                update_media_file: db
-                    .prepare_typed(
-                        r#"UPDATE media_files
-                        SET file_name = $3, node_size = $4, metadata = $5, updated_at = $6, thumbnail_key = $7, preview_key = $8
+                        INNER JOIN album_media_files amf ON amf.media_file_id = media_files.id
+                        WHERE amf.album_id = $1 AND media_files.space_id = $2 AND media_files.deleted_at IS NULL
+                          AND (media_files.taken_at, media_files.id) > ($3, $4)
+                        ORDER BY media_files.taken_at, media_files.id
+                        LIMIT $5"#,
+                    &[Type::UUID, Type::UUID, Type::TIMESTAMPTZ, Type::UUID, Type::INT8, Type::UUID],
+                ),
+                list_album_media_files_by_position: Stmt::new(
+                    r#"SELECT media_files.id, media_files.updated_at, media_files.taken_at, media_files.user_id,
+                            media_files.file_name, media_files.media_type, media_files.width, media_files.height,
+                            EXISTS (SELECT 1 FROM favorites f WHERE f.user_id = $6 AND f.media_file_id = media_files.id),
+                            (SELECT count(*) FROM comments c WHERE c.media_file_id = media_files.id), amf.position
+                        FROM media_files
+                        INNER JOIN album_media_files amf ON amf.media_file_id = media_files.id
+                        WHERE amf.album_id = $1 AND media_files.space_id = $2 AND media_files.deleted_at IS NULL
+                          AND (amf.position, amf.media_file_id) > ($3, $4)
+                        ORDER BY amf.position, amf.media_file_id
+                        LIMIT $5"#,
+                    &[Type::UUID, Type::UUID, Type::INT8, Type::UUID, Type::INT8, Type::UUID],
+                ),
+                list_media_files_gallery: Stmt::new(
+                    r#"SELECT id, updated_at, taken_at, user_id, file_name, media_type, width, height,
+                            EXISTS (SELECT 1 FROM favorites f WHERE f.user_id = $17 AND f.media_file_id = media_files.id),
+                            (SELECT count(*) FROM comments c WHERE c.media_file_id = media_files.id)
+                        FROM media_files
+                        WHERE space_id = $1 AND deleted_at IS NULL
+                          AND (taken_at, id) < ($2, $3)
+                          AND ($5::timestamptz IS NULL OR taken_at >= $5)
+                          AND ($6::timestamptz IS NULL OR taken_at < $6)
+                          AND ($7::varchar IS NULL OR media_type = $7)
+                          AND ($8::varchar IS NULL OR lower(metadata->'file_meta'->>'make') = lower($8))
+                          AND ($9::varchar IS NULL OR lower(metadata->'file_meta'->>'model') = lower($9))
+                          AND ($10::uuid IS NULL OR user_id = $10)
+                          AND ($11::varchar IS NULL
+                            OR ($11 = 'portrait' AND height > width)
+                            OR ($11 = 'landscape' AND width > height))
+                          AND ($12::bool IS NULL OR (latitude IS NOT NULL) = $12)
+                          AND ($13::float8 IS NULL OR latitude IS NOT NULL AND (
+                            point(longitude, latitude)
+                                <@ box(point($15, $13), point(CASE WHEN $15 <= $16 THEN $16 ELSE 180 END, $14))
+                            OR $15 > $16 AND point(longitude, latitude) <@ box(point(-180, $13), point($16, $14))))
+                          AND ($18::uuid IS NULL OR EXISTS (
+                            SELECT 1 FROM media_file_tags mft WHERE mft.tag_id = $18 AND mft.media_file_id = media_files.id))
+                        ORDER BY taken_at DESC, id DESC
+                        LIMIT $4"#,
+                    &[
+                        Type::UUID,
+                        Type::TIMESTAMPTZ,
+                        Type::UUID,
+                        Type::INT8,
+                        Type::TIMESTAMPTZ,
+                        Type::TIMESTAMPTZ,
+                        Type::VARCHAR,
+                        Type::VARCHAR,
+                        Type::VARCHAR,
+                        Type::UUID,
+                        Type::VARCHAR,
+                        Type::BOOL,
+                        Type::FLOAT8,
+                        Type::FLOAT8,
+                        Type::FLOAT8,
+                        Type::FLOAT8,
+                        Type::UUID,
+                        Type::UUID,
+                    ],
+                ),
+                list_media_timeline: Stmt::new(
+                    r#"WITH buckets AS (
+                            SELECT date_trunc($2, m.taken_at, $3) AS start,
+                                count(*) AS count
+                            FROM media_files m
+                            WHERE m.space_id = $1 AND m.deleted_at IS NULL
+                              AND ($4::uuid IS NULL OR EXISTS (
+                                SELECT 1 FROM album_media_files amf
+                                WHERE amf.album_id = $4 AND amf.media_file_id = m.id))
+                            GROUP BY 1
+                        )
+                        SELECT start, ((start AT TIME ZONE $3) + ('1 ' || $2)::interval) AT TIME ZONE $3 AS "end", count
+                        FROM buckets
+                        ORDER BY start DESC"#,
+                    &[Type::UUID, Type::TEXT, Type::TEXT, Type::UUID],
+                ),
+                timezone_exists: Stmt::new(
+                    r#"SELECT EXISTS (SELECT 1 FROM pg_timezone_names WHERE name = $1)"#,
+                    &[Type::TEXT],
+                ),
+                get_media_stream_keys: Stmt::new(
+                    r#"SELECT thumbnail_key, preview_key
+                        FROM media_files WHERE id = $1 AND space_id = $2 AND deleted_at IS NULL"#,
+                    &[Type::UUID, Type::UUID],
+                ),
+                get_media_object_key: Stmt::new(
+                    r#"SELECT object_key
+                        FROM media_files WHERE id = $1 AND space_id = $2 AND deleted_at IS NULL"#,
+                    &[Type::UUID, Type::UUID],
+                ),
+                list_space_media_keys: Stmt::new(
+                    r#"SELECT id, object_key, thumbnail_key, preview_key FROM media_files WHERE space_id = $1"#,
+                    &[Type::UUID],
+                ),
+                set_broken_media_files: Stmt::new(
+                    r#"UPDATE media_files
+                        SET broken_at = CASE WHEN id = ANY($2) THEN coalesce(broken_at, $3) END
+                        WHERE space_id = $1 AND (id = ANY($2) OR broken_at IS NOT NULL)"#,
+                    &[Type::UUID, Type::UUID_ARRAY, Type::TIMESTAMPTZ],
+                ),
+                list_album_archive_files: Stmt::new(
+                    r#"SELECT media_files.id, media_files.file_name, media_files.object_key
+                        FROM media_files
+                        INNER JOIN album_media_files amf ON amf.media_file_id = media_files.id
+                        WHERE amf.album_id = $1 AND media_files.space_id = $2 AND media_files.deleted_at IS NULL
+                        ORDER BY media_files.created_at"#,
+                    &[Type::UUID, Type::UUID],
+                ),
+                list_archive_files: Stmt::new(
+                    r#"SELECT id, file_name, object_key FROM media_files
+                        WHERE space_id = $1 AND id = ANY($2) AND deleted_at IS NULL
+                        ORDER BY created_at"#,
+                    &[Type::UUID, Type::UUID_ARRAY],
+                ),
+                update_media_file: Stmt::new(
+                    r#"UPDATE media_files
+                        SET file_name = $3, node_size = $4, metadata = $5, updated_at = $6, thumbnail_key = $7, preview_key = $8,
+                            taken_at = $9, media_type = $10, width = $11, height = $12, duration_secs = $13,
+                            latitude = $14, longitude = $15
                         WHERE id = $1 AND space_id = $2
                         RETURNING *"#,
-                        &[
-                            Type::UUID,
-                            Type::UUID,
-                            Type::VARCHAR,
-                            Type::INT8,
-                            Type::JSONB,
-                            Type::TIMESTAMPTZ,
-                            Type::VARCHAR,
-                            Type::VARCHAR,
-                        ],
-                    )
-                    .await
-                    .unwrap(),
-                delete_media_file: db
-                    .prepare_typed(
-                        r#"DELETE FROM media_files WHERE id = $1 AND space_id = $2"#,
-                        &[Type::UUID, Type::UUID],
-                    )
-                    .await
-                    .unwrap(),
-                insert_album: db
-                    .prepare_typed(
-                        r#"INSERT INTO albums (id, user_id, space_id, name, legacy_path)
+                    &[
+                        Type::UUID,
+                        Type::UUID,
+                        Type::VARCHAR,
+                        Type::INT8,
+                        Type::JSONB,
+                        Type::TIMESTAMPTZ,
+                        Type::VARCHAR,
+                        Type::VARCHAR,
+                        Type::TIMESTAMPTZ,
+                        Type::VARCHAR,
+                        Type::INT4,
+                        Type::INT4,
+                        Type::FLOAT8,
+                        Type::FLOAT8,
+                        Type::FLOAT8,
+                    ],
+                ),
+                delete_media_file: Stmt::new(
+                    r#"DELETE FROM media_files WHERE id = $1 AND space_id = $2 AND deleted_at IS NOT NULL
+                        RETURNING id"#,
+                    &[Type::UUID, Type::UUID],
+                ),
+                insert_album: Stmt::new(
+                    r#"INSERT INTO albums (id, user_id, space_id, name, legacy_path)
                         VALUES ($1, $2, $3, $4, $5)
                         RETURNING *"#,
-                        &[Type::UUID, Type::UUID, Type::UUID, Type::VARCHAR, Type::VARCHAR],
-                    )
-                    .await
-                    .unwrap(),
-                get_album: db
-                    .prepare_typed(
-                        r#"SELECT * FROM albums WHERE id = $1 AND space_id = $2"#,
-                        &[Type::UUID, Type::UUID],
-                    )
-                    .await
-                    .unwrap(),
-                list_albums: db
-                    .prepare_typed(
-                        r#"SELECT * FROM albums WHERE space_id = $1 ORDER BY name ASC, created_at ASC"#,
-                        &[Type::UUID],
-                    )
-                    .await
-                    .unwrap(),
-                delete_album: db
-                    .prepare_typed(r#"DELETE FROM albums WHERE id = $1 AND space_id = $2"#, &[Type::UUID, Type::UUID])
-                    .await
-                    .unwrap(),
-                link_album_media_file: db
-                    .prepare_typed(
-                        r#"INSERT INTO album_media_files (album_id, media_file_id)
-                        SELECT a.id, m.id
+                    &[Type::UUID, Type::UUID, Type::UUID, Type::VARCHAR, Type::VARCHAR],
+                ),
+                get_album: Stmt::new(
+                    r#"SELECT * FROM albums WHERE id = $1 AND space_id = $2 AND deleted_at IS NULL"#,
+                    &[Type::UUID, Type::UUID],
+                ),
+                get_album_details: Stmt::new(
+                    r#"SELECT a.*, s.item_count, s.total_size, s.first_taken_at, s.last_taken_at, c.id, c.thumbnail_key
                         FROM albums a
-                        INNER JOIN media_files m ON m.id = $2
-                        WHERE a.id = $1 AND a.space_id = $3 AND m.space_id = $3
-                        ON CONFLICT DO NOTHING"#,
-                        &[Type::UUID, Type::UUID, Type::UUID],
-                    )
-                    .await
-                    .unwrap(),
-                unlink_album_media_file: db
-                    .prepare_typed(
-                        r#"DELETE FROM album_media_files amf
-                        USING albums a, media_files m
+                        CROSS JOIN LATERAL (
+                            SELECT count(*) AS item_count, coalesce(sum(m.node_size), 0)::int8 AS total_size,
+                                min(m.taken_at) AS first_taken_at, max(m.taken_at) AS last_taken_at
+                            FROM album_media_files amf
+                            INNER JOIN media_files m ON m.id = amf.media_file_id
+                            WHERE amf.album_id = a.id AND m.deleted_at IS NULL
+                        ) s
+                        LEFT JOIN LATERAL (
+                            SELECT m.id, m.thumbnail_key
+                            FROM album_media_files amf
+                            INNER JOIN media_files m ON m.id = amf.media_file_id
+                            WHERE amf.album_id = a.id AND m.deleted_at IS NULL
+                            ORDER BY m.id = a.cover_file_id DESC, m.taken_at DESC, m.id DESC
+                            LIMIT 1
+                        ) c ON true
+                        WHERE a.id = $1 AND a.space_id = $2 AND a.deleted_at IS NULL"#,
+                    &[Type::UUID, Type::UUID],
+                ),
+                list_albums: Stmt::new(
+                    r#"SELECT a.*, s.item_count, s.total_size, s.first_taken_at, s.last_taken_at, c.id, c.thumbnail_key
+                        FROM albums a
+                        CROSS JOIN LATERAL (
+                            SELECT count(*) AS item_count, coalesce(sum(m.node_size), 0)::int8 AS total_size,
+                                min(m.taken_at) AS first_taken_at, max(m.taken_at) AS last_taken_at
+                            FROM album_media_files amf
+                            INNER JOIN media_files m ON m.id = amf.media_file_id
+                            WHERE amf.album_id = a.id AND m.deleted_at IS NULL
+                        ) s
+                        LEFT JOIN LATERAL (
+                            SELECT m.id, m.thumbnail_key
+                            FROM album_media_files amf
+                            INNER JOIN media_files m ON m.id = amf.media_file_id
+                            WHERE amf.album_id = a.id AND m.deleted_at IS NULL
+                            ORDER BY m.id = a.cover_file_id DESC, m.taken_at DESC, m.id DESC
+                            LIMIT 1
+                        ) c ON true
+                        WHERE a.space_id = $1 AND a.deleted_at IS NULL
+                          AND (a.name, a.id) > ($2, $3)
+                        ORDER BY a.name, a.id
+                        LIMIT $4"#,
+                    &[Type::UUID, Type::VARCHAR, Type::UUID, Type::INT8],
+                ),
+                update_album: Stmt::new(
+                    r#"UPDATE albums
+                        SET name = coalesce($3, name),
+                            description = CASE WHEN $4 THEN $5 ELSE description END,
+                            cover_file_id = CASE WHEN $6 THEN $7 ELSE cover_file_id END,
+                            sort_mode = coalesce($8, sort_mode),
+                            updated_at = now()
+                        WHERE id = $1 AND space_id = $2 AND deleted_at IS NULL
+                          AND ($7::uuid IS NULL OR EXISTS (
+                            SELECT 1 FROM album_media_files amf
+                            INNER JOIN media_files m ON m.id = amf.media_file_id
+                            WHERE amf.album_id = $1 AND amf.media_file_id = $7 AND m.deleted_at IS NULL))
+                        RETURNING *"#,
+                    &[
+                        Type::UUID,
+                        Type::UUID,
+                        Type::VARCHAR,
+                        Type::BOOL,
+                        Type::VARCHAR,
+                        Type::BOOL,
+                        Type::UUID,
+                        Type::INT2,
+                    ],
+                ),
+                link_album_media_files: Stmt::new(
+                    r#"WITH found AS (
+                            SELECT m.id, min(ids.ord) AS ord
+                            FROM UNNEST($2::uuid[]) WITH ORDINALITY AS ids(id, ord)
+                            INNER JOIN media_files m ON m.id = ids.id
+                            INNER JOIN albums a ON a.id = $1
+                            WHERE a.space_id = $3 AND m.space_id = $3
+                              AND a.deleted_at IS NULL AND m.deleted_at IS NULL
+                            GROUP BY m.id
+                        ), linked AS (
+                            INSERT INTO album_media_files (album_id, media_file_id, position)
+                            SELECT $1, id,
+                                (SELECT coalesce(max(position), 0) FROM album_media_files WHERE album_id = $1)
+                                    + row_number() OVER (ORDER BY ord) * 65536
+                            FROM found
+                            ON CONFLICT DO NOTHING
+                        )
+                        SELECT id FROM found"#,
+                    &[Type::UUID, Type::UUID_ARRAY, Type::UUID],
+                ),
+                unlink_album_media_files: Stmt::new(
+                    r#"DELETE FROM album_media_files amf
+                        USING albums a, media_files m, UNNEST($2::uuid[]) AS ids(id)
                         WHERE amf.album_id = a.id
                           AND amf.media_file_id = m.id
+                          AND m.id = ids.id
                           AND a.id = $1
-                          AND m.id = $2
                           AND a.space_id = $3
-                          AND m.space_id = $3"#,
-                        &[Type::UUID, Type::UUID, Type::UUID],
-                    )
-                    .await
-                    .unwrap(),
+                          AND m.space_id = $3
+                        RETURNING amf.media_file_id"#,
+                    &[Type::UUID, Type::UUID_ARRAY, Type::UUID],
+                ),
+                move_album_media_files: Stmt::new(
+                    r#"WITH moved AS (
+                            DELETE FROM album_media_files amf
+                            USING albums a, albums t, media_files m, UNNEST($3::uuid[]) AS ids(id)
+                            WHERE amf.album_id = a.id
+                              AND amf.media_file_id = m.id
+                              AND m.id = ids.id
+                              AND a.id = $1
+                              AND t.id = $2
+                              AND a.space_id = $4
+                              AND t.space_id = $4
+                              AND m.space_id = $4
+                              AND t.deleted_at IS NULL
+                              AND m.deleted_at IS NULL
+                            RETURNING amf.media_file_id, amf.position
+                        ), linked AS (
+                            INSERT INTO album_media_files (album_id, media_file_id, position)
+                            SELECT $2, media_file_id,
+                                (SELECT coalesce(max(position), 0) FROM album_media_files WHERE album_id = $2)
+                                    + row_number() OVER (ORDER BY position, media_file_id) * 65536
+                            FROM moved
+                            ON CONFLICT DO NOTHING
+                        )
+                        SELECT media_file_id FROM moved"#,
+                    &[Type::UUID, Type::UUID, Type::UUID_ARRAY, Type::UUID],
+                ),
+                reorder_album_media_files: Stmt::new(
+                    r#"WITH anchor AS (
+                            SELECT amf.position, amf.media_file_id
+                            FROM album_media_files amf
+                            INNER JOIN albums a ON a.id = amf.album_id
+                            INNER JOIN media_files m ON m.id = amf.media_file_id
+                            WHERE amf.album_id = $1 AND amf.media_file_id = $3
+                              AND a.space_id = $5 AND a.deleted_at IS NULL AND m.deleted_at IS NULL
+                        ), moving AS (
+                            SELECT amf.media_file_id, row_number() OVER (ORDER BY min(ids.ord)) AS rank
+                            FROM UNNEST($2::uuid[]) WITH ORDINALITY AS ids(id, ord)
+                            INNER JOIN album_media_files amf ON amf.album_id = $1 AND amf.media_file_id = ids.id
+                            INNER JOIN media_files m ON m.id = amf.media_file_id
+                            WHERE ids.id <> $3 AND m.deleted_at IS NULL
+                            GROUP BY amf.media_file_id
+                        ), bounds AS (
+                            SELECT
+                                CASE WHEN $4 THEN coalesce(preceding.position, anchor.position - 65536 * (c.n + 1))
+                                    ELSE anchor.position END AS lo,
+                                CASE WHEN $4 THEN anchor.position
+                                    ELSE coalesce(following.position, anchor.position + 65536 * (c.n + 1)) END AS hi,
+                                c.n
+                            FROM anchor
+                            CROSS JOIN (SELECT count(*) AS n FROM moving) c
+                            LEFT JOIN LATERAL (
+                                SELECT amf.position FROM album_media_files amf
+                                WHERE amf.album_id = $1
+                                  AND amf.media_file_id NOT IN (SELECT media_file_id FROM moving)
+                                  AND (amf.position, amf.media_file_id) < (anchor.position, anchor.media_file_id)
+                                ORDER BY amf.position DESC, amf.media_file_id DESC
+                                LIMIT 1
+                            ) preceding ON true
+                            LEFT JOIN LATERAL (
+                                SELECT amf.position FROM album_media_files amf
+                                WHERE amf.album_id = $1
+                                  AND amf.media_file_id NOT IN (SELECT media_file_id FROM moving)
+                                  AND (amf.position, amf.media_file_id) > (anchor.position, anchor.media_file_id)
+                                ORDER BY amf.position, amf.media_file_id
+                                LIMIT 1
+                            ) following ON true
+                        ), updated AS (
+                            UPDATE album_media_files amf
+                            SET position = b.lo + (b.hi - b.lo) * m.rank / (b.n + 1)
+                            FROM moving m, bounds b
+                            WHERE amf.album_id = $1 AND amf.media_file_id = m.media_file_id AND b.hi - b.lo > b.n
+                            RETURNING amf.media_file_id
+                        )
+                        SELECT EXISTS (SELECT 1 FROM anchor),
+                            coalesce((SELECT hi - lo > n FROM bounds), false),
+                            ARRAY(SELECT media_file_id FROM updated)"#,
+                    &[Type::UUID, Type::UUID_ARRAY, Type::UUID, Type::BOOL, Type::UUID],
+                ),
+                renumber_album_media_files: Stmt::new(
+                    r#"UPDATE album_media_files amf
+                        SET position = ordered.position
+                        FROM (
+                            SELECT media_file_id, row_number() OVER (ORDER BY position, media_file_id) * $2 AS position
+                            FROM album_media_files
+                            WHERE album_id = $1
+                        ) ordered
+                        WHERE amf.album_id = $1 AND amf.media_file_id = ordered.media_file_id"#,
+                    &[Type::UUID, Type::INT8],
+                ),
+                list_media_file_owners: Stmt::new(
+                    r#"SELECT id, user_id FROM media_files
+                        WHERE space_id = $1 AND id = ANY($2) AND (deleted_at IS NOT NULL) = $3"#,
+                    &[Type::UUID, Type::UUID_ARRAY, Type::BOOL],
+                ),
             }
         }
+
+        pub fn all(&self) -> Vec<&Stmt> {
+            let Self {
+                upsert_media_file,
+                get_media_file,
+                copy_media_file,
+                get_media_file_by_hash,
+                list_album_media_files,
+                list_album_media_files_by_taken_at,
+                list_album_media_files_by_position,
+                list_media_files_gallery,
+                list_media_timeline,
+                timezone_exists,
+                get_media_stream_keys,
+                get_media_object_key,
+                list_space_media_keys,
+                set_broken_media_files,
+                list_album_archive_files,
+                list_archive_files,
+                update_media_file,
+                delete_media_file,
+                insert_album,
+                get_album,
+                get_album_details,
+                list_albums,
+                update_album,
+                link_album_media_files,
+                unlink_album_media_files,
+                move_album_media_files,
+                reorder_album_media_files,
+                renumber_album_media_files,
+                list_media_file_owners,
+            } = self;
+            vec![
+                upsert_media_file,
+                get_media_file,
+                copy_media_file,
+                get_media_file_by_hash,
+                list_album_media_files,
+                list_album_media_files_by_taken_at,
+                list_album_media_files_by_position,
+                list_media_files_gallery,
+                list_media_timeline,
+                timezone_exists,
+                get_media_stream_keys,
+                get_media_object_key,
+                list_space_media_keys,
+                set_broken_media_files,
+                list_album_archive_files,
+                list_archive_files,
+                update_media_file,
+                delete_media_file,
+                insert_album,
+                get_album,
+                get_album_details,
+                list_albums,
+                update_album,
+                link_album_media_files,
+                unlink_album_media_files,
+                move_album_media_files,
+                reorder_album_media_files,
+                renumber_album_media_files,
+                list_media_file_owners,
+            ]
+        }
     }
 
     pub struct NativeAppStatements {
         /// SELECT * FROM native_app WHERE secure_identifier = $1
-        pub get_app_by_identifier: tokio_postgres::Statement,
+        pub get_app_by_identifier: Stmt,
     }
     impl NativeAppStatements {
-        pub async fn new(db: &tokio_postgres::Client) -> Self {
+        pub fn new() -> Self {
+            Self {
+                get_app_by_identifier: Stmt::new(
+                    r#"SELECT * FROM native_app WHERE secure_identifier = $1"#,
+                    &[Type::VARCHAR],
+                ),
+            }
+        }
+
+        pub fn all(&self) -> Vec<&Stmt> {
+            let Self {
+                get_app_by_identifier,
+            } = self;
+            vec![get_app_by_identifier]
+        }
+    }
+
+    pub struct UploadSessionStatements {
+        /// INSERT INTO upload_sessions
+        /// (id, user_id, space_id, upload_id, hash, file_name, file_size, part_size)
+        /// VALUES ($1, $2, $3, $4, $5, $6, $7, $8) RETURNING *
+        pub insert: Stmt,
+
+        /// SELECT * FROM upload_sessions WHERE id = $1 AND space_id = $2
+        pub get: Stmt,
+
+        /// UPDATE upload_sessions SET updated_at = now() WHERE id = $1
+        pub touch: Stmt,
+
+        /// DELETE FROM upload_sessions WHERE id = $1
+        pub delete: Stmt,
+
+        /// SELECT * FROM upload_sessions WHERE updated_at < $1 ORDER BY updated_at ASC
+        pub list_stale: Stmt,
+
+        /// DELETE FROM upload_sessions WHERE space_id = $1 RETURNING *
+        pub delete_by_space: Stmt,
+    }
+    impl UploadSessionStatements {
+        pub fn new() -> Self {
+            Self {
+                insert: Stmt::new(
+                    r#"INSERT INTO upload_sessions
+                        (id, user_id, space_id, upload_id, hash, file_name, file_size, part_size)
+                        VALUES ($1, $2, $3, $4, $5, $6, $7, $8) RETURNING *"#,
+                    &[
+                        Type::UUID,
+                        Type::UUID,
+                        Type::UUID,
+                        Type::VARCHAR,
+                        Type::BPCHAR,
+                        Type::VARCHAR,
+                        Type::INT8,
+                        Type::INT8,
+                    ],
+                ),
+                get: Stmt::new(
+                    r#"SELECT * FROM upload_sessions WHERE id = $1 AND space_id = $2"#,
+                    &[Type::UUID, Type::UUID],
+                ),
+                touch: Stmt::new(r#"UPDATE upload_sessions SET updated_at = now() WHERE id = $1"#, &[Type::UUID]),
+                delete: Stmt::new(r#"DELETE FROM upload_sessions WHERE id = $1"#, &[Type::UUID]),
+                list_stale: Stmt::new(
+                    r#"SELECT * FROM upload_sessions WHERE updated_at < $1 ORDER BY updated_at ASC"#,
+                    &[Type::TIMESTAMPTZ],
+                ),
+                delete_by_space: Stmt::new(
+                    r#"DELETE FROM upload_sessions WHERE space_id = $1 RETURNING *"#,
+                    &[Type::UUID],
+                ),
+            }
+        }
+
+        pub fn all(&self) -> Vec<&Stmt> {
+            let Self {
+                insert,
+                get,
+                touch,
+                delete,
+                list_stale,
+                delete_by_space,
+            } = self;
+            vec![insert, get, touch, delete, list_stale, delete_by_space]
+        }
+    }
+
+    pub struct JobStatements {
+        /// INSERT INTO background_jobs (id, user_id, space_id, kind)
+        /// VALUES ($1, $2, $3, $4) RETURNING *
+        pub insert: Stmt,
+
+        /// SELECT * FROM background_jobs WHERE id = $1 AND user_id = $2
+        pub get: Stmt,
+
+        /// UPDATE background_jobs SET status = $2, progress = $3, report = $4, updated_at = now()
+        /// WHERE id = $1
+        pub update_progress: Stmt,
+
+        /// UPDATE background_jobs SET status = $2, error = $3, updated_at = now()
+        /// WHERE id = $1
+        pub fail: Stmt,
+
+        /// SELECT * FROM background_jobs WHERE kind = $1 AND status = ANY($2)
+        /// ORDER BY created_at ASC
+        pub list_by_status: Stmt,
+    }
+    impl JobStatements {
+        pub fn new() -> Self {
+            Self {
+                insert: Stmt::new(
+                    r#"INSERT INTO background_jobs (id, user_id, space_id, kind)
+                        VALUES ($1, $2, $3, $4) RETURNING *"#,
+                    &[Type::UUID, Type::UUID, Type::UUID, Type::INT2],
+                ),
+                get: Stmt::new(
+                    r#"SELECT * FROM background_jobs WHERE id = $1 AND user_id = $2"#,
+                    &[Type::UUID, Type::UUID],
+                ),
+                update_progress: Stmt::new(
+                    r#"UPDATE background_jobs SET status = $2, progress = $3, report = $4, updated_at = now()
+                        WHERE id = $1"#,
+                    &[Type::UUID, Type::INT2, Type::INT8, Type::JSONB],
+                ),
+                fail: Stmt::new(
+                    r#"UPDATE background_jobs SET status = $2, error = $3, updated_at = now()
+                        WHERE id = $1"#,
+                    &[Type::UUID, Type::INT2, Type::VARCHAR],
+                ),
+                list_by_status: Stmt::new(
+                    r#"SELECT * FROM background_jobs WHERE kind = $1 AND status = ANY($2)
+                        ORDER BY created_at ASC"#,
+                    &[Type::INT2, Type::INT2_ARRAY],
+                ),
+            }
+        }
+
+        pub fn all(&self) -> Vec<&Stmt> {
+            let Self {
+                insert,
+                get,
+                update_progress,
+                fail,
+                list_by_status,
+            } = self;
+            vec![insert, get, update_progress, fail, list_by_status]
+        }
+    }
+
+    pub struct UsageStatements {
+        /// INSERT INTO space_usage (space_id, user_id, media_type, bytes, file_count)
+        /// VALUES ($1, $2, $3, $4, $5)
+        /// ON CONFLICT (space_id, user_id, media_type)
+        /// DO UPDATE SET bytes = space_usage.bytes + excluded.bytes, file_count = space_usage.file_count + excluded.file_count
+        pub add: Stmt,
+
+        /// SELECT coalesce(sum(bytes), 0)::int8 FROM space_usage WHERE space_id = $1
+        pub get_total: Stmt,
+
+        /// SELECT * FROM space_usage WHERE space_id = $1
+        pub list: Stmt,
+
+        /// SELECT id FROM spaces WHERE id = $1 FOR NO KEY UPDATE
+        pub lock_space: Stmt,
+
+        /// DELETE FROM upload_reservations WHERE space_id = $1 AND created_at < $2
+        pub delete_expired_reservations: Stmt,
+
+        /// SELECT coalesce(sum(bytes), 0)::int8 FROM upload_reservations
+        /// WHERE space_id = $1 AND NOT (user_id = $2 AND hash = $3)
+        pub get_reserved: Stmt,
+
+        /// INSERT INTO upload_reservations (space_id, user_id, hash, bytes)
+        /// VALUES ($1, $2, $3, $4)
+        /// ON CONFLICT (space_id, user_id, hash) DO UPDATE SET bytes = excluded.bytes, created_at = now()
+        pub reserve: Stmt,
+
+        /// DELETE FROM upload_reservations WHERE space_id = $1 AND user_id = $2 AND hash = $3
+        pub release: Stmt,
+    }
+    impl UsageStatements {
+        pub fn new() -> Self {
+            Self {
+                add: Stmt::new(
+                    r#"INSERT INTO space_usage (space_id, user_id, media_type, bytes, file_count)
+                        VALUES ($1, $2, $3, $4, $5)
+                        ON CONFLICT (space_id, user_id, media_type)
+                        DO UPDATE SET bytes = space_usage.bytes + EXCLUDED.bytes,
+                            file_count = space_usage.file_count + EXCLUDED.file_count"#,
+                    &[Type::UUID, Type::UUID, Type::VARCHAR, Type::INT8, Type::INT8],
+                ),
+                get_total: Stmt::new(
+                    r#"SELECT coalesce(sum(bytes), 0)::int8 FROM space_usage WHERE space_id = $1"#,
+                    &[Type::UUID],
+                ),
+                list: Stmt::new(r#"SELECT * FROM space_usage WHERE space_id = $1"#, &[Type::UUID]),
+                lock_space: Stmt::new(r#"SELECT id FROM spaces WHERE id = $1 FOR NO KEY UPDATE"#, &[Type::UUID]),
+                delete_expired_reservations: Stmt::new(
+                    r#"DELETE FROM upload_reservations WHERE space_id = $1 AND created_at < $2"#,
+                    &[Type::UUID, Type::TIMESTAMPTZ],
+                ),
+                get_reserved: Stmt::new(
+                    r#"SELECT coalesce(sum(bytes), 0)::int8 FROM upload_reservations
+                        WHERE space_id = $1 AND NOT (user_id = $2 AND hash = $3)"#,
+                    &[Type::UUID, Type::UUID, Type::BPCHAR],
+                ),
+                reserve: Stmt::new(
+                    r#"INSERT INTO upload_reservations (space_id, user_id, hash, bytes)
+                        VALUES ($1, $2, $3, $4)
+                        ON CONFLICT (space_id, user_id, hash)
+                        DO UPDATE SET bytes = EXCLUDED.bytes, created_at = now()"#,
+                    &[Type::UUID, Type::UUID, Type::BPCHAR, Type::INT8],
+                ),
+                release: Stmt::new(
+                    r#"DELETE FROM upload_reservations WHERE space_id = $1 AND user_id = $2 AND hash = $3"#,
+                    &[Type::UUID, Type::UUID, Type::BPCHAR],
+                ),
+            }
+        }
+
+        pub fn all(&self) -> Vec<&Stmt> {
+            let Self {
+                add,
+                get_total,
+                list,
+                lock_space,
+                delete_expired_reservations,
+                get_reserved,
+                reserve,
+                release,
+            } = self;
+            vec![add, get_total, list, lock_space, delete_expired_reservations, get_reserved, reserve, release]
+        }
+    }
+
+    pub struct TrashStatements {
+        /// UPDATE media_files SET deleted_at = $3
+        /// WHERE id = $1 AND space_id = $2 AND deleted_at IS NULL
+        pub trash_file: Stmt,
+
+        /// UPDATE media_files SET deleted_at = $3
+        /// WHERE id = ANY($1) AND space_id = $2 AND deleted_at IS NULL
+        /// RETURNING id
+        pub trash_files: Stmt,
+
+        /// UPDATE albums SET deleted_at = $3
+        /// WHERE id = $1 AND space_id = $2 AND deleted_at IS NULL
+        pub trash_album: Stmt,
+
+        /// UPDATE media_files SET deleted_at = NULL
+        /// WHERE id = $1 AND space_id = $2 AND deleted_at IS NOT NULL
+        pub restore_file: Stmt,
+
+        /// UPDATE media_files SET deleted_at = NULL
+        /// WHERE id = ANY($1) AND space_id = $2 AND deleted_at IS NOT NULL
+        /// RETURNING id
+        pub restore_files: Stmt,
+
+        /// UPDATE albums SET deleted_at = NULL
+        /// WHERE id = $1 AND space_id = $2 AND deleted_at IS NOT NULL
+        pub restore_album: Stmt,
+
+        /// SELECT * FROM media_files
+        /// WHERE space_id = $1 AND deleted_at IS NOT NULL
+        /// ORDER BY deleted_at DESC
+        pub list_files: Stmt,
+
+        /// SELECT * FROM albums
+        /// WHERE space_id = $1 AND deleted_at IS NOT NULL
+        /// ORDER BY deleted_at DESC
+        pub list_albums: Stmt,
+
+        /// SELECT * FROM media_files
+        /// WHERE deleted_at < $1
+        /// ORDER BY deleted_at LIMIT $2
+        pub list_expired_files: Stmt,
+
+        /// DELETE FROM albums WHERE deleted_at < $1
+        pub delete_expired_albums: Stmt,
+
+        /// DELETE FROM albums WHERE space_id = $1 AND deleted_at IS NOT NULL
+        pub delete_albums: Stmt,
+    }
+    impl TrashStatements {
+        pub fn new() -> Self {
+            Self {
+                trash_file: Stmt::new(
+                    r#"UPDATE media_files SET deleted_at = $3
+                        WHERE id = $1 AND space_id = $2 AND deleted_at IS NULL"#,
+                    &[Type::UUID, Type::UUID, Type::TIMESTAMPTZ],
+                ),
+                trash_files: Stmt::new(
+                    r#"UPDATE media_files SET deleted_at = $3
+                        WHERE id = ANY($1) AND space_id = $2 AND deleted_at IS NULL
+                        RETURNING id"#,
+                    &[Type::UUID_ARRAY, Type::UUID, Type::TIMESTAMPTZ],
+                ),
+                trash_album: Stmt::new(
+                    r#"UPDATE albums SET deleted_at = $3
+                        WHERE id = $1 AND space_id = $2 AND deleted_at IS NULL"#,
+                    &[Type::UUID, Type::UUID, Type::TIMESTAMPTZ],
+                ),
+                restore_file: Stmt::new(
+                    r#"UPDATE media_files SET deleted_at = NULL
+                        WHERE id = $1 AND space_id = $2 AND deleted_at IS NOT NULL"#,
+                    &[Type::UUID, Type::UUID],
+                ),
+                restore_files: Stmt::new(
+                    r#"UPDATE media_files SET deleted_at = NULL
+                        WHERE id = ANY($1) AND space_id = $2 AND deleted_at IS NOT NULL
+                        RETURNING id"#,
+                    &[Type::UUID_ARRAY, Type::UUID],
+                ),
+                restore_album: Stmt::new(
+                    r#"UPDATE albums SET deleted_at = NULL
+                        WHERE id = $1 AND space_id = $2 AND deleted_at IS NOT NULL"#,
+                    &[Type::UUID, Type::UUID],
+                ),
+                list_files: Stmt::new(
+                    r#"SELECT * FROM media_files
+                        WHERE space_id = $1 AND deleted_at IS NOT NULL
+                        ORDER BY deleted_at DESC"#,
+                    &[Type::UUID],
+                ),
+                list_albums: Stmt::new(
+                    r#"SELECT * FROM albums
+                        WHERE space_id = $1 AND deleted_at IS NOT NULL
+                        ORDER BY deleted_at DESC"#,
+                    &[Type::UUID],
+                ),
+                list_expired_files: Stmt::new(
+                    r#"SELECT * FROM media_files
+                        WHERE deleted_at < $1
+                        ORDER BY deleted_at LIMIT $2"#,
+                    &[Type::TIMESTAMPTZ, Type::INT8],
+                ),
+                delete_expired_albums: Stmt::new(r#"DELETE FROM albums WHERE deleted_at < $1"#, &[Type::TIMESTAMPTZ]),
+                delete_albums: Stmt::new(
+                    r#"DELETE FROM albums WHERE space_id = $1 AND deleted_at IS NOT NULL"#,
+                    &[Type::UUID],
+                ),
+            }
+        }
+
+        pub fn all(&self) -> Vec<&Stmt> {
+            let Self {
+                trash_file,
+                trash_files,
+                trash_album,
+                restore_file,
+                restore_files,
+                restore_album,
+                list_files,
+                list_albums,
+                list_expired_files,
+                delete_expired_albums,
+                delete_albums,
+            } = self;
+            vec![
+                trash_file,
+                trash_files,
+                trash_album,
+                restore_file,
+                restore_files,
+                restore_album,
+                list_files,
+                list_albums,
+                list_expired_files,
+                delete_expired_albums,
+                delete_albums,
+            ]
+        }
+    }
+
+    pub struct SearchStatements {
+        /// WITH q AS (SELECT websearch_to_tsquery('simple', $2) AS query),
+        /// hits AS (files matching by own search_vector UNION ALL files of albums or tags matching by name),
+        /// ranked AS (SELECT id, sum(rank), string_agg(group_name) FROM hits GROUP BY id)
+        /// SELECT id, updated_at, taken_at, user_id, file_name, media_type, width, height, favorite of $6,
+        ///     comment count, rank,
+        ///     ts_headline(...)
+        /// WHERE (rank, id) < ($3, $4)
+        /// ORDER BY rank DESC, id DESC LIMIT $5
+        pub search_media_files: Stmt,
+    }
+    impl SearchStatements {
+        pub fn new() -> Self {
+            Self {
+                search_media_files: Stmt::new(
+                    r#"WITH q AS (
+                            SELECT websearch_to_tsquery('simple', $2) AS query
+                        ), hits AS (
+                            SELECT m.id, ts_rank(m.search_vector, q.query)::float4 AS rank, NULL::varchar AS group_name
+                            FROM media_files m
+                            CROSS JOIN q
+                            WHERE m.space_id = $1 AND m.deleted_at IS NULL AND m.search_vector @@ q.query
+                            UNION ALL
+                            SELECT m.id, (ts_rank(a.search_vector, q.query) / 2)::float4, a.name
+                            FROM albums a
+                            CROSS JOIN q
+                            INNER JOIN album_media_files amf ON amf.album_id = a.id
+                            INNER JOIN media_files m ON m.id = amf.media_file_id
+                            WHERE a.space_id = $1 AND a.deleted_at IS NULL AND a.search_vector @@ q.query
+                              AND m.space_id = $1 AND m.deleted_at IS NULL
+                            UNION ALL
+                            SELECT m.id, (ts_rank(to_tsvector('simple', t.name), q.query) / 2)::float4, t.name
+                            FROM tags t
+                            CROSS JOIN q
+                            INNER JOIN media_file_tags mft ON mft.tag_id = t.id
+                            INNER JOIN media_files m ON m.id = mft.media_file_id
+                            WHERE t.space_id = $1 AND to_tsvector('simple', t.name) @@ q.query
+                              AND m.space_id = $1 AND m.deleted_at IS NULL
+                        ), ranked AS (
+                            SELECT id, sum(rank)::float4 AS rank, string_agg(group_name, ' ') AS group_names
+                            FROM hits
+                            GROUP BY id
+                        )
+                        SELECT m.id, m.updated_at, m.taken_at, m.user_id, m.file_name, m.media_type, m.width, m.height,
+                            EXISTS (SELECT 1 FROM favorites f WHERE f.user_id = $6 AND f.media_file_id = m.id),
+                            (SELECT count(*) FROM comments c WHERE c.media_file_id = m.id), r.rank,
+                            ts_headline('simple',
+                                concat_ws(' ', m.file_name, m.metadata->'file_meta'->>'make',
+                                    m.metadata->'file_meta'->>'model', m.metadata->'file_meta'->>'software',
+                                    r.group_names),
+                                q.query, 'HighlightAll=true') as highlight
+                        FROM ranked r
+                        CROSS JOIN q
+                        INNER JOIN media_files m ON m.id = r.id
+                        WHERE (r.rank, r.id) < ($3, $4)
+                        ORDER BY r.rank DESC, r.id DESC
+                        LIMIT $5"#,
+                    &[Type::UUID, Type::TEXT, Type::FLOAT4, Type::UUID, Type::INT8, Type::UUID],
+                ),
+            }
+        }
+
+        pub fn all(&self) -> Vec<&Stmt> {
+            let Self {
+                search_media_files,
+            } = self;
+            vec![search_media_files]
+        }
+    }
+
+    pub struct GeoStatements {
+        /// SELECT count(*), avg(lat), avg(lon), latest taken id, min(lat), max(lat), min(lon), max(lon)
+        /// FROM media_files
+        /// WHERE space_id = $1 AND deleted_at IS NULL AND point(lon, lat) <@ box(point($4, $2), point($5, $3))
+        /// GROUP BY floor(lat / $6), floor(lon / $6)
+        /// ORDER BY count DESC
+        pub list_map_clusters: Stmt,
+    }
+    impl GeoStatements {
+        pub fn new() -> Self {
+            Self {
+                list_map_clusters: Stmt::new(
+                    r#"WITH points AS (
+                            SELECT id, taken_at, latitude AS lat, longitude AS lon,
+                                floor(latitude / $6) AS cell_lat, floor(longitude / $6) AS cell_lon
+                            FROM media_files
+                            WHERE space_id = $1 AND deleted_at IS NULL
+                              AND latitude IS NOT NULL
+                              AND (point(longitude, latitude)
+                                    <@ box(point($4, $2), point(CASE WHEN $4 <= $5 THEN $5 ELSE 180 END, $3))
+                                OR $4 > $5 AND point(longitude, latitude) <@ box(point(-180, $2), point($5, $3)))
+                        ),
+                        latest AS (
+                            SELECT DISTINCT ON (cell_lat, cell_lon) cell_lat, cell_lon, id
+                            FROM points
+                            ORDER BY cell_lat, cell_lon, taken_at DESC, id DESC
+                        )
+                        SELECT count(*), avg(p.lat), avg(p.lon), l.id, min(p.lat), max(p.lat), min(p.lon), max(p.lon)
+                        FROM points p
+                        INNER JOIN latest l ON l.cell_lat = p.cell_lat AND l.cell_lon = p.cell_lon
+                        GROUP BY p.cell_lat, p.cell_lon, l.id
+                        ORDER BY count(*) DESC"#,
+                    &[Type::UUID, Type::FLOAT8, Type::FLOAT8, Type::FLOAT8, Type::FLOAT8, Type::FLOAT8],
+                ),
+            }
+        }
+
+        pub fn all(&self) -> Vec<&Stmt> {
+            let Self {
+                list_map_clusters,
+            } = self;
+            vec![list_map_clusters]
+        }
+    }
+
+    pub struct FavoriteStatements {
+        /// WITH found AS (SELECT id FROM media_files WHERE id = $3 AND space_id = $2 AND deleted_at IS NULL)
+        /// INSERT INTO favorites (user_id, media_file_id) SELECT $1, id FROM found ON CONFLICT DO NOTHING
+        /// SELECT id FROM found
+        pub insert: Stmt,
+
+        /// DELETE FROM favorites USING media_files
+        /// WHERE user_id = $1 AND media_file_id = $3 AND media_files.space_id = $2
+        pub delete: Stmt,
+
+        /// SELECT id, updated_at, taken_at, user_id, file_name, media_type, width, height, true, comment count,
+        ///     space_id, favorites.created_at
+        /// FROM favorites INNER JOIN media_files
+        /// WHERE favorites.user_id = $1 AND deleted_at IS NULL AND space of $1 by users_spaces or default_space
+        ///     AND space_id = $2 AND (favorites.created_at, id) < ($3, $4)
+        /// ORDER BY favorites.created_at DESC, id DESC LIMIT $5
+        pub list: Stmt,
+    }
+    impl FavoriteStatements {
+        pub fn new() -> Self {
+            Self {
+                insert: Stmt::new(
+                    r#"WITH found AS (
+                            SELECT id FROM media_files
+                            WHERE id = $3 AND space_id = $2 AND deleted_at IS NULL
+                        ), starred AS (
+                            INSERT INTO favorites (user_id, media_file_id)
+                            SELECT $1, id FROM found
+                            ON CONFLICT DO NOTHING
+                        )
+                        SELECT id FROM found"#,
+                    &[Type::UUID, Type::UUID, Type::UUID],
+                ),
+                delete: Stmt::new(
+                    r#"DELETE FROM favorites f
+                        USING media_files m
+                        WHERE f.user_id = $1 AND f.media_file_id = $3 AND m.id = f.media_file_id AND m.space_id = $2"#,
+                    &[Type::UUID, Type::UUID, Type::UUID],
+                ),
+                list: Stmt::new(
+                    r#"SELECT m.id, m.updated_at, m.taken_at, m.user_id, m.file_name, m.media_type, m.width, m.height,
+                            true, (SELECT count(*) FROM comments c WHERE c.media_file_id = m.id), m.space_id, f.created_at
+                        FROM favorites f
+                        INNER JOIN media_files m ON m.id = f.media_file_id
+                        WHERE f.user_id = $1 AND m.deleted_at IS NULL
+                          AND (EXISTS (SELECT 1 FROM users_spaces us WHERE us.user_id = $1 AND us.space_id = m.space_id)
+                            OR EXISTS (SELECT 1 FROM default_space ds
+                                WHERE ds.user_fk_id = $1 AND ds.space_fk_id = m.space_id))
+                          AND ($2::uuid IS NULL OR m.space_id = $2)
+                          AND (f.created_at, m.id) < ($3, $4)
+                        ORDER BY f.created_at DESC, m.id DESC
+                        LIMIT $5"#,
+                    &[Type::UUID, Type::UUID, Type::TIMESTAMPTZ, Type::UUID, Type::INT8],
+                ),
+            }
+        }
+
+        pub fn all(&self) -> Vec<&Stmt> {
+            let Self {
+                insert,
+                delete,
+                list,
+            } = self;
+            vec![insert, delete, list]
+        }
+    }
+
+    pub struct TagStatements {
+        /// INSERT INTO tags (id, space_id, name) VALUES ($1, $2, $3) RETURNING *
+        pub insert: Stmt,
+
+        /// SELECT * FROM tags WHERE id = $1 AND space_id = $2
+        pub get: Stmt,
+
+        /// SELECT tags.*, count of files out of trash FROM tags WHERE space_id = $1 ORDER BY lower(name), id
+        pub list: Stmt,
+
+        /// UPDATE tags SET name = $3, updated_at = now() WHERE id = $1 AND space_id = $2 RETURNING *
+        pub rename: Stmt,
+
+        /// WITH merged AS (INSERT INTO media_file_tags (tag_id, media_file_id) SELECT $3, files of $2 ...
+        ///     ON CONFLICT DO NOTHING)
+        /// DELETE FROM tags WHERE id = $2 AND space_id = $1 AND $3 is a tag of $1 RETURNING id
+        pub merge: Stmt,
+
+        /// DELETE FROM tags WHERE id = $1 AND space_id = $2
+        pub delete: Stmt,
+
+        /// WITH found AS (SELECT m.id FROM UNNEST($2) INNER JOIN media_files m, tags t ...)
+        /// INSERT INTO media_file_tags (tag_id, media_file_id) SELECT $1, id FROM found
+        /// ON CONFLICT DO NOTHING
+        /// SELECT id FROM found
+        pub link_media_files: Stmt,
+
+        /// DELETE FROM media_file_tags mft USING tags t, UNNEST($2)
+        /// WHERE t.id = $1 AND t.space_id = $3 ...
+        /// RETURNING mft.media_file_id
+        pub unlink_media_files: Stmt,
+    }
+    impl TagStatements {
+        pub fn new() -> Self {
             Self {
-                get_app_by_identifier: db
-                    .prepare_typed(r#"SELECT * FROM native_app WHERE secure_identifier = $1"#, &[Type::VARCHAR])
-                    .await
-                    .unwrap(),
+                insert: Stmt::new(
+                    r#"INSERT INTO tags (id, space_id, name) VALUES ($1, $2, $3) RETURNING *"#,
+                    &[Type::UUID, Type::UUID, Type::VARCHAR],
+                ),
+                get: Stmt::new(r#"SELECT * FROM tags WHERE id = $1 AND space_id = $2"#, &[Type::UUID, Type::UUID]),
+                list: Stmt::new(
+                    r#"SELECT t.*, (
+                            SELECT count(*) FROM media_file_tags mft
+                            INNER JOIN media_files m ON m.id = mft.media_file_id
+                            WHERE mft.tag_id = t.id AND m.deleted_at IS NULL
+                        )
+                        FROM tags t
+                        WHERE t.space_id = $1
+                        ORDER BY lower(t.name), t.id"#,
+                    &[Type::UUID],
+                ),
+                rename: Stmt::new(
+                    r#"UPDATE tags SET name = $3, updated_at = now()
+                        WHERE id = $1 AND space_id = $2
+                        RETURNING *"#,
+                    &[Type::UUID, Type::UUID, Type::VARCHAR],
+                ),
+                merge: Stmt::new(
+                    r#"WITH target AS (
+                            SELECT id FROM tags WHERE id = $3 AND space_id = $1
+                        ), merged AS (
+                            INSERT INTO media_file_tags (tag_id, media_file_id)
+                            SELECT target.id, mft.media_file_id
+                            FROM media_file_tags mft
+                            INNER JOIN tags s ON s.id = mft.tag_id
+                            CROSS JOIN target
+                            WHERE mft.tag_id = $2 AND s.space_id = $1
+                            ON CONFLICT DO NOTHING
+                        )
+                        DELETE FROM tags
+                        WHERE id = $2 AND space_id = $1 AND EXISTS (SELECT 1 FROM target)
+                        RETURNING id"#,
+                    &[Type::UUID, Type::UUID, Type::UUID],
+                ),
+                delete: Stmt::new(r#"DELETE FROM tags WHERE id = $1 AND space_id = $2"#, &[Type::UUID, Type::UUID]),
+                link_media_files: Stmt::new(
+                    r#"WITH found AS (
+                            SELECT DISTINCT m.id
+                            FROM UNNEST($2::uuid[]) AS ids(id)
+                            INNER JOIN media_files m ON m.id = ids.id
+                            INNER JOIN tags t ON t.id = $1
+                            WHERE t.space_id = $3 AND m.space_id = $3 AND m.deleted_at IS NULL
+                        ), linked AS (
+                            INSERT INTO media_file_tags (tag_id, media_file_id)
+                            SELECT $1, id FROM found
+                            ON CONFLICT DO NOTHING
+                        )
+                        SELECT id FROM found"#,
+                    &[Type::UUID, Type::UUID_ARRAY, Type::UUID],
+                ),
+                unlink_media_files: Stmt::new(
+                    r#"DELETE FROM media_file_tags mft
+                        USING tags t, UNNEST($2::uuid[]) AS ids(id)
+                        WHERE mft.tag_id = t.id
+                          AND mft.media_file_id = ids.id
+                          AND t.id = $1
+                          AND t.space_id = $3
+                        RETURNING mft.media_file_id"#,
+                    &[Type::UUID, Type::UUID_ARRAY, Type::UUID],
+                ),
             }
         }
+
+        pub fn all(&self) -> Vec<&Stmt> {
+            let Self {
+                insert,
+                get,
+                list,
+                rename,
+                merge,
+                delete,
+                link_media_files,
+                unlink_media_files,
+            } = self;
+            vec![insert, get, list, rename, merge, delete, link_media_files, unlink_media_files]
+        }
+    }
+
+    pub struct CommentStatements {
+        /// INSERT INTO comments (id, media_file_id, user_id, body)
+        /// SELECT $1, id, $4, $5 FROM media_files WHERE id = $2 AND space_id = $3 AND deleted_at IS NULL
+        /// RETURNING *
+        pub insert: Stmt,
+
+        /// SELECT comments.* FROM comments INNER JOIN media_files
+        /// WHERE comments.id = $1 AND media_file_id = $2 AND space_id = $3 AND deleted_at IS NULL
+        pub get: Stmt,
+
+        /// UPDATE comments SET body = $2, updated_at = now() WHERE id = $1 RETURNING *
+        pub update: Stmt,
+
+        /// DELETE FROM comments WHERE id = $1
+        pub delete: Stmt,
+
+        /// SELECT comments.* FROM comments INNER JOIN media_files
+        /// WHERE media_file_id = $1 AND space_id = $2 AND deleted_at IS NULL AND (created_at, id) > ($3, $4)
+        /// ORDER BY created_at, id LIMIT $5
+        pub list: Stmt,
+    }
+    impl CommentStatements {
+        pub fn new() -> Self {
+            Self {
+                insert: Stmt::new(
+                    r#"INSERT INTO comments (id, media_file_id, user_id, body)
+                        SELECT $1, m.id, $4, $5
+                        FROM media_files m
+                        WHERE m.id = $2 AND m.space_id = $3 AND m.deleted_at IS NULL
+                        RETURNING *"#,
+                    &[Type::UUID, Type::UUID, Type::UUID, Type::UUID, Type::VARCHAR],
+                ),
+                get: Stmt::new(
+                    r#"SELECT c.*
+                        FROM comments c
+                        INNER JOIN media_files m ON m.id = c.media_file_id
+                        WHERE c.id = $1 AND c.media_file_id = $2 AND m.space_id = $3 AND m.deleted_at IS NULL"#,
+                    &[Type::UUID, Type::UUID, Type::UUID],
+                ),
+                update: Stmt::new(
+                    r#"UPDATE comments SET body = $2, updated_at = now()
+                        WHERE id = $1
+                        RETURNING *"#,
+                    &[Type::UUID, Type::VARCHAR],
+                ),
+                delete: Stmt::new(r#"DELETE FROM comments WHERE id = $1"#, &[Type::UUID]),
+                list: Stmt::new(
+                    r#"SELECT c.*
+                        FROM comments c
+                        INNER JOIN media_files m ON m.id = c.media_file_id
+                        WHERE c.media_file_id = $1 AND m.space_id = $2 AND m.deleted_at IS NULL
+                          AND (c.created_at, c.id) > ($3, $4)
+                        ORDER BY c.created_at, c.id
+                        LIMIT $5"#,
+                    &[Type::UUID, Type::UUID, Type::TIMESTAMPTZ, Type::UUID, Type::INT8],
+                ),
+            }
+        }
+
+        pub fn all(&self) -> Vec<&Stmt> {
+            let Self {
+                insert,
+                get,
+                update,
+                delete,
+                list,
+            } = self;
+            vec![insert, get, update, delete, list]
+        }
+    }
+
+    pub struct AuditStatements {
+        /// INSERT INTO audit_log (id, space_id, actor_id, req_id, action, target_id, file_ids)
+        /// VALUES ($1, $2, $3, $4, $5, $6, $7)
+        pub insert: Stmt,
+
+        /// SELECT * FROM audit_log
+        /// WHERE space_id = $1 AND (created_at, id) < ($2, $3)
+        ///     AND actor_id = $5 AND action = $6 AND (target_id = $7 OR $7 = ANY(file_ids))
+        ///     AND created_at >= $8 AND created_at < $9
+        /// ORDER BY created_at DESC, id DESC LIMIT $4
+        pub list: Stmt,
+    }
+    impl AuditStatements {
+        pub fn new() -> Self {
+            Self {
+                insert: Stmt::new(
+                    r#"INSERT INTO audit_log (id, space_id, actor_id, req_id, action, target_id, file_ids)
+                        VALUES ($1, $2, $3, $4, $5, $6, $7)"#,
+                    &[Type::UUID, Type::UUID, Type::UUID, Type::VARCHAR, Type::INT2, Type::UUID, Type::UUID_ARRAY],
+                ),
+                list: Stmt::new(
+                    r#"SELECT * FROM audit_log
+                        WHERE space_id = $1
+                          AND (created_at, id) < ($2, $3)
+                          AND ($5::uuid IS NULL OR actor_id = $5)
+                          AND ($6::int2 IS NULL OR action = $6)
+                          AND ($7::uuid IS NULL OR target_id = $7 OR $7 = ANY(file_ids))
+                          AND ($8::timestamptz IS NULL OR created_at >= $8)
+                          AND ($9::timestamptz IS NULL OR created_at < $9)
+                        ORDER BY created_at DESC, id DESC
+                        LIMIT $4"#,
+                    &[
+                        Type::UUID,
+                        Type::TIMESTAMPTZ,
+                        Type::UUID,
+                        Type::INT8,
+                        Type::UUID,
+                        Type::INT2,
+                        Type::UUID,
+                        Type::TIMESTAMPTZ,
+                        Type::TIMESTAMPTZ,
+                    ],
+                ),
+            }
+        }
+
+        pub fn all(&self) -> Vec<&Stmt> {
+            let Self {
+                insert,
+                list,
+            } = self;
+            vec![insert, list]
+        }
     }
 }
@@ -14,7 +14,6 @@ pub trait NativeAppDs: Send + Sync {
 impl NativeAppDs for Datastore {
     async fn validate_native_app(&self, identifier: String) -> AppResult<()> {
         let rows = self
-            .db
             .query(&self.native_app_stmts.get_app_by_identifier, &[&identifier])
             .await
             .map_err(|err| ErrType::DbError.err(err, "Failed to get native app by identifier"))?;
@@ -0,0 +1,61 @@
+use lib_core::{AppResult, ErrType};
+use uuid::Uuid;
+
+use super::{storage::FileMeta, Datastore, Keyset};
+
+/// File matching a search with its rank and highlighted matched text
+pub struct SearchFileMeta {
+    pub file: FileMeta,
+    pub rank: f32,
+    pub highlight: String,
+}
+impl TryFrom<tokio_postgres::Row> for SearchFileMeta {
+    type Error = tokio_postgres::error::Error;
+
+    fn try_from(value: tokio_postgres::Row) -> Result<Self, Self::Error> {
+        let rank = value.try_get(10)?;
+        let highlight = value.try_get(11)?;
+        Ok(Self {
+            file: FileMeta::try_from(value)?,
+            rank,
+            highlight,
+        })
+    }
+}
+
+pub trait SearchDs: Send + Sync {
+    /// Files of the space matching `query` by name, camera metadata or album name, best ranked first
+    fn search_files(
+        &self,
+        space_id: &Uuid,
+        user_id: &Uuid,
+        query: &str,
+        after: Option<Keyset<f32>>,
+        limit: i64,
+    ) -> impl Future<Output = AppResult<Vec<SearchFileMeta>>> + Send;
+}
+
+impl SearchDs for Datastore {
+    async fn search_files(
+        &self,
+        space_id: &Uuid,
+        user_id: &Uuid,
+        query: &str,
+        after: Option<Keyset<f32>>,
+        limit: i64,
+    ) -> AppResult<Vec<SearchFileMeta>> {
+        let (rank, id) = Keyset::desc(after);
+        let rows = self
+            .query(&self.search_stmts.search_media_files, &[space_id, &query, &rank, &id, &limit, user_id])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to search files"))?;
+
+        let size = rows.len();
+        rows.into_iter().try_fold(Vec::with_capacity(size), |mut acc, row| {
+            let f = SearchFileMeta::try_from(row)
+                .map_err(|err| ErrType::DbError.err(err, "Failed to parse searched files"))?;
+            acc.push(f);
+            Ok(acc)
+        })
+    }
+}
@@ -2,7 +2,7 @@ use chrono::{DateTime, Utc};
 use lib_core::{AppResult, ErrType, ErrorContext};
 use uuid::Uuid;
 
-use super::Datastore;
+use super::{transaction::TransactionDs, Datastore};
 
 pub struct Space {
     pub id: Uuid,
@@ -12,6 +12,9 @@ pub struct Space {
     pub name: String,
     pub description: String,
     pub picture_url: String,
+
+    /// Storage quota in bytes, `None` falls back to configured default
+    pub storage_quota: Option<i64>,
 }
 impl TryFrom<tokio_postgres::Row> for Space {
     type Error = tokio_postgres::Error;
@@ -24,6 +27,7 @@ impl TryFrom<tokio_postgres::Row> for Space {
             name: value.try_get(3)?,
             description: value.try_get(4)?,
             picture_url: value.try_get(5)?,
+            storage_quota: value.try_get(6)?,
         };
         Ok(row)
     }
@@ -38,6 +42,7 @@ pub trait SpaceDs: Send + Sync {
         name: &'static str,
         description: &'static str,
     ) -> impl Future<Output = AppResult<Space>> + Send;
+    fn delete_space(&self, id: &Uuid) -> impl Future<Output = AppResult<()>> + Send;
     fn get_default_space(&self, user_id: &Uuid) -> impl Future<Output = AppResult<Option<Space>>> + Send;
     fn set_default_space(&self, user_id: &Uuid) -> impl Future<Output = AppResult<Space>> + Send;
 }
@@ -45,7 +50,6 @@ pub trait SpaceDs: Send + Sync {
 impl SpaceDs for Datastore {
     async fn get_space_by_id(&self, id: &Uuid) -> AppResult<Option<Space>> {
         let rows = self
-            .db
             .query(&self.space_stmts.get_by_id, &[&id])
             .await
             .map_err(|err| ErrType::DbError.err(err, "Failed to get space by id"))?;
@@ -60,7 +64,6 @@ impl SpaceDs for Datastore {
 
     async fn insert_space(&self, name: &str, description: &str) -> AppResult<Space> {
         let row = self
-            .db
             .query_one(&self.space_stmts.insert, &[&Uuid::now_v7(), &name, &description, &""])
             .await
             .map_err(|err| ErrType::DbError.err(err, "Failed to insert space"))?;
@@ -70,7 +73,6 @@ impl SpaceDs for Datastore {
 
     async fn update_space(&self, id: Uuid, name: &'static str, description: &'static str) -> AppResult<Space> {
         let row = self
-            .db
             .query_one(&self.space_stmts.update, &[&id, &name, &description])
             .await
             .map_err(|err| ErrType::DbError.err(err, "Failed to update space"))?;
@@ -78,9 +80,17 @@ impl SpaceDs for Datastore {
         Space::try_from(row).map_err(|err| ErrType::DbError.err(err, "Failed to parse updated space row"))
     }
 
+    async fn delete_space(&self, id: &Uuid) -> AppResult<()> {
+        let _ = self
+            .query(&self.space_stmts.delete, &[id])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to delete space"))?;
+
+        Ok(())
+    }
+
     async fn get_default_space(&self, user_id: &Uuid) -> AppResult<Option<Space>> {
         let rows = self
-            .db
             .query(&self.default_space_stmts.get_default_space, &[&user_id])
             .await
             .map_err(|err| ErrType::DbError.err(err, "Failed to query default space for user"))?;
@@ -94,21 +104,27 @@ impl SpaceDs for Datastore {
     }
 
     async fn set_default_space(&self, user_id: &Uuid) -> AppResult<Space> {
-        let space = self
-            .insert_space(&format!("{}'s space", user_id), &format!("Default space for {user_id}"))
-            .await
-            .context("Setting default space")?;
+        let tx = self.begin().await?;
 
-        let rows = self
-            .db
-            .query(&self.default_space_stmts.set_default_space, &[&space.id, &user_id])
-            .await
-            .map_err(|err| ErrType::DbError.err(err, "Failed to query insert default space"))?;
+        let result = async {
+            let space = tx
+                .insert_space(&format!("{}'s space", user_id), &format!("Default space for {user_id}"))
+                .await
+                .context("Setting default space")?;
+
+            let rows = tx
+                .query(&tx.default_space_stmts.set_default_space, &[&space.id, &user_id])
+                .await
+                .map_err(|err| ErrType::DbError.err(err, "Failed to query insert default space"))?;
+
+            if rows.is_empty() {
+                return Err(ErrType::DbError.msg("Failed to insert default space, empty rows"));
+            }
 
-        if rows.is_empty() {
-            return Err(ErrType::DbError.msg("Failed to insert default space, empty rows"));
+            Ok(space)
         }
+        .await;
 
-        Ok(space)
+        tx.finish(result).await
     }
 }
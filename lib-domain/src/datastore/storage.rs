@@ -1,15 +1,18 @@
 use chrono::{DateTime, Utc};
+use deadpool_postgres::PoolError;
 use lib_core::{
     smq_dto::{
         res::{FileData, ImageData},
         MediaMetadata, MediaType,
     },
-    AppResult, ErrType,
+    AppError, AppResult, ErrType,
 };
 use serde::{Deserialize, Serialize};
+use tokio_postgres::error::SqlState;
+use utoipa::ToSchema;
 use uuid::Uuid;
 
-use crate::datastore::Datastore;
+use crate::datastore::{usage::media_type_literal, Datastore, Keyset};
 
 #[derive(Serialize, Deserialize)]
 pub struct Metadata {
@@ -116,6 +119,12 @@ pub struct MediaFile {
     pub preview_key: Option<String>,
     pub node_size: i64,
     pub metadata: NodeMetadata,
+
+    /// Set when reconciliation found keys missing from storage
+    pub broken_at: Option<DateTime<Utc>>,
+
+    /// Set while the file is in trash
+    pub deleted_at: Option<DateTime<Utc>>,
 }
 impl TryFrom<tokio_postgres::Row> for MediaFile {
     type Error = tokio_postgres::error::Error;
@@ -134,6 +143,8 @@ impl TryFrom<tokio_postgres::Row> for MediaFile {
             preview_key: value.try_get(9)?,
             node_size: value.try_get(10)?,
             metadata: value.try_get(11)?,
+            broken_at: value.try_get(12)?,
+            deleted_at: value.try_get(13)?,
         })
     }
 }
@@ -147,6 +158,17 @@ pub struct Album {
     pub space_id: Uuid,
     pub name: String,
     pub legacy_path: String,
+
+    /// Set while the album is in trash
+    pub deleted_at: Option<DateTime<Utc>>,
+
+    pub description: Option<String>,
+
+    /// Cover chosen by the user, see [`AlbumDetails::cover_file_id`]
+    pub cover_file_id: Option<Uuid>,
+
+    /// Order album files are listed in
+    pub sort_mode: AlbumSortMode,
 }
 impl TryFrom<tokio_postgres::Row> for Album {
     type Error = tokio_postgres::error::Error;
@@ -160,52 +182,304 @@ impl TryFrom<tokio_postgres::Row> for Album {
             space_id: value.try_get(4)?,
             name: value.try_get(5)?,
             legacy_path: value.try_get(6)?,
+            deleted_at: value.try_get(7)?,
+            // search_vector: 8
+            description: value.try_get(9)?,
+            cover_file_id: value.try_get(10)?,
+            sort_mode: value.try_get(11)?,
+        })
+    }
+}
+
+/// Album with counts over its files out of trash
+pub struct AlbumDetails {
+    pub album: Album,
+    pub item_count: i64,
+
+    /// Total size of the files in bytes
+    pub total_size: i64,
+
+    /// `taken_at` range of the files
+    pub first_taken_at: Option<DateTime<Utc>>,
+    pub last_taken_at: Option<DateTime<Utc>>,
+
+    /// Chosen cover while it is in the album, the latest taken file otherwise
+    pub cover_file_id: Option<Uuid>,
+    pub cover_thumbnail_key: Option<String>,
+}
+impl TryFrom<tokio_postgres::Row> for AlbumDetails {
+    type Error = tokio_postgres::error::Error;
+
+    fn try_from(value: tokio_postgres::Row) -> Result<Self, Self::Error> {
+        // album columns: 0..=11
+        let item_count = value.try_get(12)?;
+        let total_size = value.try_get(13)?;
+        let first_taken_at = value.try_get(14)?;
+        let last_taken_at = value.try_get(15)?;
+        let cover_file_id = value.try_get(16)?;
+        let cover_thumbnail_key = value.try_get(17)?;
+        Ok(Self {
+            album: Album::try_from(value)?,
+            item_count,
+            total_size,
+            first_taken_at,
+            last_taken_at,
+            cover_file_id,
+            cover_thumbnail_key,
+        })
+    }
+}
+
+/// Album fields to change, `None` keeps the current value
+#[derive(Default)]
+pub struct AlbumUpdate {
+    pub name: Option<String>,
+
+    /// `Some(None)` clears the description
+    pub description: Option<Option<String>>,
+
+    /// `Some(None)` falls back to the latest taken file
+    pub cover_file_id: Option<Option<Uuid>>,
+
+    pub sort_mode: Option<AlbumSortMode>,
+}
+
+#[derive(Debug, ToSchema, Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum AlbumSortMode {
+    Name,
+
+    /// Oldest taken first
+    CaptureDate,
+
+    /// Position set by reordering, new files are appended
+    Manual,
+}
+impl AlbumSortMode {
+    pub fn value(&self) -> i16 {
+        match self {
+            AlbumSortMode::Name => 0,
+            AlbumSortMode::CaptureDate => 1,
+            AlbumSortMode::Manual => 2,
+        }
+    }
+}
+impl TryFrom<i16> for AlbumSortMode {
+    type Error = AppError;
+
+    fn try_from(value: i16) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(AlbumSortMode::Name),
+            1 => Ok(AlbumSortMode::CaptureDate),
+            2 => Ok(AlbumSortMode::Manual),
+            x => Err(ErrType::DbError.msg(format!("Invalid album sort mode literal: {x}"))),
+        }
+    }
+}
+impl<'a> tokio_postgres::types::FromSql<'a> for AlbumSortMode {
+    fn from_sql(
+        ty: &tokio_postgres::types::Type,
+        raw: &'a [u8],
+    ) -> Result<Self, Box<dyn std::error::Error + Sync + Send>> {
+        let mode_literal = i16::from_sql(ty, raw)?;
+        let mode = AlbumSortMode::try_from(mode_literal)?;
+        Ok(mode)
+    }
+
+    fn accepts(ty: &tokio_postgres::types::Type) -> bool {
+        matches!(*ty, tokio_postgres::types::Type::INT2)
+    }
+}
+
+/// Album file with its manual order position
+pub struct AlbumFileMeta {
+    pub file: FileMeta,
+    pub position: i64,
+}
+impl TryFrom<tokio_postgres::Row> for AlbumFileMeta {
+    type Error = tokio_postgres::error::Error;
+
+    fn try_from(value: tokio_postgres::Row) -> Result<Self, Self::Error> {
+        let position = value.try_get(10)?;
+        Ok(Self {
+            file: FileMeta::try_from(value)?,
+            position,
         })
     }
 }
 
+/// Side of the anchor file reordered files are placed on
+#[derive(Debug, ToSchema, Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum AlbumPlacement {
+    Before,
+    After,
+}
+
+/// Outcome of [`StorageDs::reorder_album_files`]
+pub enum AlbumReorder {
+    /// Anchor file is not in the album
+    AnchorNotFound,
+
+    /// Not enough room between the anchor and its neighbour, positions need renumbering first
+    NoGap,
+
+    /// Ids of the files that were repositioned
+    Moved(Vec<Uuid>),
+}
+
+/// Columns promoted out of metadata, written alongside it
+struct MediaColumns {
+    taken_at: DateTime<Utc>,
+    media_type: &'static str,
+    width: i32,
+    height: i32,
+    duration_secs: Option<f64>,
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+}
+impl MediaColumns {
+    fn new(file_meta: &Metadata, thumbnail: &ImageData, media_type: MediaType, updated_date: DateTime<Utc>) -> Self {
+        Self {
+            taken_at: file_meta.date_time.unwrap_or(updated_date),
+            media_type: media_type_literal(media_type),
+            width: thumbnail.width,
+            height: thumbnail.height,
+            duration_secs: file_meta
+                .duration
+                .as_deref()
+                .and_then(parse_duration_secs)
+                .or_else(|| file_meta.media_duration.as_deref().and_then(parse_duration_secs)),
+            latitude: file_meta.latitude,
+            longitude: file_meta.longitude,
+        }
+    }
+}
+
+/// Seconds of an exiftool duration, `12.5 s`, `0:01:23` or `1:23`, optionally suffixed with `(approx)`
+fn parse_duration_secs(duration: &str) -> Option<f64> {
+    let duration = duration.trim().trim_end_matches("(approx)").trim_end();
+    if let Some(secs) = duration.strip_suffix(" s") {
+        return secs.parse().ok();
+    }
+
+    duration.split(':').try_fold(0.0, |total, part| part.parse::<f64>().ok().map(|part| total * 60.0 + part))
+}
+
+/// Listing columns of a media file
+///
+/// Selected as `id, updated_at, taken_at, user_id, file_name, media_type, width, height`.
 pub struct FileMeta {
     pub id: Uuid,
     pub updated_at: DateTime<Utc>,
+    pub taken_at: DateTime<Utc>,
     pub file_name: String,
     pub media_type: MediaType,
     pub user: Option<Uuid>,
     pub width: i32,
     pub height: i32,
+
+    /// Starred by the listing user
+    pub favorite: bool,
+    pub comment_count: i64,
 }
 impl TryFrom<tokio_postgres::Row> for FileMeta {
     type Error = tokio_postgres::error::Error;
 
     fn try_from(value: tokio_postgres::Row) -> Result<Self, Self::Error> {
-        let meta: NodeMetadata = value.get(11);
-        let (width, height) = meta.thumbnail_meta.map(|m| (m.width, m.height)).unwrap_or((0, 0));
+        let media_type: Option<String> = value.try_get(5)?;
         Ok(Self {
             id: value.try_get(0)?,
-            updated_at: value.try_get(2)?,
-            file_name: value.try_get(6)?,
-            media_type: meta.media_type.unwrap_or(MediaType::Image),
+            updated_at: value.try_get(1)?,
+            taken_at: value.try_get(2)?,
             user: value.try_get(3).ok(),
-            width,
-            height,
+            file_name: value.try_get(4)?,
+            media_type: media_type
+                .and_then(|media_type| serde_json::from_value(serde_json::Value::String(media_type)).ok())
+                .unwrap_or(MediaType::Image),
+            width: value.try_get::<_, Option<i32>>(6)?.unwrap_or(0),
+            height: value.try_get::<_, Option<i32>>(7)?.unwrap_or(0),
+            favorite: value.try_get(8)?,
+            comment_count: value.try_get(9)?,
         })
     }
 }
 
-pub struct GalleryFileMeta(pub FileMeta);
-impl TryFrom<tokio_postgres::Row> for GalleryFileMeta {
+/// Orientation of the displayed media, square media is neither
+#[derive(Debug, ToSchema, Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Orientation {
+    Portrait,
+    Landscape,
+}
+
+pub fn orientation_literal(orientation: Orientation) -> &'static str {
+    match orientation {
+        Orientation::Portrait => "portrait",
+        Orientation::Landscape => "landscape",
+    }
+}
+
+/// Gallery filters combined with AND, unset filters match every file
+#[derive(Default)]
+pub struct GalleryFilter {
+    /// `taken_at` range, end exclusive
+    pub taken_after: Option<DateTime<Utc>>,
+    pub taken_before: Option<DateTime<Utc>>,
+
+    pub media_type: Option<MediaType>,
+
+    /// Camera make and model, case insensitive
+    pub make: Option<String>,
+    pub model: Option<String>,
+
+    pub uploader: Option<Uuid>,
+    pub orientation: Option<Orientation>,
+    pub has_location: Option<bool>,
+    pub bounds: Option<GeoBounds>,
+    pub tag_id: Option<Uuid>,
+}
+
+/// GPS bounding box, `min_lon > max_lon` crosses the antimeridian
+pub struct GeoBounds {
+    pub min_lat: f64,
+    pub max_lat: f64,
+    pub min_lon: f64,
+    pub max_lon: f64,
+}
+
+/// Granularity of timeline buckets
+#[derive(Debug, ToSchema, Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum TimelineGranularity {
+    Year,
+    Month,
+    Day,
+}
+
+pub fn granularity_literal(granularity: TimelineGranularity) -> &'static str {
+    match granularity {
+        TimelineGranularity::Year => "year",
+        TimelineGranularity::Month => "month",
+        TimelineGranularity::Day => "day",
+    }
+}
+
+/// Files taken within `[start, end)`, a calendar year, month or day of the requested timezone
+pub struct TimelineBucket {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub count: i64,
+}
+impl TryFrom<tokio_postgres::Row> for TimelineBucket {
     type Error = tokio_postgres::error::Error;
 
     fn try_from(value: tokio_postgres::Row) -> Result<Self, Self::Error> {
-        let media_type: String = value.try_get(4)?;
-        Ok(Self(FileMeta {
-            id: value.try_get(0)?,
-            updated_at: value.try_get(1)?,
-            user: value.try_get(2).ok(),
-            file_name: value.try_get(3)?,
-            media_type: serde_json::from_value(serde_json::Value::String(media_type)).unwrap_or(MediaType::Image),
-            width: value.try_get(5)?,
-            height: value.try_get(6)?,
-        }))
+        Ok(Self {
+            start: value.try_get(0)?,
+            end: value.try_get(1)?,
+            count: value.try_get(2)?,
+        })
     }
 }
 
@@ -224,6 +498,60 @@ impl TryFrom<tokio_postgres::Row> for StreamKeys {
     }
 }
 
+/// Storage keys referenced by a media file
+pub struct MediaKeys {
+    pub id: Uuid,
+    pub object_key: String,
+    pub thumbnail_key: Option<String>,
+    pub preview_key: Option<String>,
+}
+impl TryFrom<tokio_postgres::Row> for MediaKeys {
+    type Error = tokio_postgres::error::Error;
+
+    fn try_from(value: tokio_postgres::Row) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: value.try_get(0)?,
+            object_key: value.try_get(1)?,
+            thumbnail_key: value.try_get(2)?,
+            preview_key: value.try_get(3)?,
+        })
+    }
+}
+
+/// File selected for a ZIP archive
+pub struct ArchiveFile {
+    pub id: Uuid,
+    pub file_name: String,
+    pub object_key: String,
+}
+impl TryFrom<tokio_postgres::Row> for ArchiveFile {
+    type Error = tokio_postgres::error::Error;
+
+    fn try_from(value: tokio_postgres::Row) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: value.try_get(0)?,
+            file_name: value.try_get(1)?,
+            object_key: value.try_get(2)?,
+        })
+    }
+}
+
+/// Uploader of a file, checked by bulk operations
+pub struct FileOwner {
+    pub id: Uuid,
+    pub user_id: Uuid,
+}
+impl TryFrom<tokio_postgres::Row> for FileOwner {
+    type Error = tokio_postgres::error::Error;
+
+    fn try_from(value: tokio_postgres::Row) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: value.try_get(0)?,
+            user_id: value.try_get(1)?,
+        })
+    }
+}
+
 pub struct StreamKey {
     pub key: String,
 }
@@ -238,6 +566,7 @@ impl TryFrom<tokio_postgres::Row> for StreamKey {
 }
 
 pub trait StorageDs: Send + Sync {
+    /// Inserts file or refreshes the one with the same hash, fails when that file is in trash
     fn get_or_create_file(
         &self,
         user_id: &Uuid,
@@ -260,8 +589,72 @@ pub trait StorageDs: Send + Sync {
     ) -> impl Future<Output = AppResult<MediaFile>> + Send;
 
     fn get_file(&self, space_id: Uuid, file_id: Uuid) -> impl Future<Output = AppResult<Option<MediaFile>>> + Send;
-    fn list_files(&self, space_id: &Uuid, album_id: &Uuid) -> impl Future<Output = AppResult<Vec<FileMeta>>> + Send;
-    fn list_files_gallery(&self, space_id: &Uuid) -> impl Future<Output = AppResult<Vec<GalleryFileMeta>>> + Send;
+    /// Copies file row into `target_space_id` for `user_id`, `None` if the hash already exists there
+    fn copy_file(
+        &self,
+        file_id: &Uuid,
+        space_id: &Uuid,
+        user_id: &Uuid,
+        target_space_id: &Uuid,
+    ) -> impl Future<Output = AppResult<Option<MediaFile>>> + Send;
+    fn get_file_by_hash(
+        &self,
+        space_id: &Uuid,
+        hash: &str,
+    ) -> impl Future<Output = AppResult<Option<MediaFile>>> + Send;
+    /// Album files by name, at most `limit` after `after`
+    fn list_files(
+        &self,
+        space_id: &Uuid,
+        user_id: &Uuid,
+        album_id: &Uuid,
+        after: Option<Keyset<String>>,
+        limit: i64,
+    ) -> impl Future<Output = AppResult<Vec<AlbumFileMeta>>> + Send;
+
+    /// Album files oldest taken first, at most `limit` after `after`
+    fn list_files_by_taken_at(
+        &self,
+        space_id: &Uuid,
+        user_id: &Uuid,
+        album_id: &Uuid,
+        after: Option<Keyset<DateTime<Utc>>>,
+        limit: i64,
+    ) -> impl Future<Output = AppResult<Vec<AlbumFileMeta>>> + Send;
+
+    /// Album files by manual order position, at most `limit` after `after`
+    fn list_files_by_position(
+        &self,
+        space_id: &Uuid,
+        user_id: &Uuid,
+        album_id: &Uuid,
+        after: Option<Keyset<i64>>,
+        limit: i64,
+    ) -> impl Future<Output = AppResult<Vec<AlbumFileMeta>>> + Send;
+
+    /// Space files matching `filter` latest taken first, at most `limit` after `after`
+    fn list_files_gallery(
+        &self,
+        space_id: &Uuid,
+        user_id: &Uuid,
+        filter: &GalleryFilter,
+        after: Option<Keyset<DateTime<Utc>>>,
+        limit: i64,
+    ) -> impl Future<Output = AppResult<Vec<FileMeta>>> + Send;
+
+    /// File counts per bucket of capture time, falling back to update time, newest first
+    ///
+    /// Files of `album_id` only when set.
+    fn list_timeline(
+        &self,
+        space_id: &Uuid,
+        album_id: Option<Uuid>,
+        granularity: TimelineGranularity,
+        timezone: &str,
+    ) -> impl Future<Output = AppResult<Vec<TimelineBucket>>> + Send;
+
+    /// Whether `timezone` names an IANA zone known to the database
+    fn timezone_exists(&self, timezone: &str) -> impl Future<Output = AppResult<bool>> + Send;
     fn get_thumbnail_preview_stream_keys(
         &self,
         space_id: &Uuid,
@@ -273,6 +666,28 @@ pub trait StorageDs: Send + Sync {
         file_id: Uuid,
     ) -> impl Future<Output = AppResult<Option<String>>> + Send;
 
+    /// Keys of every file of the space, trashed ones included
+    fn list_space_media_keys(&self, space_id: &Uuid) -> impl Future<Output = AppResult<Vec<MediaKeys>>> + Send;
+
+    /// Flags `file_ids` as broken and clears the flag on every other file of the space
+    fn set_broken_files(
+        &self,
+        space_id: &Uuid,
+        file_ids: &[Uuid],
+        broken_at: &DateTime<Utc>,
+    ) -> impl Future<Output = AppResult<u64>> + Send;
+
+    fn list_album_archive_files(
+        &self,
+        space_id: &Uuid,
+        album_id: &Uuid,
+    ) -> impl Future<Output = AppResult<Vec<ArchiveFile>>> + Send;
+    fn list_archive_files(
+        &self,
+        space_id: &Uuid,
+        file_ids: &[Uuid],
+    ) -> impl Future<Output = AppResult<Vec<ArchiveFile>>> + Send;
+
     fn create_album(
         &self,
         user_id: &Uuid,
@@ -280,23 +695,75 @@ pub trait StorageDs: Send + Sync {
         album_name: String,
     ) -> impl Future<Output = AppResult<Album>> + Send;
     fn get_album(&self, space_id: &Uuid, album_id: &Uuid) -> impl Future<Output = AppResult<Option<Album>>> + Send;
-    fn list_albums(&self, space_id: Uuid) -> impl Future<Output = AppResult<Vec<Album>>> + Send;
+    fn get_album_details(
+        &self,
+        space_id: &Uuid,
+        album_id: &Uuid,
+    ) -> impl Future<Output = AppResult<Option<AlbumDetails>>> + Send;
+    /// Albums by name, at most `limit` after `after`
+    fn list_albums(
+        &self,
+        space_id: Uuid,
+        after: Option<Keyset<String>>,
+        limit: i64,
+    ) -> impl Future<Output = AppResult<Vec<AlbumDetails>>> + Send;
+
+    /// Updates album, `None` if not found or the cover file is not in the album
+    fn update_album(
+        &self,
+        space_id: &Uuid,
+        album_id: &Uuid,
+        update: AlbumUpdate,
+    ) -> impl Future<Output = AppResult<Option<Album>>> + Send;
 
+    /// Links files to album, returns ids found in the space including already linked ones
     fn link_album_files(
         &self,
         space_id: &Uuid,
         album_id: &Uuid,
         file_ids: &[Uuid],
-    ) -> impl Future<Output = AppResult<()>> + Send;
+    ) -> impl Future<Output = AppResult<Vec<Uuid>>> + Send;
+
+    /// Unlinks files from album, returns ids that were linked
     fn unlink_album_files(
         &self,
         space_id: &Uuid,
         album_id: &Uuid,
         file_ids: &[Uuid],
-    ) -> impl Future<Output = AppResult<()>> + Send;
+    ) -> impl Future<Output = AppResult<Vec<Uuid>>> + Send;
+
+    /// Moves files from album to `target_album_id`, returns ids that were linked to the source album
+    fn move_album_files(
+        &self,
+        space_id: &Uuid,
+        album_id: &Uuid,
+        target_album_id: &Uuid,
+        file_ids: &[Uuid],
+    ) -> impl Future<Output = AppResult<Vec<Uuid>>> + Send;
 
-    fn delete_album(&self, space_id: &Uuid, album_id: &Uuid) -> impl Future<Output = AppResult<()>> + Send;
-    fn delete_file(&self, file_id: &Uuid, space_id: &Uuid) -> impl Future<Output = AppResult<()>> + Send;
+    /// Places album files next to `anchor_id` in the order of `file_ids`, rewriting their positions only
+    fn reorder_album_files(
+        &self,
+        space_id: &Uuid,
+        album_id: &Uuid,
+        file_ids: &[Uuid],
+        anchor_id: &Uuid,
+        placement: AlbumPlacement,
+    ) -> impl Future<Output = AppResult<AlbumReorder>> + Send;
+
+    /// Spreads album positions `gap` apart, keeping the current order
+    fn renumber_album_files(&self, album_id: &Uuid, gap: i64) -> impl Future<Output = AppResult<()>> + Send;
+
+    /// Uploaders of files found among `file_ids`, either trashed or not
+    fn list_file_owners(
+        &self,
+        space_id: &Uuid,
+        file_ids: &[Uuid],
+        trashed: bool,
+    ) -> impl Future<Output = AppResult<Vec<FileOwner>>> + Send;
+
+    /// Permanently deletes trashed file, `false` if not found in trash
+    fn delete_file(&self, file_id: &Uuid, space_id: &Uuid) -> impl Future<Output = AppResult<bool>> + Send;
 }
 
 impl StorageDs for Datastore {
@@ -310,16 +777,12 @@ impl StorageDs for Datastore {
         updated_date: DateTime<Utc>,
         file_data: FileData,
     ) -> AppResult<MediaFile> {
-        let metadata = NodeMetadata::jsonb(
-            file_data.thumbnail,
-            file_data.preview,
-            Metadata::from(file_data.metadata, updated_date),
-            file_data.media_type,
-        )?;
+        let file_meta = Metadata::from(file_data.metadata, updated_date);
+        let columns = MediaColumns::new(&file_meta, &file_data.thumbnail, file_data.media_type, updated_date);
+        let metadata = NodeMetadata::jsonb(file_data.thumbnail, file_data.preview, file_meta, file_data.media_type)?;
 
-        let row = self
-            .db
-            .query_one(
+        let rows = self
+            .query(
                 &self.storage_stmts.upsert_media_file,
                 &[
                     &Uuid::now_v7(),
@@ -331,10 +794,19 @@ impl StorageDs for Datastore {
                     &object_key,
                     &file_data.size,
                     &metadata,
+                    &columns.taken_at,
+                    &columns.media_type,
+                    &columns.width,
+                    &columns.height,
+                    &columns.duration_secs,
+                    &columns.latitude,
+                    &columns.longitude,
                 ],
             )
             .await
             .map_err(|err| ErrType::DbError.err(err, "Failed to get or create file by hash"))?;
+        // trashed row is left untouched, it is restored or purged instead of revived by an upload
+        let row = rows.into_iter().next().ok_or_else(|| ErrType::BadRequest.msg("File is in trash"))?;
 
         MediaFile::try_from(row).map_err(|err| ErrType::DbError.err(err, "Failed to parse file by hash"))
     }
@@ -356,13 +828,29 @@ impl StorageDs for Datastore {
         preview_key: Option<String>,
     ) -> AppResult<MediaFile> {
         let file_meta = Metadata::from(metadata, updated_date);
+        let columns = MediaColumns::new(&file_meta, &thumbnail, media_type, updated_date);
         let metadata = NodeMetadata::jsonb(thumbnail, preview, file_meta, media_type)?;
 
         let row = self
-            .db
             .query_one(
                 &self.storage_stmts.update_media_file,
-                &[&file_id, &space_id, &file_name, &file_size, &metadata, &updated_date, &thumbnail_key, &preview_key],
+                &[
+                    &file_id,
+                    &space_id,
+                    &file_name,
+                    &file_size,
+                    &metadata,
+                    &updated_date,
+                    &thumbnail_key,
+                    &preview_key,
+                    &columns.taken_at,
+                    &columns.media_type,
+                    &columns.width,
+                    &columns.height,
+                    &columns.duration_secs,
+                    &columns.latitude,
+                    &columns.longitude,
+                ],
             )
             .await
             .map_err(|err| ErrType::DbError.err(err, "Failed to update file"))?;
@@ -372,7 +860,6 @@ impl StorageDs for Datastore {
 
     async fn get_file(&self, space_id: Uuid, file_id: Uuid) -> AppResult<Option<MediaFile>> {
         let rows = self
-            .db
             .query(&self.storage_stmts.get_media_file, &[&file_id, &space_id])
             .await
             .map_err(|err| ErrType::DbError.err(err, "Failed to get file by id"))?;
@@ -385,10 +872,136 @@ impl StorageDs for Datastore {
         }
     }
 
-    async fn list_files(&self, space_id: &Uuid, album_id: &Uuid) -> AppResult<Vec<FileMeta>> {
+    async fn copy_file(
+        &self,
+        file_id: &Uuid,
+        space_id: &Uuid,
+        user_id: &Uuid,
+        target_space_id: &Uuid,
+    ) -> AppResult<Option<MediaFile>> {
+        let rows = self
+            .query(&self.storage_stmts.copy_media_file, &[&Uuid::now_v7(), user_id, target_space_id, file_id, space_id])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to copy file"))?;
+
+        match rows.into_iter().next() {
+            Some(row) => MediaFile::try_from(row)
+                .map(Some)
+                .map_err(|err| ErrType::DbError.err(err, "Failed to parse copied file")),
+            None => Ok(None),
+        }
+    }
+
+    async fn get_file_by_hash(&self, space_id: &Uuid, hash: &str) -> AppResult<Option<MediaFile>> {
+        let rows = self
+            .query(&self.storage_stmts.get_media_file_by_hash, &[space_id, &hash])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to get file by hash"))?;
+
+        match rows.into_iter().next() {
+            Some(row) => MediaFile::try_from(row)
+                .map(Some)
+                .map_err(|err| ErrType::DbError.err(err, "Failed to parse file by hash")),
+            None => Ok(None),
+        }
+    }
+
+    async fn list_files(
+        &self,
+        space_id: &Uuid,
+        user_id: &Uuid,
+        album_id: &Uuid,
+        after: Option<Keyset<String>>,
+        limit: i64,
+    ) -> AppResult<Vec<AlbumFileMeta>> {
+        let (file_name, id) = Keyset::asc(after);
+        let rows = self
+            .query(&self.storage_stmts.list_album_media_files, &[album_id, space_id, &file_name, &id, &limit, user_id])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to get files"))?;
+
+        parse_album_files(rows)
+    }
+
+    async fn list_files_by_taken_at(
+        &self,
+        space_id: &Uuid,
+        user_id: &Uuid,
+        album_id: &Uuid,
+        after: Option<Keyset<DateTime<Utc>>>,
+        limit: i64,
+    ) -> AppResult<Vec<AlbumFileMeta>> {
+        let (taken_at, id) = Keyset::asc(after);
+        let rows = self
+            .query(
+                &self.storage_stmts.list_album_media_files_by_taken_at,
+                &[album_id, space_id, &taken_at, &id, &limit, user_id],
+            )
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to get files"))?;
+
+        parse_album_files(rows)
+    }
+
+    async fn list_files_by_position(
+        &self,
+        space_id: &Uuid,
+        user_id: &Uuid,
+        album_id: &Uuid,
+        after: Option<Keyset<i64>>,
+        limit: i64,
+    ) -> AppResult<Vec<AlbumFileMeta>> {
+        let (position, id) = Keyset::asc(after);
         let rows = self
-            .db
-            .query(&self.storage_stmts.list_album_media_files, &[album_id, space_id])
+            .query(
+                &self.storage_stmts.list_album_media_files_by_position,
+                &[album_id, space_id, &position, &id, &limit, user_id],
+            )
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to get files"))?;
+
+        parse_album_files(rows)
+    }
+
+    async fn list_files_gallery(
+        &self,
+        space_id: &Uuid,
+        user_id: &Uuid,
+        filter: &GalleryFilter,
+        after: Option<Keyset<DateTime<Utc>>>,
+        limit: i64,
+    ) -> AppResult<Vec<FileMeta>> {
+        let (taken_at, id) = Keyset::desc(after);
+        let media_type = filter.media_type.map(media_type_literal);
+        let orientation = filter.orientation.map(orientation_literal);
+        let (min_lat, max_lat, min_lon, max_lon) = match &filter.bounds {
+            Some(bounds) => (Some(bounds.min_lat), Some(bounds.max_lat), Some(bounds.min_lon), Some(bounds.max_lon)),
+            None => (None, None, None, None),
+        };
+        let rows = self
+            .query(
+                &self.storage_stmts.list_media_files_gallery,
+                &[
+                    space_id,
+                    &taken_at,
+                    &id,
+                    &limit,
+                    &filter.taken_after,
+                    &filter.taken_before,
+                    &media_type,
+                    &filter.make,
+                    &filter.model,
+                    &filter.uploader,
+                    &orientation,
+                    &filter.has_location,
+                    &min_lat,
+                    &max_lat,
+                    &min_lon,
+                    &max_lon,
+                    user_id,
+                    &filter.tag_id,
+                ],
+            )
             .await
             .map_err(|err| ErrType::DbError.err(err, "Failed to get files"))?;
 
@@ -400,25 +1013,46 @@ impl StorageDs for Datastore {
         })
     }
 
-    async fn list_files_gallery(&self, space_id: &Uuid) -> AppResult<Vec<GalleryFileMeta>> {
+    async fn list_timeline(
+        &self,
+        space_id: &Uuid,
+        album_id: Option<Uuid>,
+        granularity: TimelineGranularity,
+        timezone: &str,
+    ) -> AppResult<Vec<TimelineBucket>> {
         let rows = self
-            .db
-            .query(&self.storage_stmts.list_media_files_gallery, &[space_id])
+            .query(
+                &self.storage_stmts.list_media_timeline,
+                &[space_id, &granularity_literal(granularity), &timezone, &album_id],
+            )
             .await
-            .map_err(|err| ErrType::DbError.err(err, "Failed to get files"))?;
+            .map_err(|err| match &err {
+                PoolError::Backend(db_err) if db_err.code() == Some(&SqlState::INVALID_PARAMETER_VALUE) => {
+                    ErrType::BadRequest.err(err, "Unknown timezone")
+                }
+                _ => ErrType::DbError.err(err, "Failed to get timeline"),
+            })?;
 
         let size = rows.len();
         rows.into_iter().try_fold(Vec::with_capacity(size), |mut acc, row| {
-            let f = GalleryFileMeta::try_from(row)
-                .map_err(|err| ErrType::DbError.err(err, "Failed to parse listed files"))?;
-            acc.push(f);
+            let bucket = TimelineBucket::try_from(row)
+                .map_err(|err| ErrType::DbError.err(err, "Failed to parse timeline bucket"))?;
+            acc.push(bucket);
             Ok(acc)
         })
     }
 
+    async fn timezone_exists(&self, timezone: &str) -> AppResult<bool> {
+        let row = self
+            .query_one(&self.storage_stmts.timezone_exists, &[&timezone])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to check timezone"))?;
+
+        row.try_get(0).map_err(|err| ErrType::DbError.err(err, "Failed to parse timezone check"))
+    }
+
     async fn get_thumbnail_preview_stream_keys(&self, space_id: &Uuid, file_id: Uuid) -> AppResult<Option<StreamKeys>> {
         let rows = self
-            .db
             .query(&self.storage_stmts.get_media_stream_keys, &[&file_id, space_id])
             .await
             .map_err(|err| ErrType::DbError.err(err, "Failed to get file thumbnail path"))?;
@@ -433,7 +1067,6 @@ impl StorageDs for Datastore {
 
     async fn get_download_stream_key(&self, space_id: &Uuid, file_id: Uuid) -> AppResult<Option<String>> {
         let rows = self
-            .db
             .query(&self.storage_stmts.get_media_object_key, &[&file_id, space_id])
             .await
             .map_err(|err| ErrType::DbError.err(err, "Failed to get file preview path"))?;
@@ -446,9 +1079,58 @@ impl StorageDs for Datastore {
         }
     }
 
+    async fn list_space_media_keys(&self, space_id: &Uuid) -> AppResult<Vec<MediaKeys>> {
+        let rows = self
+            .query(&self.storage_stmts.list_space_media_keys, &[space_id])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to get media keys"))?;
+
+        let size = rows.len();
+        rows.into_iter().try_fold(Vec::with_capacity(size), |mut acc, row| {
+            let k = MediaKeys::try_from(row).map_err(|err| ErrType::DbError.err(err, "Failed to parse media keys"))?;
+            acc.push(k);
+            Ok(acc)
+        })
+    }
+
+    async fn set_broken_files(&self, space_id: &Uuid, file_ids: &[Uuid], broken_at: &DateTime<Utc>) -> AppResult<u64> {
+        self.execute(&self.storage_stmts.set_broken_media_files, &[space_id, &file_ids, broken_at])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to flag broken files"))
+    }
+
+    async fn list_album_archive_files(&self, space_id: &Uuid, album_id: &Uuid) -> AppResult<Vec<ArchiveFile>> {
+        let rows = self
+            .query(&self.storage_stmts.list_album_archive_files, &[album_id, space_id])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to get archive files"))?;
+
+        let size = rows.len();
+        rows.into_iter().try_fold(Vec::with_capacity(size), |mut acc, row| {
+            let f =
+                ArchiveFile::try_from(row).map_err(|err| ErrType::DbError.err(err, "Failed to parse archive files"))?;
+            acc.push(f);
+            Ok(acc)
+        })
+    }
+
+    async fn list_archive_files(&self, space_id: &Uuid, file_ids: &[Uuid]) -> AppResult<Vec<ArchiveFile>> {
+        let rows = self
+            .query(&self.storage_stmts.list_archive_files, &[space_id, &file_ids])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to get archive files"))?;
+
+        let size = rows.len();
+        rows.into_iter().try_fold(Vec::with_capacity(size), |mut acc, row| {
+            let f =
+                ArchiveFile::try_from(row).map_err(|err| ErrType::DbError.err(err, "Failed to parse archive files"))?;
+            acc.push(f);
+            Ok(acc)
+        })
+    }
+
     async fn create_album(&self, user_id: &Uuid, space_id: Uuid, album_name: String) -> AppResult<Album> {
         let row = self
-            .db
             .query_one(
                 &self.storage_stmts.insert_album,
                 &[&Uuid::now_v7(), user_id, &space_id, &album_name, &String::new()],
@@ -461,7 +1143,6 @@ impl StorageDs for Datastore {
 
     async fn get_album(&self, space_id: &Uuid, album_id: &Uuid) -> AppResult<Option<Album>> {
         let rows = self
-            .db
             .query(&self.storage_stmts.get_album, &[album_id, space_id])
             .await
             .map_err(|err| ErrType::DbError.err(err, "Failed to get album"))?;
@@ -474,62 +1155,175 @@ impl StorageDs for Datastore {
         }
     }
 
-    async fn list_albums(&self, space_id: Uuid) -> AppResult<Vec<Album>> {
+    async fn get_album_details(&self, space_id: &Uuid, album_id: &Uuid) -> AppResult<Option<AlbumDetails>> {
         let rows = self
-            .db
-            .query(&self.storage_stmts.list_albums, &[&space_id])
+            .query(&self.storage_stmts.get_album_details, &[album_id, space_id])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to get album"))?;
+
+        match rows.into_iter().next() {
+            Some(row) => AlbumDetails::try_from(row)
+                .map(Some)
+                .map_err(|err| ErrType::DbError.err(err, "Failed to parse album by id")),
+            None => Ok(None),
+        }
+    }
+
+    async fn list_albums(
+        &self,
+        space_id: Uuid,
+        after: Option<Keyset<String>>,
+        limit: i64,
+    ) -> AppResult<Vec<AlbumDetails>> {
+        let (name, id) = Keyset::asc(after);
+        let rows = self
+            .query(&self.storage_stmts.list_albums, &[&space_id, &name, &id, &limit])
             .await
             .map_err(|err| ErrType::DbError.err(err, "Failed to get albums"))?;
 
         let size = rows.len();
         rows.into_iter().try_fold(Vec::with_capacity(size), |mut acc, row| {
-            let a = Album::try_from(row).map_err(|err| ErrType::DbError.err(err, "Failed to parse listed albums"))?;
+            let a = AlbumDetails::try_from(row)
+                .map_err(|err| ErrType::DbError.err(err, "Failed to parse listed albums"))?;
             acc.push(a);
             Ok(acc)
         })
     }
 
-    async fn link_album_files(&self, space_id: &Uuid, album_id: &Uuid, file_ids: &[Uuid]) -> AppResult<()> {
-        for file_id in file_ids {
-            let _ = self
-                .db
-                .query(&self.storage_stmts.link_album_media_file, &[album_id, file_id, space_id])
-                .await
-                .map_err(|err| ErrType::DbError.err(err, "Failed to link file to album"))?;
+    async fn update_album(&self, space_id: &Uuid, album_id: &Uuid, update: AlbumUpdate) -> AppResult<Option<Album>> {
+        let (set_description, description) = (update.description.is_some(), update.description.flatten());
+        let (set_cover, cover_file_id) = (update.cover_file_id.is_some(), update.cover_file_id.flatten());
+        let sort_mode = update.sort_mode.map(|mode| mode.value());
+        let rows = self
+            .query(
+                &self.storage_stmts.update_album,
+                &[
+                    album_id,
+                    space_id,
+                    &update.name,
+                    &set_description,
+                    &description,
+                    &set_cover,
+                    &cover_file_id,
+                    &sort_mode,
+                ],
+            )
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to update album"))?;
+
+        match rows.into_iter().next() {
+            Some(row) => {
+                Album::try_from(row).map(Some).map_err(|err| ErrType::DbError.err(err, "Failed to parse updated album"))
+            }
+            None => Ok(None),
         }
+    }
 
-        Ok(())
+    async fn link_album_files(&self, space_id: &Uuid, album_id: &Uuid, file_ids: &[Uuid]) -> AppResult<Vec<Uuid>> {
+        let rows = self
+            .query(&self.storage_stmts.link_album_media_files, &[album_id, &file_ids, space_id])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to link files to album"))?;
+
+        rows.iter()
+            .map(|row| row.try_get(0))
+            .collect::<Result<_, _>>()
+            .map_err(|err| ErrType::DbError.err(err, "Failed to parse linked file ids"))
     }
 
-    async fn unlink_album_files(&self, space_id: &Uuid, album_id: &Uuid, file_ids: &[Uuid]) -> AppResult<()> {
-        for file_id in file_ids {
-            let _ = self
-                .db
-                .query(&self.storage_stmts.unlink_album_media_file, &[album_id, file_id, space_id])
-                .await
-                .map_err(|err| ErrType::DbError.err(err, "Failed to unlink file from album"))?;
-        }
+    async fn unlink_album_files(&self, space_id: &Uuid, album_id: &Uuid, file_ids: &[Uuid]) -> AppResult<Vec<Uuid>> {
+        let rows = self
+            .query(&self.storage_stmts.unlink_album_media_files, &[album_id, &file_ids, space_id])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to unlink files from album"))?;
 
-        Ok(())
+        rows.iter()
+            .map(|row| row.try_get(0))
+            .collect::<Result<_, _>>()
+            .map_err(|err| ErrType::DbError.err(err, "Failed to parse unlinked file ids"))
     }
 
-    async fn delete_album(&self, space_id: &Uuid, album_id: &Uuid) -> AppResult<()> {
-        let _ = self
-            .db
-            .query(&self.storage_stmts.delete_album, &[album_id, space_id])
+    async fn move_album_files(
+        &self,
+        space_id: &Uuid,
+        album_id: &Uuid,
+        target_album_id: &Uuid,
+        file_ids: &[Uuid],
+    ) -> AppResult<Vec<Uuid>> {
+        let rows = self
+            .query(&self.storage_stmts.move_album_media_files, &[album_id, target_album_id, &file_ids, space_id])
             .await
-            .map_err(|err| ErrType::DbError.err(err, "Failed to delete album"))?;
+            .map_err(|err| ErrType::DbError.err(err, "Failed to move album files"))?;
 
-        Ok(())
+        rows.iter()
+            .map(|row| row.try_get(0))
+            .collect::<Result<_, _>>()
+            .map_err(|err| ErrType::DbError.err(err, "Failed to parse moved file ids"))
+    }
+
+    async fn reorder_album_files(
+        &self,
+        space_id: &Uuid,
+        album_id: &Uuid,
+        file_ids: &[Uuid],
+        anchor_id: &Uuid,
+        placement: AlbumPlacement,
+    ) -> AppResult<AlbumReorder> {
+        let before = matches!(placement, AlbumPlacement::Before);
+        let row = self
+            .query_one(
+                &self.storage_stmts.reorder_album_media_files,
+                &[album_id, &file_ids, anchor_id, &before, space_id],
+            )
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to reorder album files"))?;
+
+        let anchored: bool = row.try_get(0).map_err(|err| ErrType::DbError.err(err, "Failed to parse reorder"))?;
+        let fits: bool = row.try_get(1).map_err(|err| ErrType::DbError.err(err, "Failed to parse reorder"))?;
+        let moved: Vec<Uuid> =
+            row.try_get(2).map_err(|err| ErrType::DbError.err(err, "Failed to parse reordered file ids"))?;
+
+        Ok(match (anchored, fits) {
+            (false, _) => AlbumReorder::AnchorNotFound,
+            (true, false) => AlbumReorder::NoGap,
+            (true, true) => AlbumReorder::Moved(moved),
+        })
     }
 
-    async fn delete_file(&self, file_id: &Uuid, space_id: &Uuid) -> AppResult<()> {
-        let _ = self
-            .db
-            .query(&self.storage_stmts.delete_media_file, &[file_id, space_id])
+    async fn renumber_album_files(&self, album_id: &Uuid, gap: i64) -> AppResult<()> {
+        self.execute(&self.storage_stmts.renumber_album_media_files, &[album_id, &gap])
             .await
-            .map_err(|err| ErrType::DbError.err(err, "Failed to delete file"))?;
+            .map_err(|err| ErrType::DbError.err(err, "Failed to renumber album files"))?;
 
         Ok(())
     }
+
+    async fn list_file_owners(&self, space_id: &Uuid, file_ids: &[Uuid], trashed: bool) -> AppResult<Vec<FileOwner>> {
+        let rows = self
+            .query(&self.storage_stmts.list_media_file_owners, &[space_id, &file_ids, &trashed])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to list file owners"))?;
+
+        rows.into_iter().try_fold(Vec::new(), |mut acc, row| {
+            acc.push(FileOwner::try_from(row).map_err(|err| ErrType::DbError.err(err, "Failed to parse file owner"))?);
+            Ok(acc)
+        })
+    }
+
+    async fn delete_file(&self, file_id: &Uuid, space_id: &Uuid) -> AppResult<bool> {
+        self.query(&self.storage_stmts.delete_media_file, &[file_id, space_id])
+            .await
+            .map(|rows| !rows.is_empty())
+            .map_err(|err| ErrType::DbError.err(err, "Failed to delete file"))
+    }
+}
+
+fn parse_album_files(rows: Vec<tokio_postgres::Row>) -> AppResult<Vec<AlbumFileMeta>> {
+    let size = rows.len();
+    rows.into_iter().try_fold(Vec::with_capacity(size), |mut acc, row| {
+        let f =
+            AlbumFileMeta::try_from(row).map_err(|err| ErrType::DbError.err(err, "Failed to parse listed files"))?;
+        acc.push(f);
+        Ok(acc)
+    })
 }
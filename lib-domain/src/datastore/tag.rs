@@ -0,0 +1,192 @@
+use chrono::{DateTime, Utc};
+use deadpool_postgres::PoolError;
+use lib_core::{AppError, AppResult, ErrType};
+use tokio_postgres::error::SqlState;
+use uuid::Uuid;
+
+use super::Datastore;
+
+pub struct Tag {
+    pub id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+
+    pub space_id: Uuid,
+    pub name: String,
+}
+impl TryFrom<tokio_postgres::Row> for Tag {
+    type Error = tokio_postgres::error::Error;
+
+    fn try_from(value: tokio_postgres::Row) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: value.try_get(0)?,
+            created_at: value.try_get(1)?,
+            updated_at: value.try_get(2)?,
+            space_id: value.try_get(3)?,
+            name: value.try_get(4)?,
+        })
+    }
+}
+
+/// Tag with the number of its files out of trash
+pub struct TagCount {
+    pub tag: Tag,
+    pub file_count: i64,
+}
+impl TryFrom<tokio_postgres::Row> for TagCount {
+    type Error = tokio_postgres::error::Error;
+
+    fn try_from(value: tokio_postgres::Row) -> Result<Self, Self::Error> {
+        let file_count = value.try_get(5)?;
+        Ok(Self {
+            tag: Tag::try_from(value)?,
+            file_count,
+        })
+    }
+}
+
+pub trait TagDs: Send + Sync {
+    /// Creates tag, names are unique per space ignoring case
+    fn create_tag(&self, space_id: &Uuid, name: &str) -> impl Future<Output = AppResult<Tag>> + Send;
+
+    fn get_tag(&self, space_id: &Uuid, tag_id: &Uuid) -> impl Future<Output = AppResult<Option<Tag>>> + Send;
+
+    /// Tags of the space by name with their file counts
+    fn list_tags(&self, space_id: &Uuid) -> impl Future<Output = AppResult<Vec<TagCount>>> + Send;
+
+    fn rename_tag(
+        &self,
+        space_id: &Uuid,
+        tag_id: &Uuid,
+        name: &str,
+    ) -> impl Future<Output = AppResult<Option<Tag>>> + Send;
+
+    /// Moves files of `tag_id` to `target_tag_id` and deletes `tag_id`, `false` if either is not found
+    fn merge_tags(
+        &self,
+        space_id: &Uuid,
+        tag_id: &Uuid,
+        target_tag_id: &Uuid,
+    ) -> impl Future<Output = AppResult<bool>> + Send;
+
+    fn delete_tag(&self, space_id: &Uuid, tag_id: &Uuid) -> impl Future<Output = AppResult<bool>> + Send;
+
+    /// Tags files, returns ids found in the space including already tagged ones
+    fn link_tag_files(
+        &self,
+        space_id: &Uuid,
+        tag_id: &Uuid,
+        file_ids: &[Uuid],
+    ) -> impl Future<Output = AppResult<Vec<Uuid>>> + Send;
+
+    /// Untags files, returns ids that were tagged
+    fn unlink_tag_files(
+        &self,
+        space_id: &Uuid,
+        tag_id: &Uuid,
+        file_ids: &[Uuid],
+    ) -> impl Future<Output = AppResult<Vec<Uuid>>> + Send;
+}
+
+impl TagDs for Datastore {
+    async fn create_tag(&self, space_id: &Uuid, name: &str) -> AppResult<Tag> {
+        let row = self
+            .query_one(&self.tag_stmts.insert, &[&Uuid::now_v7(), space_id, &name])
+            .await
+            .map_err(|err| tag_name_err(err, "Failed to create tag"))?;
+
+        Tag::try_from(row).map_err(|err| ErrType::DbError.err(err, "Failed to parse created tag"))
+    }
+
+    async fn get_tag(&self, space_id: &Uuid, tag_id: &Uuid) -> AppResult<Option<Tag>> {
+        let rows = self
+            .query(&self.tag_stmts.get, &[tag_id, space_id])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to get tag"))?;
+
+        match rows.into_iter().next() {
+            Some(row) => Tag::try_from(row).map(Some).map_err(|err| ErrType::DbError.err(err, "Failed to parse tag")),
+            None => Ok(None),
+        }
+    }
+
+    async fn list_tags(&self, space_id: &Uuid) -> AppResult<Vec<TagCount>> {
+        let rows = self
+            .query(&self.tag_stmts.list, &[space_id])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to get tags"))?;
+
+        let size = rows.len();
+        rows.into_iter().try_fold(Vec::with_capacity(size), |mut acc, row| {
+            let tag = TagCount::try_from(row).map_err(|err| ErrType::DbError.err(err, "Failed to parse tags"))?;
+            acc.push(tag);
+            Ok(acc)
+        })
+    }
+
+    async fn rename_tag(&self, space_id: &Uuid, tag_id: &Uuid, name: &str) -> AppResult<Option<Tag>> {
+        let rows = self
+            .query(&self.tag_stmts.rename, &[tag_id, space_id, &name])
+            .await
+            .map_err(|err| tag_name_err(err, "Failed to rename tag"))?;
+
+        match rows.into_iter().next() {
+            Some(row) => {
+                Tag::try_from(row).map(Some).map_err(|err| ErrType::DbError.err(err, "Failed to parse renamed tag"))
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn merge_tags(&self, space_id: &Uuid, tag_id: &Uuid, target_tag_id: &Uuid) -> AppResult<bool> {
+        let rows = self
+            .query(&self.tag_stmts.merge, &[space_id, tag_id, target_tag_id])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to merge tags"))?;
+
+        Ok(!rows.is_empty())
+    }
+
+    async fn delete_tag(&self, space_id: &Uuid, tag_id: &Uuid) -> AppResult<bool> {
+        let deleted = self
+            .execute(&self.tag_stmts.delete, &[tag_id, space_id])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to delete tag"))?;
+
+        Ok(deleted > 0)
+    }
+
+    async fn link_tag_files(&self, space_id: &Uuid, tag_id: &Uuid, file_ids: &[Uuid]) -> AppResult<Vec<Uuid>> {
+        let rows = self
+            .query(&self.tag_stmts.link_media_files, &[tag_id, &file_ids, space_id])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to tag files"))?;
+
+        rows.iter()
+            .map(|row| row.try_get(0))
+            .collect::<Result<_, _>>()
+            .map_err(|err| ErrType::DbError.err(err, "Failed to parse tagged file ids"))
+    }
+
+    async fn unlink_tag_files(&self, space_id: &Uuid, tag_id: &Uuid, file_ids: &[Uuid]) -> AppResult<Vec<Uuid>> {
+        let rows = self
+            .query(&self.tag_stmts.unlink_media_files, &[tag_id, &file_ids, space_id])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to untag files"))?;
+
+        rows.iter()
+            .map(|row| row.try_get(0))
+            .collect::<Result<_, _>>()
+            .map_err(|err| ErrType::DbError.err(err, "Failed to parse untagged file ids"))
+    }
+}
+
+/// Duplicate names are a client error
+fn tag_name_err(err: PoolError, msg: &str) -> AppError {
+    match &err {
+        PoolError::Backend(db_err) if db_err.code() == Some(&SqlState::UNIQUE_VIOLATION) => {
+            ErrType::BadRequest.err(err, "Tag name already exists")
+        }
+        _ => ErrType::DbError.err(err, msg),
+    }
+}
@@ -0,0 +1,81 @@
+use std::sync::{atomic::Ordering, Arc};
+
+use lib_core::{AppResult, ErrType};
+
+use super::{Datastore, TxConn};
+
+pub trait TransactionDs: Sized + Send + Sync {
+    /// Datastore running every call in one transaction, nested calls open a savepoint
+    fn begin(&self) -> impl Future<Output = AppResult<Self>> + Send;
+    fn commit(self) -> impl Future<Output = AppResult<()>> + Send;
+    fn rollback(self) -> impl Future<Output = AppResult<()>> + Send;
+
+    /// Commits on `Ok`, rolls back otherwise
+    fn finish<T: Send>(self, result: AppResult<T>) -> impl Future<Output = AppResult<T>> + Send {
+        async move {
+            match result {
+                Ok(value) => self.commit().await.map(|_| value),
+                Err(err) => {
+                    if let Err(rollback_err) = self.rollback().await {
+                        tracing::warn!("Failed to roll back transaction: {rollback_err}");
+                    }
+                    Err(err)
+                }
+            }
+        }
+    }
+}
+
+impl TransactionDs for Datastore {
+    async fn begin(&self) -> AppResult<Self> {
+        match &self.tx {
+            Some(tx) => {
+                let savepoint = format!("sp_{}", tx.savepoints.fetch_add(1, Ordering::Relaxed));
+                tx.batch_execute(&format!("SAVEPOINT {savepoint}"))
+                    .await
+                    .map_err(|err| ErrType::DbError.err(err, "Failed to create savepoint"))?;
+
+                Ok(self.with_tx(tx.clone(), Some(savepoint)))
+            }
+            None => {
+                let client =
+                    self.pool.get().await.map_err(|err| ErrType::DbError.err(err, "Failed to get db connection"))?;
+                client.batch_execute("BEGIN").await.map_err(|err| ErrType::DbError.err(err, "Failed to begin"))?;
+
+                Ok(self.with_tx(Arc::new(TxConn::new(client)), None))
+            }
+        }
+    }
+
+    async fn commit(self) -> AppResult<()> {
+        let tx = self.tx.as_ref().ok_or(ErrType::ServerError.msg("No transaction to commit"))?;
+
+        match &self.savepoint {
+            Some(savepoint) => tx
+                .batch_execute(&format!("RELEASE SAVEPOINT {savepoint}"))
+                .await
+                .map_err(|err| ErrType::DbError.err(err, "Failed to release savepoint")),
+            None => {
+                tx.batch_execute("COMMIT").await.map_err(|err| ErrType::DbError.err(err, "Failed to commit"))?;
+                tx.finished.store(true, Ordering::Release);
+                Ok(())
+            }
+        }
+    }
+
+    async fn rollback(self) -> AppResult<()> {
+        let tx = self.tx.as_ref().ok_or(ErrType::ServerError.msg("No transaction to roll back"))?;
+
+        match &self.savepoint {
+            Some(savepoint) => tx
+                .batch_execute(&format!("ROLLBACK TO SAVEPOINT {savepoint}"))
+                .await
+                .map_err(|err| ErrType::DbError.err(err, "Failed to roll back savepoint")),
+            None => {
+                tx.batch_execute("ROLLBACK").await.map_err(|err| ErrType::DbError.err(err, "Failed to roll back"))?;
+                tx.finished.store(true, Ordering::Release);
+                Ok(())
+            }
+        }
+    }
+}
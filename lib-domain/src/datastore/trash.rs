@@ -0,0 +1,166 @@
+use chrono::{DateTime, Utc};
+use lib_core::{AppResult, ErrType};
+use uuid::Uuid;
+
+use super::{
+    storage::{Album, MediaFile},
+    Datastore,
+};
+
+pub trait TrashDs: Send + Sync {
+    /// Moves file to trash, `false` if not found or already trashed
+    fn trash_file(
+        &self,
+        space_id: &Uuid,
+        file_id: &Uuid,
+        deleted_at: &DateTime<Utc>,
+    ) -> impl Future<Output = AppResult<bool>> + Send;
+    /// Moves files to trash, returns ids that were trashed
+    fn trash_files(
+        &self,
+        space_id: &Uuid,
+        file_ids: &[Uuid],
+        deleted_at: &DateTime<Utc>,
+    ) -> impl Future<Output = AppResult<Vec<Uuid>>> + Send;
+    fn trash_album(
+        &self,
+        space_id: &Uuid,
+        album_id: &Uuid,
+        deleted_at: &DateTime<Utc>,
+    ) -> impl Future<Output = AppResult<bool>> + Send;
+
+    /// Restores trashed file, `false` if not found in trash
+    fn restore_file(&self, space_id: &Uuid, file_id: &Uuid) -> impl Future<Output = AppResult<bool>> + Send;
+    /// Restores trashed files, returns ids that were restored
+    fn restore_files(&self, space_id: &Uuid, file_ids: &[Uuid]) -> impl Future<Output = AppResult<Vec<Uuid>>> + Send;
+    fn restore_album(&self, space_id: &Uuid, album_id: &Uuid) -> impl Future<Output = AppResult<bool>> + Send;
+
+    fn list_trashed_files(&self, space_id: &Uuid) -> impl Future<Output = AppResult<Vec<MediaFile>>> + Send;
+    fn list_trashed_albums(&self, space_id: &Uuid) -> impl Future<Output = AppResult<Vec<Album>>> + Send;
+
+    /// Files of every space trashed before `before`, at most `limit`
+    fn list_expired_files(
+        &self,
+        before: &DateTime<Utc>,
+        limit: i64,
+    ) -> impl Future<Output = AppResult<Vec<MediaFile>>> + Send;
+
+    /// Deletes albums of every space trashed before `before`
+    fn delete_expired_albums(&self, before: &DateTime<Utc>) -> impl Future<Output = AppResult<u64>> + Send;
+    fn delete_trashed_albums(&self, space_id: &Uuid) -> impl Future<Output = AppResult<u64>> + Send;
+}
+
+impl TrashDs for Datastore {
+    async fn trash_file(&self, space_id: &Uuid, file_id: &Uuid, deleted_at: &DateTime<Utc>) -> AppResult<bool> {
+        self.execute(&self.trash_stmts.trash_file, &[file_id, space_id, deleted_at])
+            .await
+            .map(|count| count > 0)
+            .map_err(|err| ErrType::DbError.err(err, "Failed to trash file"))
+    }
+
+    async fn trash_files(
+        &self,
+        space_id: &Uuid,
+        file_ids: &[Uuid],
+        deleted_at: &DateTime<Utc>,
+    ) -> AppResult<Vec<Uuid>> {
+        let rows = self
+            .query(&self.trash_stmts.trash_files, &[&file_ids, space_id, deleted_at])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to trash files"))?;
+
+        rows.iter()
+            .map(|row| row.try_get(0))
+            .collect::<Result<_, _>>()
+            .map_err(|err| ErrType::DbError.err(err, "Failed to parse trashed file ids"))
+    }
+
+    async fn trash_album(&self, space_id: &Uuid, album_id: &Uuid, deleted_at: &DateTime<Utc>) -> AppResult<bool> {
+        self.execute(&self.trash_stmts.trash_album, &[album_id, space_id, deleted_at])
+            .await
+            .map(|count| count > 0)
+            .map_err(|err| ErrType::DbError.err(err, "Failed to trash album"))
+    }
+
+    async fn restore_file(&self, space_id: &Uuid, file_id: &Uuid) -> AppResult<bool> {
+        self.execute(&self.trash_stmts.restore_file, &[file_id, space_id])
+            .await
+            .map(|count| count > 0)
+            .map_err(|err| ErrType::DbError.err(err, "Failed to restore file"))
+    }
+
+    async fn restore_files(&self, space_id: &Uuid, file_ids: &[Uuid]) -> AppResult<Vec<Uuid>> {
+        let rows = self
+            .query(&self.trash_stmts.restore_files, &[&file_ids, space_id])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to restore files"))?;
+
+        rows.iter()
+            .map(|row| row.try_get(0))
+            .collect::<Result<_, _>>()
+            .map_err(|err| ErrType::DbError.err(err, "Failed to parse restored file ids"))
+    }
+
+    async fn restore_album(&self, space_id: &Uuid, album_id: &Uuid) -> AppResult<bool> {
+        self.execute(&self.trash_stmts.restore_album, &[album_id, space_id])
+            .await
+            .map(|count| count > 0)
+            .map_err(|err| ErrType::DbError.err(err, "Failed to restore album"))
+    }
+
+    async fn list_trashed_files(&self, space_id: &Uuid) -> AppResult<Vec<MediaFile>> {
+        let rows = self
+            .query(&self.trash_stmts.list_files, &[space_id])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to get trashed files"))?;
+
+        let size = rows.len();
+        rows.into_iter().try_fold(Vec::with_capacity(size), |mut acc, row| {
+            let f =
+                MediaFile::try_from(row).map_err(|err| ErrType::DbError.err(err, "Failed to parse trashed files"))?;
+            acc.push(f);
+            Ok(acc)
+        })
+    }
+
+    async fn list_trashed_albums(&self, space_id: &Uuid) -> AppResult<Vec<Album>> {
+        let rows = self
+            .query(&self.trash_stmts.list_albums, &[space_id])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to get trashed albums"))?;
+
+        let size = rows.len();
+        rows.into_iter().try_fold(Vec::with_capacity(size), |mut acc, row| {
+            let a = Album::try_from(row).map_err(|err| ErrType::DbError.err(err, "Failed to parse trashed albums"))?;
+            acc.push(a);
+            Ok(acc)
+        })
+    }
+
+    async fn list_expired_files(&self, before: &DateTime<Utc>, limit: i64) -> AppResult<Vec<MediaFile>> {
+        let rows = self
+            .query(&self.trash_stmts.list_expired_files, &[before, &limit])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to get expired trashed files"))?;
+
+        let size = rows.len();
+        rows.into_iter().try_fold(Vec::with_capacity(size), |mut acc, row| {
+            let f = MediaFile::try_from(row)
+                .map_err(|err| ErrType::DbError.err(err, "Failed to parse expired trashed files"))?;
+            acc.push(f);
+            Ok(acc)
+        })
+    }
+
+    async fn delete_expired_albums(&self, before: &DateTime<Utc>) -> AppResult<u64> {
+        self.execute(&self.trash_stmts.delete_expired_albums, &[before])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to delete expired albums"))
+    }
+
+    async fn delete_trashed_albums(&self, space_id: &Uuid) -> AppResult<u64> {
+        self.execute(&self.trash_stmts.delete_albums, &[space_id])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to delete trashed albums"))
+    }
+}
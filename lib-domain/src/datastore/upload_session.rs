@@ -0,0 +1,159 @@
+use chrono::{DateTime, Utc};
+use lib_core::{AppResult, ErrType};
+use uuid::Uuid;
+
+use super::Datastore;
+
+pub struct UploadSession {
+    pub id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+
+    pub user_id: Uuid,
+    pub space_id: Uuid,
+
+    /// Multipart upload id issued by the storage backend
+    pub upload_id: String,
+    pub hash: String,
+    pub file_name: String,
+    pub file_size: i64,
+    pub part_size: i64,
+}
+impl TryFrom<tokio_postgres::Row> for UploadSession {
+    type Error = tokio_postgres::Error;
+
+    fn try_from(value: tokio_postgres::Row) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: value.try_get(0)?,
+            created_at: value.try_get(1)?,
+            updated_at: value.try_get(2)?,
+            user_id: value.try_get(3)?,
+            space_id: value.try_get(4)?,
+            upload_id: value.try_get(5)?,
+            hash: value.try_get(6)?,
+            file_name: value.try_get(7)?,
+            file_size: value.try_get(8)?,
+            part_size: value.try_get(9)?,
+        })
+    }
+}
+
+/// Multipart upload to register as a resumable session
+pub struct UploadSessionRecord<'a> {
+    pub user_id: Uuid,
+    pub space_id: Uuid,
+    pub upload_id: &'a str,
+    pub hash: &'a str,
+    pub file_name: &'a str,
+    pub file_size: i64,
+    pub part_size: i64,
+}
+
+pub trait UploadSessionDs: Send + Sync {
+    fn insert_upload_session(
+        &self,
+        record: UploadSessionRecord<'_>,
+    ) -> impl Future<Output = AppResult<UploadSession>> + Send;
+    fn get_upload_session(
+        &self,
+        space_id: &Uuid,
+        session_id: &Uuid,
+    ) -> impl Future<Output = AppResult<Option<UploadSession>>> + Send;
+    fn touch_upload_session(&self, session_id: &Uuid) -> impl Future<Output = AppResult<()>> + Send;
+    fn delete_upload_session(&self, session_id: &Uuid) -> impl Future<Output = AppResult<()>> + Send;
+    fn list_stale_upload_sessions(
+        &self,
+        before: DateTime<Utc>,
+    ) -> impl Future<Output = AppResult<Vec<UploadSession>>> + Send;
+
+    /// Deletes every session of the space, returning them for their uploads to be aborted
+    fn delete_space_upload_sessions(
+        &self,
+        space_id: &Uuid,
+    ) -> impl Future<Output = AppResult<Vec<UploadSession>>> + Send;
+}
+
+impl UploadSessionDs for Datastore {
+    async fn insert_upload_session(&self, record: UploadSessionRecord<'_>) -> AppResult<UploadSession> {
+        let row = self
+            .query_one(
+                &self.upload_session_stmts.insert,
+                &[
+                    &Uuid::now_v7(),
+                    &record.user_id,
+                    &record.space_id,
+                    &record.upload_id,
+                    &record.hash,
+                    &record.file_name,
+                    &record.file_size,
+                    &record.part_size,
+                ],
+            )
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to insert upload session"))?;
+
+        UploadSession::try_from(row).map_err(|err| ErrType::DbError.err(err, "Failed to parse inserted upload session"))
+    }
+
+    async fn get_upload_session(&self, space_id: &Uuid, session_id: &Uuid) -> AppResult<Option<UploadSession>> {
+        let rows = self
+            .query(&self.upload_session_stmts.get, &[session_id, space_id])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to get upload session"))?;
+
+        match rows.into_iter().next() {
+            Some(row) => UploadSession::try_from(row)
+                .map(Some)
+                .map_err(|err| ErrType::DbError.err(err, "Failed to parse upload session")),
+            None => Ok(None),
+        }
+    }
+
+    async fn touch_upload_session(&self, session_id: &Uuid) -> AppResult<()> {
+        let _ = self
+            .query(&self.upload_session_stmts.touch, &[session_id])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to touch upload session"))?;
+
+        Ok(())
+    }
+
+    async fn delete_upload_session(&self, session_id: &Uuid) -> AppResult<()> {
+        let _ = self
+            .query(&self.upload_session_stmts.delete, &[session_id])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to delete upload session"))?;
+
+        Ok(())
+    }
+
+    async fn list_stale_upload_sessions(&self, before: DateTime<Utc>) -> AppResult<Vec<UploadSession>> {
+        let rows = self
+            .query(&self.upload_session_stmts.list_stale, &[&before])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to get stale upload sessions"))?;
+
+        let size = rows.len();
+        rows.into_iter().try_fold(Vec::with_capacity(size), |mut acc, row| {
+            let s = UploadSession::try_from(row)
+                .map_err(|err| ErrType::DbError.err(err, "Failed to parse listed upload sessions"))?;
+            acc.push(s);
+            Ok(acc)
+        })
+    }
+
+    async fn delete_space_upload_sessions(&self, space_id: &Uuid) -> AppResult<Vec<UploadSession>> {
+        let rows = self
+            .query(&self.upload_session_stmts.delete_by_space, &[space_id])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to delete space upload sessions"))?;
+
+        let size = rows.len();
+        rows.into_iter().try_fold(Vec::with_capacity(size), |mut acc, row| {
+            let s = UploadSession::try_from(row)
+                .map_err(|err| ErrType::DbError.err(err, "Failed to parse deleted upload sessions"))?;
+            acc.push(s);
+            Ok(acc)
+        })
+    }
+}
@@ -0,0 +1,166 @@
+use chrono::{DateTime, Utc};
+use lib_core::{smq_dto::MediaType, AppResult, ErrType};
+use uuid::Uuid;
+
+use super::Datastore;
+
+/// Usage counters of a space per uploader and media type
+pub struct SpaceUsage {
+    pub user_id: Uuid,
+    pub media_type: MediaType,
+    pub bytes: i64,
+    pub file_count: i64,
+}
+impl TryFrom<tokio_postgres::Row> for SpaceUsage {
+    type Error = tokio_postgres::Error;
+
+    fn try_from(value: tokio_postgres::Row) -> Result<Self, Self::Error> {
+        // space_id: 0
+        let media_type: String = value.try_get(2)?;
+        Ok(Self {
+            user_id: value.try_get(1)?,
+            media_type: serde_json::from_value(serde_json::Value::String(media_type)).unwrap_or(MediaType::Image),
+            bytes: value.try_get(3)?,
+            file_count: value.try_get(4)?,
+        })
+    }
+}
+
+pub fn media_type_literal(media_type: MediaType) -> &'static str {
+    match media_type {
+        MediaType::Image => "image",
+        MediaType::Video => "video",
+    }
+}
+
+pub trait UsageDs: Send + Sync {
+    /// Adds deltas to usage counters, negative deltas release usage
+    fn add_usage(
+        &self,
+        space_id: &Uuid,
+        user_id: &Uuid,
+        media_type: MediaType,
+        bytes: i64,
+        file_count: i64,
+    ) -> impl Future<Output = AppResult<()>> + Send;
+    fn get_space_used_bytes(&self, space_id: &Uuid) -> impl Future<Output = AppResult<i64>> + Send;
+    fn list_space_usage(&self, space_id: &Uuid) -> impl Future<Output = AppResult<Vec<SpaceUsage>>> + Send;
+
+    /// Serializes quota reservations of the space until the transaction ends
+    fn lock_space_usage(&self, space_id: &Uuid) -> impl Future<Output = AppResult<()>> + Send;
+    fn delete_expired_reservations(
+        &self,
+        space_id: &Uuid,
+        before: DateTime<Utc>,
+    ) -> impl Future<Output = AppResult<()>> + Send;
+
+    /// Bytes reserved by pending uploads of the space, except the upload of `hash` by `user_id`
+    fn get_space_reserved_bytes(
+        &self,
+        space_id: &Uuid,
+        user_id: &Uuid,
+        hash: &str,
+    ) -> impl Future<Output = AppResult<i64>> + Send;
+
+    /// Reserves `bytes` for the upload of `hash` by `user_id`, replacing any previous reservation
+    fn reserve_usage(
+        &self,
+        space_id: &Uuid,
+        user_id: &Uuid,
+        hash: &str,
+        bytes: i64,
+    ) -> impl Future<Output = AppResult<()>> + Send;
+    fn release_usage_reservation(
+        &self,
+        space_id: &Uuid,
+        user_id: &Uuid,
+        hash: &str,
+    ) -> impl Future<Output = AppResult<()>> + Send;
+}
+
+impl UsageDs for Datastore {
+    async fn add_usage(
+        &self,
+        space_id: &Uuid,
+        user_id: &Uuid,
+        media_type: MediaType,
+        bytes: i64,
+        file_count: i64,
+    ) -> AppResult<()> {
+        let _ = self
+            .query(&self.usage_stmts.add, &[space_id, user_id, &media_type_literal(media_type), &bytes, &file_count])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to update space usage"))?;
+
+        Ok(())
+    }
+
+    async fn get_space_used_bytes(&self, space_id: &Uuid) -> AppResult<i64> {
+        let row = self
+            .query_one(&self.usage_stmts.get_total, &[space_id])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to get space usage"))?;
+
+        row.try_get(0).map_err(|err| ErrType::DbError.err(err, "Failed to parse space usage"))
+    }
+
+    async fn list_space_usage(&self, space_id: &Uuid) -> AppResult<Vec<SpaceUsage>> {
+        let rows = self
+            .query(&self.usage_stmts.list, &[space_id])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to list space usage"))?;
+
+        let size = rows.len();
+        rows.into_iter().try_fold(Vec::with_capacity(size), |mut acc, row| {
+            let u =
+                SpaceUsage::try_from(row).map_err(|err| ErrType::DbError.err(err, "Failed to parse space usage"))?;
+            acc.push(u);
+            Ok(acc)
+        })
+    }
+
+    async fn lock_space_usage(&self, space_id: &Uuid) -> AppResult<()> {
+        let _ = self
+            .query(&self.usage_stmts.lock_space, &[space_id])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to lock space usage"))?;
+
+        Ok(())
+    }
+
+    async fn delete_expired_reservations(&self, space_id: &Uuid, before: DateTime<Utc>) -> AppResult<()> {
+        let _ = self
+            .query(&self.usage_stmts.delete_expired_reservations, &[space_id, &before])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to delete expired upload reservations"))?;
+
+        Ok(())
+    }
+
+    async fn get_space_reserved_bytes(&self, space_id: &Uuid, user_id: &Uuid, hash: &str) -> AppResult<i64> {
+        let row = self
+            .query_one(&self.usage_stmts.get_reserved, &[space_id, user_id, &hash])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to get reserved space usage"))?;
+
+        row.try_get(0).map_err(|err| ErrType::DbError.err(err, "Failed to parse reserved space usage"))
+    }
+
+    async fn reserve_usage(&self, space_id: &Uuid, user_id: &Uuid, hash: &str, bytes: i64) -> AppResult<()> {
+        let _ = self
+            .query(&self.usage_stmts.reserve, &[space_id, user_id, &hash, &bytes])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to reserve space usage"))?;
+
+        Ok(())
+    }
+
+    async fn release_usage_reservation(&self, space_id: &Uuid, user_id: &Uuid, hash: &str) -> AppResult<()> {
+        let _ = self
+            .query(&self.usage_stmts.release, &[space_id, user_id, &hash])
+            .await
+            .map_err(|err| ErrType::DbError.err(err, "Failed to release space usage reservation"))?;
+
+        Ok(())
+    }
+}
@@ -48,7 +48,6 @@ pub trait UserDs: Send + Sync {
 impl UserDs for Datastore {
     async fn get_user_by_clerk_id(&self, clerk_id: &str) -> AppResult<Option<User>> {
         let rows = self
-            .db
             .query(&self.user_stmts.get_by_clerk_id, &[&clerk_id])
             .await
             .map_err(|err| ErrType::DbError.err(err, "Failed to check user by clerk id"))?;
@@ -58,7 +57,6 @@ impl UserDs for Datastore {
 
     async fn get_user_by_id(&self, id: Uuid) -> AppResult<Option<User>> {
         let rows = self
-            .db
             .query(&self.user_stmts.get_by_id, &[&id])
             .await
             .map_err(|err| ErrType::DbError.err(err, "Failed to get user by id"))?;
@@ -68,7 +66,6 @@ impl UserDs for Datastore {
 
     async fn get_platform_users(&self) -> AppResult<Vec<User>> {
         let rows = self
-            .db
             .query(&self.user_stmts.get_allowed, &[])
             .await
             .map_err(|err| ErrType::DbError.err(err, "Failed to get allowed users"))?;
@@ -78,7 +75,6 @@ impl UserDs for Datastore {
 
     async fn insert_user(&self, claims: TokenClaims) -> AppResult<User> {
         let row = self
-            .db
             .query_one(
                 &self.user_stmts.insert,
                 &[&Uuid::now_v7(), &claims.sub, &claims.email, &claims.name, &"", &claims.picture],
@@ -91,7 +87,6 @@ impl UserDs for Datastore {
 
     async fn update_user(&self, id: Uuid, first_name: &str, last_name: &str, picture_url: &str) -> AppResult<User> {
         let row = self
-            .db
             .query_one(&self.user_stmts.update, &[&id, &first_name, &last_name, &picture_url])
             .await
             .map_err(|err| ErrType::DbError.err(err, "Failed to update user"))?;
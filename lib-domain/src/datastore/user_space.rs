@@ -4,7 +4,7 @@ use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 use uuid::Uuid;
 
-use super::Datastore;
+use super::{Datastore, Keyset};
 
 #[derive(Debug, ToSchema, Serialize, Deserialize, Clone, Copy)]
 #[serde(rename_all = "snake_case")]
@@ -80,6 +80,7 @@ impl From<tokio_postgres::Row> for UserSpace {
                 name: value.get(9),
                 description: value.get(10),
                 picture_url: value.get(11),
+                storage_quota: value.get(12),
             },
         }
     }
@@ -153,7 +154,13 @@ pub trait UserSpaceDs: Send + Sync {
         space_id: &Uuid,
     ) -> impl Future<Output = AppResult<Option<SpaceMember>>> + Send;
     fn get_all_spaces_for_user(&self, user_id: Uuid) -> impl Future<Output = AppResult<Vec<UserSpace>>> + Send;
-    fn get_all_users_for_space(&self, space_id: &Uuid) -> impl Future<Output = AppResult<Vec<SpaceUser>>> + Send;
+    /// Members by join date, at most `limit` after `after`
+    fn get_all_users_for_space(
+        &self,
+        space_id: &Uuid,
+        after: Option<Keyset<DateTime<Utc>>>,
+        limit: i64,
+    ) -> impl Future<Output = AppResult<Vec<SpaceUser>>> + Send;
     fn update_space_user_role(
         &self,
         space_member_id: Uuid,
@@ -165,7 +172,6 @@ pub trait UserSpaceDs: Send + Sync {
 impl UserSpaceDs for Datastore {
     async fn add_user_to_space(&self, user_id: &Uuid, space_id: &Uuid, role: SpaceRole) -> AppResult<SpaceMember> {
         let row = self
-            .db
             .query_one(&self.user_space_stmts.insert, &[&Uuid::now_v7(), &user_id, &space_id, &role.value()])
             .await
             .map_err(|err| ErrType::DbError.err(err, "Failed to add user to space"))?;
@@ -175,7 +181,6 @@ impl UserSpaceDs for Datastore {
 
     async fn get_user_space(&self, user_id: &Uuid, space_id: &Uuid) -> AppResult<Option<SpaceMember>> {
         let rows = self
-            .db
             .query(&self.user_space_stmts.get_user_space, &[&user_id, &space_id])
             .await
             .map_err(|err| ErrType::DbError.err(err, "Failed to get user space member"))?;
@@ -185,7 +190,6 @@ impl UserSpaceDs for Datastore {
 
     async fn get_all_spaces_for_user(&self, user_id: Uuid) -> AppResult<Vec<UserSpace>> {
         let rows = self
-            .db
             .query(&self.user_space_stmts.get_all_spaces_for_user, &[&user_id])
             .await
             .map_err(|err| ErrType::DbError.err(err, "Failed to get spaces for user"))?;
@@ -193,10 +197,15 @@ impl UserSpaceDs for Datastore {
         Ok(rows.into_iter().map(UserSpace::from).collect())
     }
 
-    async fn get_all_users_for_space(&self, space_id: &Uuid) -> AppResult<Vec<SpaceUser>> {
+    async fn get_all_users_for_space(
+        &self,
+        space_id: &Uuid,
+        after: Option<Keyset<DateTime<Utc>>>,
+        limit: i64,
+    ) -> AppResult<Vec<SpaceUser>> {
+        let (created_at, id) = Keyset::asc(after);
         let rows = self
-            .db
-            .query(&self.user_space_stmts.get_all_users_for_space, &[&space_id])
+            .query(&self.user_space_stmts.get_all_users_for_space, &[&space_id, &created_at, &id, &limit])
             .await
             .map_err(|err| ErrType::DbError.err(err, "Failed to get users for space"))?;
 
@@ -205,7 +214,6 @@ impl UserSpaceDs for Datastore {
 
     async fn update_space_user_role(&self, space_member_id: Uuid, role: SpaceRole) -> AppResult<()> {
         let _ = self
-            .db
             .query_one(&self.user_space_stmts.update, &[&space_member_id, &role.value()])
             .await
             .map_err(|err| ErrType::DbError.err(err, "Failed to update user space role"))?;
@@ -214,7 +222,6 @@ impl UserSpaceDs for Datastore {
 
     async fn remove_user_from_space(&self, space_member_id: Uuid) -> AppResult<()> {
         let _ = self
-            .db
             .query_one(&self.user_space_stmts.delete, &[&space_member_id])
             .await
             .map_err(|err| ErrType::DbError.err(err, "Failed to delete user from space"))?;
@@ -0,0 +1,50 @@
+pub mod res {
+    use ser_mapper::impl_dto;
+    use utoipa::ToSchema;
+
+    use crate::{
+        datastore::audit::{AuditAction, AuditEntry},
+        dto::{_IdOptionRef, _IdRef, _IdVecRef, Datetime},
+    };
+
+    impl_dto!(
+        #[derive(ToSchema)]
+        pub struct AuditEntryResponse<AuditEntry> {
+            id: String = id => _IdRef,
+            created_at: Datetime = created_at,
+
+            actor: String = actor_id => _IdRef,
+            req_id: String = req_id,
+            action: AuditAction = action,
+
+            /// Album, tag, comment, member or job acted on
+            target: Option<String> = target_id => _IdOptionRef,
+            files: Vec<String> = file_ids => _IdVecRef,
+        }
+    );
+}
+
+pub mod req {
+    use chrono::{DateTime, Utc};
+    use serde::Deserialize;
+    use utoipa::ToSchema;
+    use uuid::Uuid;
+
+    use crate::datastore::audit::AuditAction;
+
+    /// Audit filters combined with AND
+    #[derive(Deserialize, ToSchema)]
+    pub struct AuditQuery {
+        pub actor_id: Option<Uuid>,
+        pub action: Option<AuditAction>,
+
+        /// Entries acting on the album, tag, comment, member or file
+        pub target_id: Option<Uuid>,
+
+        /// `created_at` range, `created_before` exclusive
+        #[schema(value_type = Option<String>, format = DateTime)]
+        pub created_after: Option<DateTime<Utc>>,
+        #[schema(value_type = Option<String>, format = DateTime)]
+        pub created_before: Option<DateTime<Utc>>,
+    }
+}
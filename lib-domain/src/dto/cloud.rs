@@ -5,7 +5,9 @@ pub mod res {
     use utoipa::ToSchema;
 
     use crate::{
-        datastore::storage::{Album, FileMeta, MediaFile, Metadata, NodeMetadata},
+        datastore::storage::{
+            AlbumDetails, AlbumSortMode, FileMeta, MediaFile, Metadata, NodeMetadata, TimelineBucket,
+        },
         dto::{_IdOptionRef, _IdRef, Datetime},
     };
 
@@ -15,6 +17,73 @@ pub mod res {
         pub file_name: String,
     }
 
+    #[derive(Serialize, ToSchema)]
+    pub struct UploadedPartResponse {
+        pub part_number: i32,
+        pub size: i64,
+    }
+
+    #[derive(Serialize, ToSchema)]
+    pub struct UploadSessionResponse {
+        pub id: String,
+        pub file_name: String,
+        pub file_size: i64,
+        pub part_size: i64,
+        pub part_count: i32,
+
+        /// Parts already stored, clients resume by uploading the missing ones
+        pub uploaded_parts: Vec<UploadedPartResponse>,
+    }
+
+    #[derive(Serialize, ToSchema)]
+    pub struct UploadPartUrlResponse {
+        pub part_number: i32,
+        pub url: String,
+    }
+
+    #[derive(Serialize, ToSchema)]
+    pub struct UploadPartUrlsResponse {
+        pub parts: Vec<UploadPartUrlResponse>,
+    }
+
+    #[derive(Serialize, ToSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub enum BulkFailureReason {
+        NotFound,
+
+        /// File was uploaded by someone else and the role only changes own files
+        Forbidden,
+    }
+
+    #[derive(Serialize, ToSchema)]
+    pub struct BulkFailureResponse {
+        pub id: String,
+        pub reason: BulkFailureReason,
+    }
+
+    #[derive(Serialize, ToSchema)]
+    pub struct BulkFilesResponse {
+        /// Files the operation was applied to
+        pub processed: usize,
+
+        /// Files skipped, with the reason
+        pub failed: Vec<BulkFailureResponse>,
+    }
+
+    #[derive(Serialize, ToSchema)]
+    pub struct TransferredFileResponse {
+        pub source_id: String,
+        pub target_id: String,
+
+        /// Target space already had the file, nothing was copied
+        pub deduplicated: bool,
+    }
+
+    #[derive(Serialize, ToSchema)]
+    pub struct TransferFilesResponse {
+        pub files: Vec<TransferredFileResponse>,
+    }
+
     #[derive(Serialize, ToSchema)]
     pub struct DownloadUrlResponse {
         pub url: String,
@@ -70,19 +139,28 @@ pub mod res {
         }
     );
 
+    /// Media file with its favorite flag for the requesting user and comment count
+    pub struct FileView {
+        pub file: MediaFile,
+        pub favorite: bool,
+        pub comment_count: i64,
+    }
+
     impl_dto!(
         #[derive(ToSchema)]
-        pub struct FileResponse<MediaFile> {
-            id: String = id => _IdRef,
-            created_at: Datetime = created_at,
-            updated_at: Datetime = updated_at,
+        pub struct FileResponse<FileView> {
+            id: String = file.id => _IdRef,
+            created_at: Datetime = file.created_at,
+            updated_at: Datetime = file.updated_at,
 
-            file_name: String = file_name,
-            file_size: u64 = node_size,
-            object_key: String = object_key,
-            user: String = user_id => _IdRef,
-            space: String = space_id => _IdRef,
-            metadata: FileMetadataResponse = metadata => _FileMetadataResponseRef,
+            file_name: String = file.file_name,
+            file_size: u64 = file.node_size,
+            object_key: String = file.object_key,
+            user: String = file.user_id => _IdRef,
+            space: String = file.space_id => _IdRef,
+            metadata: FileMetadataResponse = file.metadata => _FileMetadataResponseRef,
+            favorite: bool = favorite,
+            comment_count: i64 = comment_count,
         }
     );
 
@@ -91,34 +169,73 @@ pub mod res {
         pub struct FileMetaResponse<FileMeta> {
             id: String = id => _IdRef,
             updated_at: Datetime = updated_at,
+            taken_at: Datetime = taken_at,
 
             file_name: String = file_name,
             media_type: MediaType = media_type,
             user: Option<String> = user => _IdOptionRef,
             width: u32 = width,
             height: u32 = height,
+            favorite: bool = favorite,
+            comment_count: i64 = comment_count,
         }
     );
 
     impl_dto!(
         #[derive(ToSchema)]
-        pub struct AlbumResponse<Album> {
-            id: String = id => _IdRef,
-            created_at: Datetime = created_at,
-            updated_at: Datetime = updated_at,
+        pub struct TimelineBucketResponse<TimelineBucket> {
+            start: Datetime = start,
+            end: Datetime = end,
+            count: i64 = count,
+        }
+    );
 
-            name: String = name,
-            legacy_path: String = legacy_path,
+    /// Album details with the cover thumbnail resolved to a URL
+    pub struct AlbumView {
+        pub details: AlbumDetails,
+        pub cover_thumbnail_url: Option<String>,
+    }
+
+    impl_dto!(
+        #[derive(ToSchema)]
+        pub struct AlbumResponse<AlbumView> {
+            id: String = details.album.id => _IdRef,
+            created_at: Datetime = details.album.created_at,
+            updated_at: Datetime = details.album.updated_at,
+
+            name: String = details.album.name,
+            legacy_path: String = details.album.legacy_path,
+            description: Option<String> = details.album.description,
+            sort_mode: AlbumSortMode = details.album.sort_mode,
+
+            item_count: i64 = details.item_count,
+            total_size: i64 = details.total_size,
+            first_taken_at: Option<Datetime> = details.first_taken_at,
+            last_taken_at: Option<Datetime> = details.last_taken_at,
+
+            cover_file_id: Option<String> = details.cover_file_id => _IdOptionRef,
+            cover_thumbnail_url: Option<String> = cover_thumbnail_url,
         }
     );
 }
 
 pub mod req {
+    use chrono::{DateTime, Utc};
+    use lib_core::{smq_dto::MediaType, storage::MAX_OBJECT_SIZE};
     use serde::Deserialize;
     use utoipa::ToSchema;
     use uuid::Uuid;
     use validator::Validate;
 
+    use crate::{
+        datastore::storage::{AlbumPlacement, AlbumSortMode, Orientation, TimelineGranularity},
+        dto::double_option,
+        service::transfer::MAX_TRANSFER_FILES,
+    };
+
+    /// Files per bulk request, each id is reported back in the response
+    pub const MAX_BULK_FILES: u64 = 1000;
+
     #[derive(Deserialize, ToSchema, Validate)]
     pub struct InitiateUploadRequest {
         #[validate(length(min = 3))]
@@ -126,6 +243,28 @@ pub mod req {
 
         #[validate(length(equal = 64))]
         pub hash: String,
+
+        /// Reserves quota for the upload, without it only full spaces reject the upload
+        #[validate(range(min = 1, max = MAX_OBJECT_SIZE))]
+        pub file_size: Option<i64>,
+    }
+
+    #[derive(Deserialize, ToSchema, Validate)]
+    pub struct CreateUploadSessionRequest {
+        #[validate(length(min = 3))]
+        pub file_name: String,
+
+        #[validate(length(equal = 64))]
+        pub hash: String,
+
+        #[validate(range(min = 1, max = MAX_OBJECT_SIZE))]
+        pub file_size: i64,
+    }
+
+    #[derive(Deserialize, ToSchema, Validate)]
+    pub struct SignUploadPartsRequest {
+        #[validate(length(min = 1, max = 100))]
+        pub part_numbers: Vec<i32>,
     }
 
     #[derive(Deserialize, ToSchema, Validate)]
@@ -135,19 +274,156 @@ pub mod req {
 
         #[validate(length(equal = 64))]
         pub hash: String,
-        pub file_size: usize,
+
+        /// Must match the uploaded object
+        #[validate(range(min = 1, max = MAX_OBJECT_SIZE))]
+        pub file_size: i64,
         pub updated_millis: i64,
     }
 
+    #[derive(Deserialize, ToSchema, Default, Clone, Copy)]
+    #[serde(rename_all = "snake_case")]
+    pub enum MediaVariant {
+        #[default]
+        Original,
+        Thumbnail,
+        Preview,
+    }
+    impl MediaVariant {
+        pub fn as_str(&self) -> &'static str {
+            match self {
+                Self::Original => "original",
+                Self::Thumbnail => "thumbnail",
+                Self::Preview => "preview",
+            }
+        }
+    }
+
+    /// Query of a signed media content URL, see [`lib_core::storage::Storage::generate_content_signed_url`]
+    #[derive(Deserialize, ToSchema)]
+    pub struct MediaContentQuery {
+        pub space_id: Uuid,
+
+        /// User the URL was generated for, must still be a member of the space
+        pub user_id: Uuid,
+
+        #[serde(default)]
+        pub variant: MediaVariant,
+        pub expires: i64,
+        pub signature: String,
+    }
+
+    /// Gallery filters combined with AND
+    #[derive(Deserialize, ToSchema)]
+    pub struct GalleryFilterQuery {
+        /// `taken_at` range, `taken_before` exclusive
+        #[schema(value_type = Option<String>, format = DateTime)]
+        pub taken_after: Option<DateTime<Utc>>,
+        #[schema(value_type = Option<String>, format = DateTime)]
+        pub taken_before: Option<DateTime<Utc>>,
+
+        pub media_type: Option<MediaType>,
+
+        /// Camera make and model, case insensitive
+        pub make: Option<String>,
+        pub model: Option<String>,
+
+        pub uploader: Option<Uuid>,
+        pub orientation: Option<Orientation>,
+        pub has_location: Option<bool>,
+
+        /// GPS bounding box, all four bounds are required together
+        ///
+        /// `min_lon` greater than `max_lon` selects a box crossing the antimeridian.
+        pub min_lat: Option<f64>,
+        pub max_lat: Option<f64>,
+        pub min_lon: Option<f64>,
+        pub max_lon: Option<f64>,
+
+        /// Files carrying the tag
+        pub tag_id: Option<Uuid>,
+    }
+
+    #[derive(Deserialize, ToSchema)]
+    pub struct TimelineQuery {
+        pub granularity: TimelineGranularity,
+
+        /// IANA timezone buckets are cut in, defaults to `UTC`
+        pub tz: Option<String>,
+
+        /// Counts files of the album only
+        pub album_id: Option<Uuid>,
+    }
+
     #[derive(Deserialize, ToSchema, Validate)]
     pub struct CreateAlbumRequest {
         #[validate(length(min = 3, max = 255))]
         pub name: String,
     }
 
+    /// Absent fields are kept, `null` clears the description or resets the cover to the latest taken file
+    #[derive(Deserialize, ToSchema, Validate)]
+    pub struct UpdateAlbumRequest {
+        #[validate(length(min = 3, max = 255))]
+        pub name: Option<String>,
+
+        #[serde(default, deserialize_with = "double_option")]
+        #[schema(value_type = Option<String>)]
+        #[validate(length(max = 2000))]
+        pub description: Option<Option<String>>,
+
+        /// File of the album shown as its cover
+        #[serde(default, deserialize_with = "double_option")]
+        #[schema(value_type = Option<Uuid>)]
+        pub cover_file_id: Option<Option<Uuid>>,
+
+        pub sort_mode: Option<AlbumSortMode>,
+    }
+
     #[derive(Deserialize, ToSchema, Validate)]
     pub struct UpdateAlbumFilesRequest {
-        #[validate(length(min = 1))]
+        #[validate(length(min = 1, max = MAX_BULK_FILES))]
+        pub file_ids: Vec<Uuid>,
+    }
+
+    /// Places files of the album next to the anchor file in the given order
+    #[derive(Deserialize, ToSchema, Validate)]
+    pub struct ReorderAlbumFilesRequest {
+        #[validate(length(min = 1, max = MAX_BULK_FILES))]
+        pub file_ids: Vec<Uuid>,
+
+        pub anchor_file_id: Uuid,
+        pub placement: AlbumPlacement,
+    }
+
+    #[derive(Deserialize, ToSchema, Validate)]
+    pub struct MoveAlbumFilesRequest {
+        pub target_album_id: Uuid,
+
+        #[validate(length(min = 1, max = MAX_BULK_FILES))]
+        pub file_ids: Vec<Uuid>,
+    }
+
+    #[derive(Deserialize, ToSchema, Validate)]
+    pub struct BulkFilesRequest {
+        #[validate(length(min = 1, max = MAX_BULK_FILES))]
+        pub file_ids: Vec<Uuid>,
+    }
+
+    #[derive(Deserialize, ToSchema, Validate)]
+    pub struct TransferFilesRequest {
+        pub target_space_id: Uuid,
+
+        #[validate(length(min = 1, max = MAX_TRANSFER_FILES))]
+        pub file_ids: Vec<Uuid>,
+
+        /// Album of the target space to add files to, album memberships are dropped otherwise
+        pub target_album_id: Option<Uuid>,
+    }
+
+    #[derive(Deserialize, ToSchema, Validate)]
+    pub struct ArchiveFilesRequest {
+        #[validate(length(min = 1, max = 1000))]
         pub file_ids: Vec<Uuid>,
     }
 }
@@ -0,0 +1,40 @@
+pub mod res {
+    use ser_mapper::impl_dto;
+    use utoipa::ToSchema;
+
+    use crate::{
+        datastore::comment::Comment,
+        dto::{_IdRef, Datetime},
+    };
+
+    impl_dto!(
+        #[derive(ToSchema)]
+        pub struct CommentResponse<Comment> {
+            id: String = id => _IdRef,
+            created_at: Datetime = created_at,
+            updated_at: Datetime = updated_at,
+
+            file: String = media_file_id => _IdRef,
+            user: String = user_id => _IdRef,
+            body: String = body,
+        }
+    );
+}
+
+pub mod req {
+    use serde::Deserialize;
+    use utoipa::ToSchema;
+    use validator::Validate;
+
+    #[derive(Deserialize, ToSchema, Validate)]
+    pub struct CreateCommentRequest {
+        #[validate(length(min = 1, max = 2000))]
+        pub body: String,
+    }
+
+    #[derive(Deserialize, ToSchema, Validate)]
+    pub struct UpdateCommentRequest {
+        #[validate(length(min = 1, max = 2000))]
+        pub body: String,
+    }
+}
@@ -0,0 +1,42 @@
+pub mod res {
+    use lib_core::smq_dto::MediaType;
+    use ser_mapper::impl_dto;
+    use utoipa::ToSchema;
+
+    use crate::{
+        datastore::favorite::FavoriteFileMeta,
+        dto::{_IdOptionRef, _IdRef, Datetime},
+    };
+
+    impl_dto!(
+        #[derive(ToSchema)]
+        pub struct FavoriteFileResponse<FavoriteFileMeta> {
+            id: String = file.id => _IdRef,
+            updated_at: Datetime = file.updated_at,
+            taken_at: Datetime = file.taken_at,
+
+            file_name: String = file.file_name,
+            media_type: MediaType = file.media_type,
+            user: Option<String> = file.user => _IdOptionRef,
+            width: u32 = file.width,
+            height: u32 = file.height,
+            comment_count: i64 = file.comment_count,
+
+            /// Space to pass in `x-space-id` when fetching the file
+            space: String = space_id => _IdRef,
+            favorited_at: Datetime = favorited_at,
+        }
+    );
+}
+
+pub mod req {
+    use serde::Deserialize;
+    use utoipa::ToSchema;
+    use uuid::Uuid;
+
+    #[derive(Deserialize, ToSchema)]
+    pub struct FavoritesQuery {
+        /// Favorites of this space only, every space of the user otherwise
+        pub space_id: Option<Uuid>,
+    }
+}
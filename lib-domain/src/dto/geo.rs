@@ -0,0 +1,44 @@
+pub mod res {
+    use ser_mapper::impl_dto;
+    use utoipa::ToSchema;
+
+    use crate::{datastore::geo::MapCluster, dto::_IdRef};
+
+    impl_dto!(
+        #[derive(ToSchema)]
+        pub struct MapClusterResponse<MapCluster> {
+            count: i64 = count,
+            lat: f64 = lat,
+            lon: f64 = lon,
+
+            /// Latest taken file, shown as the marker
+            file_id: String = file_id => _IdRef,
+
+            /// Extent of the cluster files, see `/v1/media/map/files`
+            min_lat: f64 = bounds.min_lat,
+            max_lat: f64 = bounds.max_lat,
+            min_lon: f64 = bounds.min_lon,
+            max_lon: f64 = bounds.max_lon,
+        }
+    );
+}
+
+pub mod req {
+    use serde::Deserialize;
+    use utoipa::ToSchema;
+
+    /// Visible map area, `min_lon` greater than `max_lon` crosses the antimeridian
+    #[derive(Deserialize, ToSchema)]
+    pub struct MapBoundsQuery {
+        pub min_lat: f64,
+        pub max_lat: f64,
+        pub min_lon: f64,
+        pub max_lon: f64,
+    }
+
+    #[derive(Deserialize, ToSchema)]
+    pub struct MapZoomQuery {
+        /// Web map zoom level, 0 to 20
+        pub zoom: u8,
+    }
+}
@@ -0,0 +1,26 @@
+pub mod res {
+    use ser_mapper::impl_dto;
+    use utoipa::ToSchema;
+
+    use crate::{
+        datastore::job::{Job, JobKind, JobStatus},
+        dto::{_IdRef, Datetime},
+    };
+
+    impl_dto!(
+        #[derive(ToSchema)]
+        pub struct JobResponse<Job> {
+            id: String = id => _IdRef,
+            created_at: Datetime = created_at,
+            updated_at: Datetime = updated_at,
+
+            space: String = space_id => _IdRef,
+            kind: JobKind = kind,
+            status: JobStatus = status,
+            progress: i64 = progress,
+            #[schema(value_type = Object)]
+            report: serde_json::Value = report,
+            error: Option<String> = error,
+        }
+    );
+}
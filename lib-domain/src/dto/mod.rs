@@ -1,15 +1,24 @@
 use chrono::{DateTime, Utc};
 use ser_mapper::impl_dto;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use utoipa::{
     openapi::{schema::SchemaType, KnownFormat, ObjectBuilder, RefOr, Schema, SchemaFormat, Type},
     PartialSchema, ToSchema,
 };
 use uuid::Uuid;
 
+pub mod audit;
 pub mod cloud;
+pub mod comment;
+pub mod favorite;
+pub mod geo;
+pub mod job;
 pub mod native_app;
+pub mod search;
 pub mod space;
+pub mod tag;
+pub mod trash;
+pub mod usage;
 pub mod user;
 
 #[derive(Serialize)]
@@ -28,6 +37,33 @@ impl PartialSchema for Datetime {
     }
 }
 
+/// Page of a keyset paginated listing
+#[derive(Serialize, ToSchema)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+
+    /// Cursor of the next page, `null` on the last page
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct PageQuery {
+    /// `next_cursor` of the previous page
+    pub cursor: Option<String>,
+
+    /// Page size, defaults to 100, at most 500
+    pub limit: Option<i64>,
+}
+
+/// Tells an absent field (`None`) from an explicit `null` (`Some(None)`), paired with `#[serde(default)]`
+pub(crate) fn double_option<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: serde::Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
 impl_dto!(@define_dto
     pub struct Id<Uuid> {
         __pad: u64,
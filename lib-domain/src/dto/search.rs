@@ -0,0 +1,43 @@
+pub mod res {
+    use lib_core::smq_dto::MediaType;
+    use ser_mapper::impl_dto;
+    use utoipa::ToSchema;
+
+    use crate::{
+        datastore::search::SearchFileMeta,
+        dto::{_IdOptionRef, _IdRef, Datetime},
+    };
+
+    impl_dto!(
+        #[derive(ToSchema)]
+        pub struct SearchFileResponse<SearchFileMeta> {
+            id: String = file.id => _IdRef,
+            updated_at: Datetime = file.updated_at,
+            taken_at: Datetime = file.taken_at,
+
+            file_name: String = file.file_name,
+            media_type: MediaType = file.media_type,
+            user: Option<String> = file.user => _IdOptionRef,
+            width: u32 = file.width,
+            height: u32 = file.height,
+            favorite: bool = file.favorite,
+            comment_count: i64 = file.comment_count,
+
+            rank: f32 = rank,
+
+            /// File name, camera metadata and matched album names with matches wrapped in `<b></b>`
+            highlight: String = highlight,
+        }
+    );
+}
+
+pub mod req {
+    use serde::Deserialize;
+    use utoipa::ToSchema;
+
+    #[derive(Deserialize, ToSchema)]
+    pub struct SearchQuery {
+        /// Web search syntax, quoted phrases, `or` and `-` exclusions are supported
+        pub q: String,
+    }
+}
@@ -85,4 +85,11 @@ pub mod req {
 
         pub role: SpaceRole,
     }
+
+    #[derive(Deserialize, ToSchema, Validate)]
+    pub struct ReconcileSpaceRequest {
+        /// Delete orphaned objects and flag broken files, otherwise only report them
+        #[serde(default)]
+        pub enforce: bool,
+    }
 }
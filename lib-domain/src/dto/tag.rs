@@ -0,0 +1,59 @@
+pub mod res {
+    use ser_mapper::impl_dto;
+    use utoipa::ToSchema;
+
+    use crate::{
+        datastore::tag::{Tag, TagCount},
+        dto::{_IdRef, Datetime},
+    };
+
+    impl_dto!(
+        #[derive(ToSchema)]
+        pub struct TagResponse<Tag> {
+            id: String = id => _IdRef,
+            created_at: Datetime = created_at,
+            updated_at: Datetime = updated_at,
+
+            name: String = name,
+        }
+    );
+
+    impl_dto!(
+        #[derive(ToSchema)]
+        pub struct TagCountResponse<TagCount> {
+            id: String = tag.id => _IdRef,
+            created_at: Datetime = tag.created_at,
+            updated_at: Datetime = tag.updated_at,
+
+            name: String = tag.name,
+
+            /// Tagged files out of trash
+            file_count: i64 = file_count,
+        }
+    );
+}
+
+pub mod req {
+    use serde::Deserialize;
+    use utoipa::ToSchema;
+    use uuid::Uuid;
+    use validator::Validate;
+
+    #[derive(Deserialize, ToSchema, Validate)]
+    pub struct CreateTagRequest {
+        #[validate(length(min = 1, max = 64))]
+        pub name: String,
+    }
+
+    #[derive(Deserialize, ToSchema, Validate)]
+    pub struct UpdateTagRequest {
+        #[validate(length(min = 1, max = 64))]
+        pub name: String,
+    }
+
+    /// Files of the tag move to the target tag, the tag is deleted
+    #[derive(Deserialize, ToSchema, Validate)]
+    pub struct MergeTagRequest {
+        pub target_tag_id: Uuid,
+    }
+}
@@ -0,0 +1,35 @@
+pub mod res {
+    use lib_core::smq_dto::MediaType;
+    use ser_mapper::impl_dto;
+    use utoipa::ToSchema;
+
+    use crate::{
+        datastore::storage::{Album, MediaFile},
+        dto::{_IdRef, Datetime},
+    };
+
+    impl_dto!(
+        #[derive(ToSchema)]
+        pub struct TrashedFileResponse<MediaFile> {
+            id: String = id => _IdRef,
+            updated_at: Datetime = updated_at,
+            deleted_at: Option<Datetime> = deleted_at,
+
+            file_name: String = file_name,
+            file_size: i64 = node_size,
+            media_type: Option<MediaType> = metadata.media_type,
+            user: String = user_id => _IdRef,
+        }
+    );
+
+    impl_dto!(
+        #[derive(ToSchema)]
+        pub struct TrashedAlbumResponse<Album> {
+            id: String = id => _IdRef,
+            updated_at: Datetime = updated_at,
+            deleted_at: Option<Datetime> = deleted_at,
+
+            name: String = name,
+        }
+    );
+}
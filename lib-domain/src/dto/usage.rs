@@ -0,0 +1,29 @@
+pub mod res {
+    use lib_core::smq_dto::MediaType;
+    use serde::Serialize;
+    use utoipa::ToSchema;
+
+    #[derive(Serialize, ToSchema)]
+    pub struct UploaderUsageResponse {
+        pub user: String,
+        pub bytes: i64,
+        pub file_count: i64,
+    }
+
+    #[derive(Serialize, ToSchema)]
+    pub struct MediaTypeUsageResponse {
+        pub media_type: MediaType,
+        pub bytes: i64,
+        pub file_count: i64,
+    }
+
+    #[derive(Serialize, ToSchema)]
+    pub struct SpaceUsageResponse {
+        /// Quota in bytes, `null` when unlimited
+        pub quota: Option<i64>,
+        pub used_bytes: i64,
+        pub file_count: i64,
+        pub by_uploader: Vec<UploaderUsageResponse>,
+        pub by_media_type: Vec<MediaTypeUsageResponse>,
+    }
+}
@@ -1,4 +1,4 @@
-use lib_core::clerk::TokenClaims;
+use lib_core::{clerk::TokenClaims, ReqId};
 use uuid::Uuid;
 
 use crate::datastore::user_space::SpaceRole;
@@ -15,10 +15,25 @@ impl Clone for UserId {
     }
 }
 
+/// Requesting user and request, recorded in the audit log on mutations
+pub struct Actor {
+    pub user_id: Uuid,
+    pub req_id: ReqId,
+}
+impl Clone for Actor {
+    fn clone(&self) -> Self {
+        Self {
+            user_id: self.user_id,
+            req_id: self.req_id.clone(),
+        }
+    }
+}
+
 pub struct SpaceCtx {
     pub membership_id: Uuid,
     pub space_id: Uuid,
     pub role: SpaceRole,
+    pub actor: Actor,
 }
 impl Clone for SpaceCtx {
     fn clone(&self) -> Self {
@@ -26,6 +41,7 @@ impl Clone for SpaceCtx {
             membership_id: self.membership_id,
             space_id: self.space_id,
             role: self.role,
+            actor: self.actor.clone(),
         }
     }
 }
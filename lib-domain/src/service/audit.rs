@@ -0,0 +1,101 @@
+use lib_core::{AppResult, ErrType};
+use uuid::Uuid;
+
+use crate::{
+    datastore::{
+        audit::{AuditAction, AuditDs, AuditFilter, AuditRecord},
+        user_space::SpaceRole,
+        Keyset,
+    },
+    dto::{
+        audit::{req::AuditQuery, res::_AuditEntryResponse},
+        Page, PageQuery,
+    },
+    extension::{Actor, SpaceCtx},
+};
+
+use super::{
+    pagination::{into_page, page_params},
+    ServiceWrapper,
+};
+
+pub trait AuditService: Send + Sync {
+    /// Audit log of the space latest first, readable by owners only
+    fn list_audit_entries(
+        &self,
+        space_ctx: SpaceCtx,
+        query: AuditQuery,
+        page: PageQuery,
+    ) -> impl Future<Output = AppResult<Page<_AuditEntryResponse>>> + Send;
+}
+
+impl<D: AuditDs> AuditService for ServiceWrapper<'_, D> {
+    async fn list_audit_entries(
+        &self,
+        SpaceCtx {
+            role,
+            space_id,
+            ..
+        }: SpaceCtx,
+        query: AuditQuery,
+        page: PageQuery,
+    ) -> AppResult<Page<_AuditEntryResponse>> {
+        match role {
+            SpaceRole::Owner | SpaceRole::DefaultSpace => (),
+            _ => return Err(ErrType::Unauthorized.msg("Cannot read audit log: Unauthorized non-owner role")),
+        };
+
+        let filter = audit_filter(query)?;
+        let (after, limit) = page_params(page)?;
+        let entries = self.ds.list_audit_entries(&space_id, &filter, after, limit + 1).await?;
+
+        into_page(
+            entries,
+            limit,
+            |entry| Keyset {
+                key: entry.created_at,
+                id: entry.id,
+            },
+            _AuditEntryResponse,
+        )
+    }
+}
+
+/// Appends a mutation of the space by `actor` to the audit log
+///
+/// Called with the transaction of the mutation, so the mutation does not commit without its entry.
+pub(super) async fn audit<D: AuditDs>(
+    ds: &D,
+    space_id: Uuid,
+    actor: &Actor,
+    action: AuditAction,
+    target_id: Option<Uuid>,
+    file_ids: &[Uuid],
+) -> AppResult<()> {
+    let record = AuditRecord {
+        space_id,
+        actor_id: actor.user_id,
+        req_id: &actor.req_id.0,
+        action,
+        target_id,
+        file_ids,
+    };
+
+    ds.record_audit(record).await
+}
+
+fn audit_filter(query: AuditQuery) -> AppResult<AuditFilter> {
+    if let (Some(after), Some(before)) = (query.created_after, query.created_before)
+        && after >= before
+    {
+        return Err(ErrType::BadRequest.msg("created_after must be before created_before"));
+    }
+
+    Ok(AuditFilter {
+        actor_id: query.actor_id,
+        action: query.action,
+        target_id: query.target_id,
+        created_after: query.created_after,
+        created_before: query.created_before,
+    })
+}
@@ -0,0 +1,61 @@
+use std::collections::HashSet;
+
+use lib_core::AppResult;
+use uuid::Uuid;
+
+use crate::{
+    datastore::{storage::StorageDs, user_space::SpaceRole},
+    dto::cloud::res::{BulkFailureReason, BulkFailureResponse, BulkFilesResponse},
+};
+
+/// Files among `file_ids` the caller may change, uploaders only change files they uploaded
+///
+/// Returns permitted ids and ids of files uploaded by someone else.
+pub(super) async fn partition_owned_files<D: StorageDs>(
+    ds: &D,
+    user_id: &Uuid,
+    role: SpaceRole,
+    space_id: &Uuid,
+    file_ids: &[Uuid],
+    trashed: bool,
+) -> AppResult<(Vec<Uuid>, HashSet<Uuid>)> {
+    let SpaceRole::Upload = role else {
+        return Ok((file_ids.to_vec(), HashSet::new()));
+    };
+
+    let mut permitted = Vec::with_capacity(file_ids.len());
+    let mut forbidden = HashSet::new();
+    for owner in ds.list_file_owners(space_id, file_ids, trashed).await? {
+        if owner.user_id == *user_id {
+            permitted.push(owner.id);
+        } else {
+            forbidden.insert(owner.id);
+        }
+    }
+
+    Ok((permitted, forbidden))
+}
+
+/// Reports requested ids missing from `processed` as forbidden or not found
+pub(super) fn bulk_response(requested: &[Uuid], processed: &[Uuid], forbidden: &HashSet<Uuid>) -> BulkFilesResponse {
+    let processed = processed.iter().collect::<HashSet<_>>();
+
+    let mut seen = HashSet::new();
+    let failed = requested
+        .iter()
+        .filter(|id| !processed.contains(id) && seen.insert(**id))
+        .map(|id| BulkFailureResponse {
+            id: id.to_string(),
+            reason: if forbidden.contains(id) {
+                BulkFailureReason::Forbidden
+            } else {
+                BulkFailureReason::NotFound
+            },
+        })
+        .collect();
+
+    BulkFilesResponse {
+        processed: processed.len(),
+        failed,
+    }
+}
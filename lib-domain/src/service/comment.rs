@@ -0,0 +1,216 @@
+use lib_core::{AppResult, ErrType};
+use uuid::Uuid;
+
+use crate::{
+    datastore::{
+        audit::{AuditAction, AuditDs},
+        comment::CommentDs,
+        storage::StorageDs,
+        transaction::TransactionDs,
+        user_space::SpaceRole,
+        Keyset,
+    },
+    dto::{
+        comment::{
+            req::{CreateCommentRequest, UpdateCommentRequest},
+            res::_CommentResponse,
+        },
+        Page, PageQuery,
+    },
+    extension::{SpaceCtx, UserId},
+};
+
+use super::{
+    audit::audit,
+    pagination::{into_page, page_params},
+    ServiceWrapper,
+};
+
+/// Length limit of the `comments.body` column, in characters
+const MAX_COMMENT_LEN: usize = 2000;
+
+pub trait CommentService: Send + Sync {
+    /// Comments file out of trash, every role of the space may comment
+    fn create_comment(
+        &self,
+        user_id: UserId,
+        space_ctx: SpaceCtx,
+        file_id: Uuid,
+        dto: CreateCommentRequest,
+    ) -> impl Future<Output = AppResult<_CommentResponse>> + Send;
+
+    /// Comments of the file oldest first
+    fn list_comments(
+        &self,
+        space_ctx: SpaceCtx,
+        file_id: Uuid,
+        page: PageQuery,
+    ) -> impl Future<Output = AppResult<Page<_CommentResponse>>> + Send;
+
+    /// Edits own comment only
+    fn update_comment(
+        &self,
+        user_id: UserId,
+        space_ctx: SpaceCtx,
+        file_id: Uuid,
+        comment_id: Uuid,
+        dto: UpdateCommentRequest,
+    ) -> impl Future<Output = AppResult<_CommentResponse>> + Send;
+
+    /// Deletes own comment, or any comment of the space with modify rights
+    fn delete_comment(
+        &self,
+        user_id: UserId,
+        space_ctx: SpaceCtx,
+        file_id: Uuid,
+        comment_id: Uuid,
+    ) -> impl Future<Output = AppResult<()>> + Send;
+}
+
+impl<D: CommentDs + StorageDs + TransactionDs + AuditDs> CommentService for ServiceWrapper<'_, D> {
+    async fn create_comment(
+        &self,
+        UserId(user_id): UserId,
+        SpaceCtx {
+            space_id,
+            actor,
+            ..
+        }: SpaceCtx,
+        file_id: Uuid,
+        CreateCommentRequest {
+            body,
+        }: CreateCommentRequest,
+    ) -> AppResult<_CommentResponse> {
+        let body = comment_body(&body)?;
+        let tx = self.ds.begin().await?;
+
+        let result = async {
+            let comment = tx
+                .create_comment(&space_id, &file_id, &user_id, body)
+                .await?
+                .ok_or(ErrType::NotFound.msg("Requested file not found"))?;
+            audit(&tx, space_id, &actor, AuditAction::CreateComment, Some(comment.id), &[file_id]).await?;
+
+            Ok(_CommentResponse(comment))
+        }
+        .await;
+
+        tx.finish(result).await
+    }
+
+    async fn list_comments(
+        &self,
+        SpaceCtx {
+            space_id,
+            ..
+        }: SpaceCtx,
+        file_id: Uuid,
+        page: PageQuery,
+    ) -> AppResult<Page<_CommentResponse>> {
+        let file = self.ds.get_file(space_id, file_id).await?;
+        if !matches!(file, Some(file) if file.deleted_at.is_none()) {
+            return Err(ErrType::NotFound.msg("Requested file not found"));
+        }
+
+        let (after, limit) = page_params(page)?;
+        let comments = self.ds.list_comments(&space_id, &file_id, after, limit + 1).await?;
+
+        into_page(
+            comments,
+            limit,
+            |comment| Keyset {
+                key: comment.created_at,
+                id: comment.id,
+            },
+            _CommentResponse,
+        )
+    }
+
+    async fn update_comment(
+        &self,
+        UserId(user_id): UserId,
+        SpaceCtx {
+            space_id,
+            actor,
+            ..
+        }: SpaceCtx,
+        file_id: Uuid,
+        comment_id: Uuid,
+        UpdateCommentRequest {
+            body,
+        }: UpdateCommentRequest,
+    ) -> AppResult<_CommentResponse> {
+        let comment = self
+            .ds
+            .get_comment(&space_id, &file_id, &comment_id)
+            .await?
+            .ok_or(ErrType::NotFound.msg("Comment not found"))?;
+
+        if comment.user_id != user_id {
+            return Err(ErrType::Unauthorized.msg("Cannot edit comment: Not the author"));
+        }
+
+        let body = comment_body(&body)?;
+        let tx = self.ds.begin().await?;
+
+        let result = async {
+            let comment = tx.update_comment(&comment_id, body).await?;
+            audit(&tx, space_id, &actor, AuditAction::UpdateComment, Some(comment_id), &[file_id]).await?;
+
+            Ok(_CommentResponse(comment))
+        }
+        .await;
+
+        tx.finish(result).await
+    }
+
+    async fn delete_comment(
+        &self,
+        UserId(user_id): UserId,
+        SpaceCtx {
+            role,
+            space_id,
+            actor,
+            ..
+        }: SpaceCtx,
+        file_id: Uuid,
+        comment_id: Uuid,
+    ) -> AppResult<()> {
+        let comment = self
+            .ds
+            .get_comment(&space_id, &file_id, &comment_id)
+            .await?
+            .ok_or(ErrType::NotFound.msg("Comment not found for deletion"))?;
+
+        if comment.user_id != user_id {
+            match role {
+                SpaceRole::Read | SpaceRole::Upload => {
+                    return Err(ErrType::Unauthorized.msg("Cannot delete comment: Unauthorized read|upload role"))
+                }
+                _ => (),
+            };
+        }
+
+        let tx = self.ds.begin().await?;
+
+        let result = async {
+            tx.delete_comment(&comment_id).await?;
+            audit(&tx, space_id, &actor, AuditAction::DeleteComment, Some(comment_id), &[file_id]).await
+        }
+        .await;
+
+        tx.finish(result).await
+    }
+}
+
+fn comment_body(body: &str) -> AppResult<&str> {
+    let body = body.trim();
+    if body.is_empty() {
+        return Err(ErrType::BadRequest.msg("Comment cannot be blank"));
+    }
+    if body.chars().count() > MAX_COMMENT_LEN {
+        return Err(ErrType::BadRequest.msg(format!("Comment must be at most {MAX_COMMENT_LEN} characters")));
+    }
+
+    Ok(body)
+}
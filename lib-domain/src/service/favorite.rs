@@ -0,0 +1,93 @@
+use lib_core::{AppResult, ErrType};
+use uuid::Uuid;
+
+use crate::{
+    datastore::{favorite::FavoriteDs, Keyset},
+    dto::{
+        favorite::{req::FavoritesQuery, res::_FavoriteFileResponse},
+        Page, PageQuery,
+    },
+    extension::{SpaceCtx, UserId},
+};
+
+use super::{
+    pagination::{into_page, page_params},
+    ServiceWrapper,
+};
+
+pub trait FavoriteService: Send + Sync {
+    /// Stars file of the space for the user, starring twice is a no-op
+    fn star_file(
+        &self,
+        user_id: UserId,
+        space_ctx: SpaceCtx,
+        file_id: Uuid,
+    ) -> impl Future<Output = AppResult<()>> + Send;
+
+    fn unstar_file(
+        &self,
+        user_id: UserId,
+        space_ctx: SpaceCtx,
+        file_id: Uuid,
+    ) -> impl Future<Output = AppResult<()>> + Send;
+
+    /// Favorites across the spaces of the user, latest starred first
+    fn list_favorites(
+        &self,
+        user_id: UserId,
+        query: FavoritesQuery,
+        page: PageQuery,
+    ) -> impl Future<Output = AppResult<Page<_FavoriteFileResponse>>> + Send;
+}
+
+impl<D: FavoriteDs> FavoriteService for ServiceWrapper<'_, D> {
+    async fn star_file(
+        &self,
+        UserId(user_id): UserId,
+        SpaceCtx {
+            space_id,
+            ..
+        }: SpaceCtx,
+        file_id: Uuid,
+    ) -> AppResult<()> {
+        if !self.ds.add_favorite(&user_id, &space_id, &file_id).await? {
+            return Err(ErrType::NotFound.msg("Requested file not found"));
+        }
+
+        Ok(())
+    }
+
+    async fn unstar_file(
+        &self,
+        UserId(user_id): UserId,
+        SpaceCtx {
+            space_id,
+            ..
+        }: SpaceCtx,
+        file_id: Uuid,
+    ) -> AppResult<()> {
+        self.ds.remove_favorite(&user_id, &space_id, &file_id).await
+    }
+
+    async fn list_favorites(
+        &self,
+        UserId(user_id): UserId,
+        FavoritesQuery {
+            space_id,
+        }: FavoritesQuery,
+        page: PageQuery,
+    ) -> AppResult<Page<_FavoriteFileResponse>> {
+        let (after, limit) = page_params(page)?;
+        let files = self.ds.list_favorites(&user_id, space_id, after, limit + 1).await?;
+
+        into_page(
+            files,
+            limit,
+            |file| Keyset {
+                key: file.favorited_at,
+                id: file.file.id,
+            },
+            _FavoriteFileResponse,
+        )
+    }
+}
@@ -0,0 +1,196 @@
+use lib_core::{AppResult, ErrType};
+
+use crate::{
+    datastore::{
+        geo::GeoDs,
+        storage::{GalleryFilter, GeoBounds, StorageDs},
+        Keyset,
+    },
+    dto::{
+        cloud::res::_FileMetaResponse,
+        geo::{
+            req::{MapBoundsQuery, MapZoomQuery},
+            res::_MapClusterResponseVec,
+        },
+        Page, PageQuery,
+    },
+    extension::{SpaceCtx, UserId},
+};
+
+use super::{
+    pagination::{into_page, page_params},
+    ServiceWrapper,
+};
+
+const MAX_ZOOM: u8 = 20;
+
+/// Grid cells across the width of a map tile, higher values give smaller clusters
+const CELLS_PER_TILE: f64 = 8.0;
+
+/// Grid cells the bounds may span, bounding the clusters returned
+///
+/// A 4K viewport spans about 8k cells
+const MAX_CELLS: f64 = 16_384.0;
+
+pub trait GeoService: Send + Sync {
+    /// Located files within the bounds clustered on a grid sized by `zoom`
+    ///
+    /// Bounds spanning more than [`MAX_CELLS`] grid cells are rejected
+    fn list_map_clusters(
+        &self,
+        space_ctx: SpaceCtx,
+        bounds: MapBoundsQuery,
+        zoom: MapZoomQuery,
+    ) -> impl Future<Output = AppResult<_MapClusterResponseVec>> + Send;
+
+    /// Located files within the bounds latest taken first, expands a cluster given its extent
+    fn list_map_files(
+        &self,
+        user_id: UserId,
+        space_ctx: SpaceCtx,
+        bounds: MapBoundsQuery,
+        page: PageQuery,
+    ) -> impl Future<Output = AppResult<Page<_FileMetaResponse>>> + Send;
+}
+
+impl<D: GeoDs + StorageDs> GeoService for ServiceWrapper<'_, D> {
+    async fn list_map_clusters(
+        &self,
+        SpaceCtx {
+            space_id,
+            ..
+        }: SpaceCtx,
+        bounds: MapBoundsQuery,
+        MapZoomQuery {
+            zoom,
+        }: MapZoomQuery,
+    ) -> AppResult<_MapClusterResponseVec> {
+        let bounds = geo_bounds(bounds.min_lat, bounds.max_lat, bounds.min_lon, bounds.max_lon)?;
+        let cell_size = grid_cell_size(&bounds, zoom)?;
+
+        self.ds.list_map_clusters(&space_id, &bounds, cell_size).await.map(_MapClusterResponseVec)
+    }
+
+    async fn list_map_files(
+        &self,
+        UserId(user_id): UserId,
+        SpaceCtx {
+            space_id,
+            ..
+        }: SpaceCtx,
+        bounds: MapBoundsQuery,
+        page: PageQuery,
+    ) -> AppResult<Page<_FileMetaResponse>> {
+        let filter = GalleryFilter {
+            bounds: Some(geo_bounds(bounds.min_lat, bounds.max_lat, bounds.min_lon, bounds.max_lon)?),
+            ..Default::default()
+        };
+
+        let (after, limit) = page_params(page)?;
+        let files = self.ds.list_files_gallery(&space_id, &user_id, &filter, after, limit + 1).await?;
+
+        into_page(
+            files,
+            limit,
+            |file| Keyset {
+                key: file.taken_at,
+                id: file.id,
+            },
+            _FileMetaResponse,
+        )
+    }
+}
+
+/// Cell size in degrees of the cluster grid at `zoom`, rejecting bounds spanning more than [`MAX_CELLS`]
+fn grid_cell_size(bounds: &GeoBounds, zoom: u8) -> AppResult<f64> {
+    if zoom > MAX_ZOOM {
+        return Err(ErrType::BadRequest.msg(format!("Zoom must be at most {MAX_ZOOM}")));
+    }
+
+    // a tile spans 360 / 2^zoom degrees of longitude
+    let cell_size = 360.0 / f64::from(1u32 << zoom) / CELLS_PER_TILE;
+
+    let lon_span = match bounds.min_lon <= bounds.max_lon {
+        true => bounds.max_lon - bounds.min_lon,
+        false => 360.0 - (bounds.min_lon - bounds.max_lon),
+    };
+    let cells = ((bounds.max_lat - bounds.min_lat) / cell_size + 1.0).ceil() * (lon_span / cell_size + 1.0).ceil();
+    if cells > MAX_CELLS {
+        return Err(ErrType::BadRequest.msg("Bounds too large for zoom, zoom out or narrow the bounds"));
+    }
+
+    Ok(cell_size)
+}
+
+/// Validated bounding box, `min_lon > max_lon` crosses the antimeridian
+pub(super) fn geo_bounds(min_lat: f64, max_lat: f64, min_lon: f64, max_lon: f64) -> AppResult<GeoBounds> {
+    if !(-90.0..=90.0).contains(&min_lat) || !(-90.0..=90.0).contains(&max_lat) || min_lat > max_lat {
+        return Err(ErrType::BadRequest.msg("Latitude bounds must be within -90..90 with min <= max"));
+    }
+    if !(-180.0..=180.0).contains(&min_lon) || !(-180.0..=180.0).contains(&max_lon) {
+        return Err(ErrType::BadRequest.msg("Longitude bounds must be within -180..180"));
+    }
+
+    Ok(GeoBounds {
+        min_lat,
+        max_lat,
+        min_lon,
+        max_lon,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn world() -> GeoBounds {
+        geo_bounds(-90.0, 90.0, -180.0, 180.0).unwrap()
+    }
+
+    #[test]
+    fn bounds_accept_edges_and_antimeridian() {
+        assert!(geo_bounds(-90.0, 90.0, -180.0, 180.0).is_ok());
+        assert!(geo_bounds(10.0, 10.0, 20.0, 20.0).is_ok());
+        assert!(geo_bounds(-10.0, 10.0, 170.0, -170.0).is_ok());
+    }
+
+    #[test]
+    fn bounds_reject_out_of_range() {
+        assert!(geo_bounds(-90.1, 0.0, 0.0, 1.0).is_err());
+        assert!(geo_bounds(0.0, 90.1, 0.0, 1.0).is_err());
+        assert!(geo_bounds(0.0, 1.0, -180.1, 1.0).is_err());
+        assert!(geo_bounds(0.0, 1.0, 0.0, 180.1).is_err());
+        assert!(geo_bounds(10.0, -10.0, 0.0, 1.0).is_err());
+        assert!(geo_bounds(f64::NAN, 1.0, 0.0, 1.0).is_err());
+        assert!(geo_bounds(0.0, 1.0, 0.0, f64::INFINITY).is_err());
+    }
+
+    #[test]
+    fn grid_covers_world_at_low_zoom() {
+        assert_eq!(grid_cell_size(&world(), 0).unwrap(), 45.0);
+        assert!(grid_cell_size(&world(), 3).is_ok());
+    }
+
+    #[test]
+    fn grid_rejects_too_many_cells() {
+        assert!(grid_cell_size(&world(), 6).is_err());
+
+        let narrow = geo_bounds(48.856, 48.859, 2.350, 2.353).unwrap();
+        assert!(grid_cell_size(&narrow, MAX_ZOOM).is_ok());
+    }
+
+    #[test]
+    fn grid_counts_antimeridian_span() {
+        // 20 degrees across the antimeridian, not 340
+        let crossing = geo_bounds(-10.0, 10.0, 170.0, -170.0).unwrap();
+        let wrapped = geo_bounds(-10.0, 10.0, -170.0, 170.0).unwrap();
+        assert!(grid_cell_size(&crossing, 8).is_ok());
+        assert!(grid_cell_size(&wrapped, 8).is_err());
+    }
+
+    #[test]
+    fn grid_rejects_zoom_above_max() {
+        let narrow = geo_bounds(48.856, 48.859, 2.350, 2.353).unwrap();
+        assert!(grid_cell_size(&narrow, MAX_ZOOM + 1).is_err());
+    }
+}
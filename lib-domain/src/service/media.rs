@@ -1,7 +1,8 @@
-use std::path::Path;
+use std::{collections::HashSet, path::Path};
 
-use chrono::DateTime;
+use chrono::{DateTime, Utc};
 use lib_core::{
+    config::{self, MediaDelivery},
     interconnect::ServiceInterconnect,
     smq_dto::{
         self,
@@ -9,25 +10,64 @@ use lib_core::{
         res::{FileData, ImageData, MediaData},
         MediaDatetime, MediaMetadata,
     },
-    storage::Storage,
-    AppResult, ErrType,
+    storage::{ArchiveEntry, ObjectContent, Storage, ARCHIVE_MANIFEST},
+    AppResult, ErrType, ErrorContext,
 };
 use reqwest::Response;
 use uuid::Uuid;
 
 use crate::{
-    datastore::{storage::StorageDs, user_space::SpaceRole},
-    dto::cloud::{
-        req::{InitiateUploadRequest, QueueMediaProcessRequest},
-        res::{
-            _AlbumResponse, _AlbumResponseVec, _FileMetaResponseVec, DownloadUrlResponse, InitiateUploadResponse,
-            StreamedUrlResponse,
+    datastore::{
+        audit::{AuditAction, AuditDs},
+        space::SpaceDs,
+        storage::{
+            AlbumDetails, AlbumFileMeta, AlbumReorder, AlbumSortMode, AlbumUpdate, ArchiveFile, GalleryFilter,
+            StorageDs,
         },
+        transaction::TransactionDs,
+        trash::TrashDs,
+        usage::UsageDs,
+        user_space::SpaceRole,
+        Keyset,
     },
-    extension::{SpaceCtx, UserId},
+    dto::{
+        cloud::{
+            req::{
+                GalleryFilterQuery, InitiateUploadRequest, MediaVariant, MoveAlbumFilesRequest,
+                QueueMediaProcessRequest, ReorderAlbumFilesRequest, TimelineQuery, UpdateAlbumRequest,
+            },
+            res::{
+                _AlbumResponse, _FileMetaResponse, _TimelineBucketResponseVec, AlbumView, BulkFilesResponse,
+                DownloadUrlResponse, InitiateUploadResponse, StreamedUrlResponse,
+            },
+        },
+        Page, PageQuery,
+    },
+    extension::{Actor, SpaceCtx, UserId},
+};
+
+use super::{
+    audit::audit,
+    bulk::{bulk_response, partition_owned_files},
+    geo::geo_bounds,
+    pagination::{into_page, page_params},
+    trash::ensure_not_trashed,
+    usage::reserve_space_quota,
+    ServiceWrapper,
 };
 
-use super::ServiceWrapper;
+/// Staged upload waiting for [`MediaService::verify_pending_upload`]
+pub struct PendingUpload {
+    pub user_id: Uuid,
+    pub space_id: Uuid,
+    pub actor: Actor,
+    pub file_name: String,
+    pub hash: String,
+    pub updated_date: DateTime<Utc>,
+}
+
+/// Spacing of album positions, as laid out by the album manual order migration
+const ALBUM_POSITION_GAP: i64 = 65536;
 
 pub trait MediaService: Send + Sync {
     fn create_album(
@@ -44,6 +84,10 @@ pub trait MediaService: Send + Sync {
         dto: InitiateUploadRequest,
     ) -> impl Future<Output = AppResult<InitiateUploadResponse>> + Send;
 
+    /// Promotes the staged upload and queues it for processing
+    ///
+    /// Uploads without a stored checksum are returned instead, to be hashed in the background
+    /// by [`MediaService::verify_pending_upload`] before they are promoted.
     fn queue_media_process(
         &self,
         user_id: UserId,
@@ -51,36 +95,98 @@ pub trait MediaService: Send + Sync {
         storage: &Storage,
         interconnect: &ServiceInterconnect,
         dto: QueueMediaProcessRequest,
+    ) -> impl Future<Output = AppResult<Option<PendingUpload>>> + Send;
+
+    /// Hashes the staged upload, promoting and queueing it when it matches the declared hash
+    ///
+    /// Mismatching uploads are deleted and their quota reservation released.
+    fn verify_pending_upload(
+        &self,
+        storage: &Storage,
+        interconnect: &ServiceInterconnect,
+        upload: PendingUpload,
     ) -> impl Future<Output = AppResult<()>> + Send;
 
     fn complete_media_queue(&self, space_id: Uuid, media_data: MediaData)
         -> impl Future<Output = AppResult<()>> + Send;
 
+    /// Album files in the sort mode of the album
     fn list_files(
         &self,
+        user_id: UserId,
         space_ctx: SpaceCtx,
         album_id: Uuid,
-    ) -> impl Future<Output = AppResult<_FileMetaResponseVec>> + Send;
+        page: PageQuery,
+    ) -> impl Future<Output = AppResult<Page<_FileMetaResponse>>> + Send;
+
+    /// Space files matching `filter`, latest taken first
+    fn list_files_gallery(
+        &self,
+        user_id: UserId,
+        space_ctx: SpaceCtx,
+        filter: GalleryFilterQuery,
+        page: PageQuery,
+    ) -> impl Future<Output = AppResult<Page<_FileMetaResponse>>> + Send;
+
+    /// File counts per year, month or day of capture time, newest first
+    fn get_timeline(
+        &self,
+        space_ctx: SpaceCtx,
+        query: TimelineQuery,
+    ) -> impl Future<Output = AppResult<_TimelineBucketResponseVec>> + Send;
 
-    fn list_files_gallery(&self, space_ctx: SpaceCtx) -> impl Future<Output = AppResult<_FileMetaResponseVec>> + Send;
+    fn list_albums(
+        &self,
+        space_ctx: SpaceCtx,
+        storage: &Storage,
+        page: PageQuery,
+    ) -> impl Future<Output = AppResult<Page<_AlbumResponse>>> + Send;
 
-    fn list_albums(&self, space_ctx: SpaceCtx) -> impl Future<Output = AppResult<_AlbumResponseVec>> + Send;
+    fn get_album(
+        &self,
+        space_ctx: SpaceCtx,
+        storage: &Storage,
+        album_id: Uuid,
+    ) -> impl Future<Output = AppResult<_AlbumResponse>> + Send;
 
-    fn get_album(&self, space_ctx: SpaceCtx, album_id: Uuid) -> impl Future<Output = AppResult<_AlbumResponse>> + Send;
+    /// Renames album, sets its description or cover, returns the updated album
+    fn update_album(
+        &self,
+        space_ctx: SpaceCtx,
+        storage: &Storage,
+        album_id: Uuid,
+        dto: UpdateAlbumRequest,
+    ) -> impl Future<Output = AppResult<_AlbumResponse>> + Send;
 
     fn link_album_files(
         &self,
         space_ctx: SpaceCtx,
         album_id: Uuid,
         file_ids: Vec<Uuid>,
-    ) -> impl Future<Output = AppResult<()>> + Send;
+    ) -> impl Future<Output = AppResult<BulkFilesResponse>> + Send;
 
     fn unlink_album_files(
         &self,
         space_ctx: SpaceCtx,
         album_id: Uuid,
         file_ids: Vec<Uuid>,
-    ) -> impl Future<Output = AppResult<()>> + Send;
+    ) -> impl Future<Output = AppResult<BulkFilesResponse>> + Send;
+
+    /// Places files next to `dto.anchor_file_id` for the manual sort mode
+    fn reorder_album_files(
+        &self,
+        space_ctx: SpaceCtx,
+        album_id: Uuid,
+        dto: ReorderAlbumFilesRequest,
+    ) -> impl Future<Output = AppResult<BulkFilesResponse>> + Send;
+
+    /// Moves files from album to `dto.target_album_id` of the same space
+    fn move_album_files(
+        &self,
+        space_ctx: SpaceCtx,
+        album_id: Uuid,
+        dto: MoveAlbumFilesRequest,
+    ) -> impl Future<Output = AppResult<BulkFilesResponse>> + Send;
 
     fn generate_thumbnail_preview_signed_urls(
         &self,
@@ -96,23 +202,59 @@ pub trait MediaService: Send + Sync {
         file_id: Uuid,
     ) -> impl Future<Output = AppResult<DownloadUrlResponse>> + Send;
 
+    /// Reads media variant for the proxy route, `range` and `if_none_match` are the HTTP header values
+    ///
+    /// The route is authorized by URL signature, so the space comes from the verified URL.
+    fn get_media_stream(
+        &self,
+        space_id: Uuid,
+        storage: &Storage,
+        file_id: Uuid,
+        variant: MediaVariant,
+        range: Option<String>,
+        if_none_match: Option<String>,
+    ) -> impl Future<Output = AppResult<ObjectContent>> + Send;
+
+    /// Archive name and entries for the files of an album
+    fn get_album_archive(
+        &self,
+        space_ctx: SpaceCtx,
+        album_id: Uuid,
+    ) -> impl Future<Output = AppResult<(String, Vec<ArchiveEntry>)>> + Send;
+
+    /// Archive name and entries for selected files, unknown ids are skipped
+    fn get_files_archive(
+        &self,
+        space_ctx: SpaceCtx,
+        file_ids: Vec<Uuid>,
+    ) -> impl Future<Output = AppResult<(String, Vec<ArchiveEntry>)>> + Send;
+
+    /// Moves album to trash, files stay in the gallery
     fn delete_album(&self, space_ctx: SpaceCtx, album_id: Uuid) -> impl Future<Output = AppResult<()>> + Send;
 
-    fn delete_file(
+    /// Moves file to trash, storage is freed once purged by [`super::trash::TrashService`]
+    ///
+    /// Uploaders only trash files they uploaded.
+    fn delete_file(&self, space_ctx: SpaceCtx, file_id: Uuid) -> impl Future<Output = AppResult<()>> + Send;
+
+    /// Moves files to trash, with the roles of [`Self::delete_file`]
+    ///
+    /// Files uploaded by someone else are reported forbidden instead of failing the request.
+    fn delete_files(
         &self,
         space_ctx: SpaceCtx,
-        storage: &Storage,
-        file_id: Uuid,
-    ) -> impl Future<Output = AppResult<()>> + Send;
+        file_ids: Vec<Uuid>,
+    ) -> impl Future<Output = AppResult<BulkFilesResponse>> + Send;
 }
 
-impl<D: StorageDs> MediaService for ServiceWrapper<'_, D> {
+impl<D: StorageDs + SpaceDs + UsageDs + TrashDs + TransactionDs + AuditDs> MediaService for ServiceWrapper<'_, D> {
     async fn create_album(
         &self,
         UserId(user_id): UserId,
         SpaceCtx {
             role,
             space_id,
+            actor,
             ..
         }: SpaceCtx,
         album_name: String,
@@ -121,7 +263,15 @@ impl<D: StorageDs> MediaService for ServiceWrapper<'_, D> {
             return Err(ErrType::Unauthorized.msg("Cannot create album: Unauthorized read role"));
         }
 
-        self.ds.create_album(&user_id, space_id, album_name).await.map(|_| ())
+        let tx = self.ds.begin().await?;
+
+        let result = async {
+            let album = tx.create_album(&user_id, space_id, album_name).await?;
+            audit(&tx, space_id, &actor, AuditAction::CreateAlbum, Some(album.id), &[]).await
+        }
+        .await;
+
+        tx.finish(result).await
     }
 
     async fn initiate_upload(
@@ -129,22 +279,28 @@ impl<D: StorageDs> MediaService for ServiceWrapper<'_, D> {
         SpaceCtx {
             role,
             space_id,
+            actor,
             ..
         }: SpaceCtx,
         storage: &Storage,
         InitiateUploadRequest {
             file_name,
             hash,
+            file_size,
         }: InitiateUploadRequest,
     ) -> AppResult<InitiateUploadResponse> {
         if let SpaceRole::Read = role {
             return Err(ErrType::Unauthorized.msg("Cannot upload: Unauthorized read role"));
         }
 
+        if ensure_not_trashed(self.ds.get_file_by_hash(&space_id, &hash).await?)?.is_none() {
+            reserve_space_quota(self.ds, &space_id, &actor.user_id, &hash, file_size).await?;
+        }
+
         let file_name = sanitize_file_name(file_name);
-        let object_key = get_canonical_object_key(&hash, &file_name);
+        let staging_key = get_staging_object_key(&actor.user_id, &hash, &file_name);
 
-        let url = storage.generate_upload_signed_url(&space_id.to_string(), &object_key).await?;
+        let url = storage.generate_upload_signed_url(&space_id.to_string(), &staging_key).await?;
         Ok(InitiateUploadResponse {
             url,
             file_name,
@@ -157,6 +313,7 @@ impl<D: StorageDs> MediaService for ServiceWrapper<'_, D> {
         SpaceCtx {
             space_id,
             role,
+            actor,
             ..
         }: SpaceCtx,
         storage: &Storage,
@@ -164,10 +321,10 @@ impl<D: StorageDs> MediaService for ServiceWrapper<'_, D> {
         QueueMediaProcessRequest {
             file_name,
             hash,
+            file_size,
             updated_millis,
-            ..
         }: QueueMediaProcessRequest,
-    ) -> AppResult<()> {
+    ) -> AppResult<Option<PendingUpload>> {
         if let SpaceRole::Read = role {
             return Err(ErrType::Unauthorized.msg("Cannot queue media: Unauthorized read role"));
         }
@@ -176,54 +333,61 @@ impl<D: StorageDs> MediaService for ServiceWrapper<'_, D> {
             return Err(ErrType::BadRequest.msg("Invalid timestamp"));
         };
 
-        let file_name = sanitize_file_name(file_name);
-        let object_key = get_canonical_object_key(&hash, &file_name);
+        let existing = ensure_not_trashed(self.ds.get_file_by_hash(&space_id, &hash).await?)?;
+        if existing.is_none() {
+            reserve_space_quota(self.ds, &space_id, &user_id, &hash, Some(file_size)).await?;
+        }
 
+        let upload = PendingUpload {
+            user_id,
+            space_id,
+            actor,
+            file_name: sanitize_file_name(file_name),
+            hash,
+            updated_date,
+        };
+        let staging_key = get_staging_object_key(&user_id, &upload.hash, &upload.file_name);
         let space_id_str = space_id.to_string();
-        let remote_path = storage.get_remote_path(&space_id_str, &object_key)?;
 
-        let file = self
-            .ds
-            .get_or_create_file(
-                &user_id,
-                &space_id,
-                &hash,
-                file_name,
-                object_key,
-                updated_date,
-                FileData {
-                    file_name: String::new(),
-                    thumbnail: ImageData::default(),
-                    preview: ImageData::default(),
-                    metadata: MediaMetadata::default(),
-                    size: 0,
-                    media_type: smq_dto::MediaType::Image,
-                },
-            )
-            .await?;
+        // content already stored under the key of the existing row, upsert keeps that key
+        if let Some(file) = existing {
+            storage.delete_file(&space_id_str, staging_key, None, None).await.context("s:queue_media_process")?;
+            self.queue_upload(storage, interconnect, upload, file.object_key).await?;
+            return Ok(None);
+        }
 
-        let payload_token = interconnect.get_sending_token()?;
-        let mq_url = interconnect.mq_uri("/v1/queue");
+        let verified = storage
+            .check_upload(&space_id_str, &staging_key, &upload.hash, file_size)
+            .await
+            .context("s:queue_media_process")?;
+        if !verified {
+            return Ok(Some(upload));
+        }
 
-        let response = request_mq_retry_until_ok(
-            &mq_url,
-            &payload_token,
-            ProcessMediaRequest {
-                file_id: file.id,
-                updated_date: MediaDatetime(updated_date),
-                space_id,
-                s3_file_path: remote_path,
-            },
-        )
-        .await?;
+        let object_key = get_canonical_object_key(&upload.hash, &upload.file_name);
+        storage.move_object(&space_id_str, &staging_key, &object_key).await.context("s:queue_media_process")?;
+        self.queue_upload(storage, interconnect, upload, object_key).await?;
 
-        let status = response.status();
-        if status.is_success() {
-            Ok(())
-        } else {
-            Err(ErrType::ServerError
-                .msg(format!("Unable to queue media for processing: {:?}", status.canonical_reason())))
+        Ok(None)
+    }
+
+    async fn verify_pending_upload(
+        &self,
+        storage: &Storage,
+        interconnect: &ServiceInterconnect,
+        upload: PendingUpload,
+    ) -> AppResult<()> {
+        let staging_key = get_staging_object_key(&upload.user_id, &upload.hash, &upload.file_name);
+        let space_id_str = upload.space_id.to_string();
+
+        if let Err(err) = storage.verify_sha256(&space_id_str, &staging_key, &upload.hash).await {
+            self.ds.release_usage_reservation(&upload.space_id, &upload.user_id, &upload.hash).await?;
+            return Err(err).context("s:verify_pending_upload");
         }
+
+        let object_key = get_canonical_object_key(&upload.hash, &upload.file_name);
+        storage.move_object(&space_id_str, &staging_key, &object_key).await.context("s:verify_pending_upload")?;
+        self.queue_upload(storage, interconnect, upload, object_key).await
     }
 
     async fn complete_media_queue(
@@ -258,61 +422,225 @@ impl<D: StorageDs> MediaService for ServiceWrapper<'_, D> {
             .then_some(None)
             .unwrap_or_else(|| Some(join_key_dir(&file.object_key, &file_data.preview.file_name)));
 
-        self.ds.update_file(file_id, &space_id, updated_date.0, file_data, thumbnail_key, preview_key).await?;
+        let media_type = file_data.media_type;
+        let tx = self.ds.begin().await?;
+
+        let result = async {
+            let updated =
+                tx.update_file(file_id, &space_id, updated_date.0, file_data, thumbnail_key, preview_key).await?;
+
+            // release previously accounted size on reprocessing
+            if file.node_size > 0 {
+                let old_media_type = file.metadata.media_type.unwrap_or(media_type);
+                tx.add_usage(&space_id, &file.user_id, old_media_type, -file.node_size, -1).await?;
+            }
+            tx.add_usage(&space_id, &updated.user_id, media_type, updated.node_size, 1).await?;
+            tx.release_usage_reservation(&space_id, &updated.user_id, &updated.hash).await
+        }
+        .await;
 
-        Ok(())
+        tx.finish(result).await
     }
 
     async fn list_files(
         &self,
+        UserId(user_id): UserId,
         SpaceCtx {
             space_id,
             ..
         }: SpaceCtx,
         album_id: Uuid,
-    ) -> AppResult<_FileMetaResponseVec> {
-        let _ = self.ds.get_album(&space_id, &album_id).await?.ok_or(ErrType::NotFound.msg("Album not found"))?;
-
-        let mut files = self.ds.list_files(&space_id, &album_id).await?;
-        files.sort_by(|a, b| a.file_name.cmp(&b.file_name));
-        Ok(_FileMetaResponseVec(files))
+        page: PageQuery,
+    ) -> AppResult<Page<_FileMetaResponse>> {
+        let album = self.ds.get_album(&space_id, &album_id).await?.ok_or(ErrType::NotFound.msg("Album not found"))?;
+
+        let map = |album_file: AlbumFileMeta| _FileMetaResponse(album_file.file);
+        match album.sort_mode {
+            AlbumSortMode::Name => {
+                let (after, limit) = page_params(page)?;
+                let files = self.ds.list_files(&space_id, &user_id, &album_id, after, limit + 1).await?;
+                into_page(
+                    files,
+                    limit,
+                    |album_file| Keyset {
+                        key: album_file.file.file_name.clone(),
+                        id: album_file.file.id,
+                    },
+                    map,
+                )
+            }
+            AlbumSortMode::CaptureDate => {
+                let (after, limit) = page_params(page)?;
+                let files = self.ds.list_files_by_taken_at(&space_id, &user_id, &album_id, after, limit + 1).await?;
+                into_page(
+                    files,
+                    limit,
+                    |album_file| Keyset {
+                        key: album_file.file.taken_at,
+                        id: album_file.file.id,
+                    },
+                    map,
+                )
+            }
+            AlbumSortMode::Manual => {
+                let (after, limit) = page_params(page)?;
+                let files = self.ds.list_files_by_position(&space_id, &user_id, &album_id, after, limit + 1).await?;
+                into_page(
+                    files,
+                    limit,
+                    |album_file| Keyset {
+                        key: album_file.position,
+                        id: album_file.file.id,
+                    },
+                    map,
+                )
+            }
+        }
     }
 
     async fn list_files_gallery(
+        &self,
+        UserId(user_id): UserId,
+        SpaceCtx {
+            space_id,
+            ..
+        }: SpaceCtx,
+        filter: GalleryFilterQuery,
+        page: PageQuery,
+    ) -> AppResult<Page<_FileMetaResponse>> {
+        let filter = gallery_filter(filter)?;
+        let (after, limit) = page_params(page)?;
+        let files = self.ds.list_files_gallery(&space_id, &user_id, &filter, after, limit + 1).await?;
+
+        into_page(
+            files,
+            limit,
+            |file| Keyset {
+                key: file.taken_at,
+                id: file.id,
+            },
+            _FileMetaResponse,
+        )
+    }
+
+    async fn get_timeline(
         &self,
         SpaceCtx {
             space_id,
             ..
         }: SpaceCtx,
-    ) -> AppResult<_FileMetaResponseVec> {
-        let files = self.ds.list_files_gallery(&space_id).await?;
-        let files: Vec<_> = files.into_iter().map(|g| g.0).collect();
-        Ok(_FileMetaResponseVec(files))
+        TimelineQuery {
+            granularity,
+            tz,
+            album_id,
+        }: TimelineQuery,
+    ) -> AppResult<_TimelineBucketResponseVec> {
+        if let Some(album_id) = album_id {
+            let _ = self.ds.get_album(&space_id, &album_id).await?.ok_or(ErrType::NotFound.msg("Album not found"))?;
+        }
+
+        let tz = tz.unwrap_or_else(|| "UTC".to_owned());
+        if !self.ds.timezone_exists(&tz).await? {
+            return Err(ErrType::BadRequest.msg(format!("Unknown timezone: {tz}")));
+        }
+        self.ds.list_timeline(&space_id, album_id, granularity, &tz).await.map(_TimelineBucketResponseVec)
     }
 
     async fn list_albums(
         &self,
         SpaceCtx {
             space_id,
+            actor,
             ..
         }: SpaceCtx,
-    ) -> AppResult<_AlbumResponseVec> {
-        self.ds.list_albums(space_id).await.map(_AlbumResponseVec)
+        storage: &Storage,
+        page: PageQuery,
+    ) -> AppResult<Page<_AlbumResponse>> {
+        let (after, limit) = page_params(page)?;
+        let albums = self.ds.list_albums(space_id, after, limit + 1).await?;
+
+        let space_id_str = space_id.to_string();
+        let mut views = Vec::with_capacity(albums.len());
+        for details in albums {
+            views.push(album_view(storage, &space_id_str, &actor.user_id, details).await?);
+        }
+
+        into_page(
+            views,
+            limit,
+            |view| Keyset {
+                key: view.details.album.name.clone(),
+                id: view.details.album.id,
+            },
+            _AlbumResponse,
+        )
     }
 
     async fn get_album(
         &self,
         SpaceCtx {
             space_id,
+            actor,
             ..
         }: SpaceCtx,
+        storage: &Storage,
         album_id: Uuid,
     ) -> AppResult<_AlbumResponse> {
-        self.ds
-            .get_album(&space_id, &album_id)
-            .await?
-            .ok_or(ErrType::NotFound.msg("Album not found"))
-            .map(_AlbumResponse)
+        let details =
+            self.ds.get_album_details(&space_id, &album_id).await?.ok_or(ErrType::NotFound.msg("Album not found"))?;
+
+        album_view(storage, &space_id.to_string(), &actor.user_id, details).await.map(_AlbumResponse)
+    }
+
+    async fn update_album(
+        &self,
+        SpaceCtx {
+            role,
+            space_id,
+            actor,
+            ..
+        }: SpaceCtx,
+        storage: &Storage,
+        album_id: Uuid,
+        UpdateAlbumRequest {
+            name,
+            description,
+            cover_file_id,
+            sort_mode,
+        }: UpdateAlbumRequest,
+    ) -> AppResult<_AlbumResponse> {
+        match role {
+            SpaceRole::Read | SpaceRole::Upload => {
+                return Err(ErrType::Unauthorized.msg("Cannot update album: Unauthorized read|upload role"))
+            }
+            _ => (),
+        };
+
+        let _ = self.ds.get_album(&space_id, &album_id).await?.ok_or(ErrType::NotFound.msg("Album not found"))?;
+
+        let update = AlbumUpdate {
+            name,
+            description,
+            cover_file_id,
+            sort_mode,
+        };
+        let tx = self.ds.begin().await?;
+
+        let result = async {
+            if tx.update_album(&space_id, &album_id, update).await?.is_none() {
+                return Err(ErrType::BadRequest.msg("Cover file is not in album"));
+            }
+
+            audit(&tx, space_id, &actor, AuditAction::UpdateAlbum, Some(album_id), &[]).await
+        }
+        .await;
+
+        tx.finish(result).await?;
+
+        let details =
+            self.ds.get_album_details(&space_id, &album_id).await?.ok_or(ErrType::NotFound.msg("Album not found"))?;
+
+        album_view(storage, &space_id.to_string(), &actor.user_id, details).await.map(_AlbumResponse)
     }
 
     async fn link_album_files(
@@ -320,18 +648,31 @@ impl<D: StorageDs> MediaService for ServiceWrapper<'_, D> {
         SpaceCtx {
             role,
             space_id,
+            actor,
             ..
         }: SpaceCtx,
         album_id: Uuid,
         file_ids: Vec<Uuid>,
-    ) -> AppResult<()> {
+    ) -> AppResult<BulkFilesResponse> {
         if let SpaceRole::Read = role {
             return Err(ErrType::Unauthorized.msg("Cannot link files: Unauthorized read role"));
         }
 
         let _ = self.ds.get_album(&space_id, &album_id).await?.ok_or(ErrType::NotFound.msg("Album not found"))?;
 
-        self.ds.link_album_files(&space_id, &album_id, &file_ids).await
+        let tx = self.ds.begin().await?;
+
+        let result = async {
+            let linked = tx.link_album_files(&space_id, &album_id, &file_ids).await?;
+            audit(&tx, space_id, &actor, AuditAction::LinkAlbumFiles, Some(album_id), &linked).await?;
+
+            Ok(linked)
+        }
+        .await;
+
+        let linked = tx.finish(result).await?;
+
+        Ok(bulk_response(&file_ids, &linked, &HashSet::new()))
     }
 
     async fn unlink_album_files(
@@ -339,24 +680,137 @@ impl<D: StorageDs> MediaService for ServiceWrapper<'_, D> {
         SpaceCtx {
             role,
             space_id,
+            actor,
             ..
         }: SpaceCtx,
         album_id: Uuid,
         file_ids: Vec<Uuid>,
-    ) -> AppResult<()> {
+    ) -> AppResult<BulkFilesResponse> {
         if let SpaceRole::Read = role {
             return Err(ErrType::Unauthorized.msg("Cannot unlink files: Unauthorized read role"));
         }
 
         let _ = self.ds.get_album(&space_id, &album_id).await?.ok_or(ErrType::NotFound.msg("Album not found"))?;
 
-        self.ds.unlink_album_files(&space_id, &album_id, &file_ids).await
+        let tx = self.ds.begin().await?;
+
+        let result = async {
+            let unlinked = tx.unlink_album_files(&space_id, &album_id, &file_ids).await?;
+            audit(&tx, space_id, &actor, AuditAction::UnlinkAlbumFiles, Some(album_id), &unlinked).await?;
+
+            Ok(unlinked)
+        }
+        .await;
+
+        let unlinked = tx.finish(result).await?;
+
+        Ok(bulk_response(&file_ids, &unlinked, &HashSet::new()))
+    }
+
+    async fn reorder_album_files(
+        &self,
+        SpaceCtx {
+            role,
+            space_id,
+            actor,
+            ..
+        }: SpaceCtx,
+        album_id: Uuid,
+        ReorderAlbumFilesRequest {
+            file_ids,
+            anchor_file_id,
+            placement,
+        }: ReorderAlbumFilesRequest,
+    ) -> AppResult<BulkFilesResponse> {
+        if let SpaceRole::Read = role {
+            return Err(ErrType::Unauthorized.msg("Cannot reorder files: Unauthorized read role"));
+        }
+
+        if file_ids.contains(&anchor_file_id) {
+            return Err(ErrType::BadRequest.msg("Anchor file cannot be reordered"));
+        }
+
+        let _ = self.ds.get_album(&space_id, &album_id).await?.ok_or(ErrType::NotFound.msg("Album not found"))?;
+
+        let tx = self.ds.begin().await?;
+
+        let result = async {
+            let reorder = tx.reorder_album_files(&space_id, &album_id, &file_ids, &anchor_file_id, placement).await?;
+            let reorder = match reorder {
+                AlbumReorder::NoGap => {
+                    // a gap wider than the moved files always leaves room next to the anchor
+                    let gap = (file_ids.len() as i64 + 1).max(ALBUM_POSITION_GAP);
+                    tx.renumber_album_files(&album_id, gap).await?;
+                    tx.reorder_album_files(&space_id, &album_id, &file_ids, &anchor_file_id, placement).await?
+                }
+                reorder => reorder,
+            };
+
+            let moved = match reorder {
+                AlbumReorder::AnchorNotFound => return Err(ErrType::BadRequest.msg("Anchor file is not in album")),
+                AlbumReorder::NoGap => return Err(ErrType::BadRequest.msg("No room left to reorder album files")),
+                AlbumReorder::Moved(moved) => moved,
+            };
+            audit(&tx, space_id, &actor, AuditAction::ReorderAlbumFiles, Some(album_id), &moved).await?;
+
+            Ok(moved)
+        }
+        .await;
+
+        let moved = tx.finish(result).await?;
+
+        Ok(bulk_response(&file_ids, &moved, &HashSet::new()))
+    }
+
+    async fn move_album_files(
+        &self,
+        SpaceCtx {
+            role,
+            space_id,
+            actor,
+            ..
+        }: SpaceCtx,
+        album_id: Uuid,
+        MoveAlbumFilesRequest {
+            target_album_id,
+            file_ids,
+        }: MoveAlbumFilesRequest,
+    ) -> AppResult<BulkFilesResponse> {
+        if let SpaceRole::Read = role {
+            return Err(ErrType::Unauthorized.msg("Cannot move files: Unauthorized read role"));
+        }
+
+        if target_album_id == album_id {
+            return Err(ErrType::BadRequest.msg("Target album must differ from source album"));
+        }
+
+        let _ = self.ds.get_album(&space_id, &album_id).await?.ok_or(ErrType::NotFound.msg("Album not found"))?;
+        let _ = self
+            .ds
+            .get_album(&space_id, &target_album_id)
+            .await?
+            .ok_or(ErrType::NotFound.msg("Target album not found"))?;
+
+        let tx = self.ds.begin().await?;
+
+        let result = async {
+            let moved = tx.move_album_files(&space_id, &album_id, &target_album_id, &file_ids).await?;
+            audit(&tx, space_id, &actor, AuditAction::MoveAlbumFiles, Some(album_id), &moved).await?;
+
+            Ok(moved)
+        }
+        .await;
+
+        let moved = tx.finish(result).await?;
+
+        Ok(bulk_response(&file_ids, &moved, &HashSet::new()))
     }
 
     async fn generate_thumbnail_preview_signed_urls(
         &self,
         SpaceCtx {
             space_id,
+            actor,
             ..
         }: SpaceCtx,
         storage: &Storage,
@@ -371,8 +825,11 @@ impl<D: StorageDs> MediaService for ServiceWrapper<'_, D> {
         let preview_key = stream_keys.preview_key.ok_or(ErrType::NotFound.msg("Preview key not found for file"))?;
 
         let space_id_str = space_id.to_string();
-        let thumbnail_stream = storage.generate_stream_signed_url(&space_id_str, &thumbnail_key).await?;
-        let preview_stream = storage.generate_stream_signed_url(&space_id_str, &preview_key).await?;
+        let thumbnail_stream =
+            get_media_url(storage, &space_id_str, &actor.user_id, file_id, MediaVariant::Thumbnail, &thumbnail_key)
+                .await?;
+        let preview_stream =
+            get_media_url(storage, &space_id_str, &actor.user_id, file_id, MediaVariant::Preview, &preview_key).await?;
 
         Ok(StreamedUrlResponse {
             thumbnail_url: thumbnail_stream,
@@ -384,6 +841,7 @@ impl<D: StorageDs> MediaService for ServiceWrapper<'_, D> {
         &self,
         SpaceCtx {
             space_id,
+            actor,
             ..
         }: SpaceCtx,
         storage: &Storage,
@@ -394,18 +852,80 @@ impl<D: StorageDs> MediaService for ServiceWrapper<'_, D> {
         };
 
         let space_id_str = space_id.to_string();
-        let download_stream = storage.generate_stream_signed_url(&space_id_str, &stream_key).await?;
+        let download_stream =
+            get_media_url(storage, &space_id_str, &actor.user_id, file_id, MediaVariant::Original, &stream_key).await?;
 
         Ok(DownloadUrlResponse {
             url: download_stream,
         })
     }
 
+    async fn get_media_stream(
+        &self,
+        space_id: Uuid,
+        storage: &Storage,
+        file_id: Uuid,
+        variant: MediaVariant,
+        range: Option<String>,
+        if_none_match: Option<String>,
+    ) -> AppResult<ObjectContent> {
+        let key = match variant {
+            MediaVariant::Original => self.ds.get_download_stream_key(&space_id, file_id).await?,
+            MediaVariant::Thumbnail | MediaVariant::Preview => {
+                let Some(stream_keys) = self.ds.get_thumbnail_preview_stream_keys(&space_id, file_id).await? else {
+                    return Err(ErrType::NotFound.msg("Requested file not found"));
+                };
+                match variant {
+                    MediaVariant::Thumbnail => stream_keys.thumbnail_key,
+                    _ => stream_keys.preview_key,
+                }
+            }
+        };
+        let key = key.ok_or(ErrType::NotFound.msg("Requested file not found"))?;
+
+        storage.get_object(&space_id.to_string(), &key, range.as_deref(), if_none_match.as_deref()).await
+    }
+
+    async fn get_album_archive(
+        &self,
+        SpaceCtx {
+            space_id,
+            ..
+        }: SpaceCtx,
+        album_id: Uuid,
+    ) -> AppResult<(String, Vec<ArchiveEntry>)> {
+        let album = self.ds.get_album(&space_id, &album_id).await?.ok_or(ErrType::NotFound.msg("Album not found"))?;
+
+        let files = self.ds.list_album_archive_files(&space_id, &album_id).await?;
+        if files.is_empty() {
+            return Err(ErrType::NotFound.msg("Album has no files"));
+        }
+
+        Ok((sanitize_file_name(album.name), get_archive_entries(files)))
+    }
+
+    async fn get_files_archive(
+        &self,
+        SpaceCtx {
+            space_id,
+            ..
+        }: SpaceCtx,
+        file_ids: Vec<Uuid>,
+    ) -> AppResult<(String, Vec<ArchiveEntry>)> {
+        let files = self.ds.list_archive_files(&space_id, &file_ids).await?;
+        if files.is_empty() {
+            return Err(ErrType::NotFound.msg("Requested files not found"));
+        }
+
+        Ok((format!("somarift-{}", chrono::Utc::now().format("%Y%m%d-%H%M%S")), get_archive_entries(files)))
+    }
+
     async fn delete_album(
         &self,
         SpaceCtx {
             role,
             space_id,
+            actor,
             ..
         }: SpaceCtx,
         album_id: Uuid,
@@ -417,13 +937,18 @@ impl<D: StorageDs> MediaService for ServiceWrapper<'_, D> {
             _ => (),
         };
 
-        let _ = self
-            .ds
-            .get_album(&space_id, &album_id)
-            .await?
-            .ok_or(ErrType::NotFound.msg("Album not found for deletion"))?;
+        let tx = self.ds.begin().await?;
+
+        let result = async {
+            if !tx.trash_album(&space_id, &album_id, &Utc::now()).await? {
+                return Err(ErrType::NotFound.msg("Album not found for deletion"));
+            }
 
-        self.ds.delete_album(&space_id, &album_id).await
+            audit(&tx, space_id, &actor, AuditAction::DeleteAlbum, Some(album_id), &[]).await
+        }
+        .await;
+
+        tx.finish(result).await
     }
 
     async fn delete_file(
@@ -431,27 +956,135 @@ impl<D: StorageDs> MediaService for ServiceWrapper<'_, D> {
         SpaceCtx {
             role,
             space_id,
+            actor,
             ..
         }: SpaceCtx,
-        storage: &Storage,
         file_id: Uuid,
     ) -> AppResult<()> {
-        match role {
-            SpaceRole::Read | SpaceRole::Upload => {
-                return Err(ErrType::Unauthorized.msg("Cannot delete: Unauthorized read|upload role"))
+        if let SpaceRole::Read = role {
+            return Err(ErrType::Unauthorized.msg("Cannot delete: Unauthorized read role"));
+        }
+
+        let (_, forbidden) = partition_owned_files(self.ds, &actor.user_id, role, &space_id, &[file_id], false).await?;
+        if !forbidden.is_empty() {
+            return Err(ErrType::Unauthorized.msg("Cannot delete: Uploaders only delete their own files"));
+        }
+
+        let tx = self.ds.begin().await?;
+
+        let result = async {
+            if !tx.trash_file(&space_id, &file_id, &Utc::now()).await? {
+                return Err(ErrType::NotFound.msg("File not found for deletion"));
             }
-            _ => (),
-        };
 
-        if let Some(file) = self.ds.get_file(space_id, file_id).await? {
-            storage.delete_file(&space_id.to_string(), file.object_key, file.thumbnail_key, file.preview_key).await?;
+            audit(&tx, space_id, &actor, AuditAction::DeleteFiles, None, &[file_id]).await
+        }
+        .await;
 
-            self.ds.delete_file(&file.id, &space_id).await?;
+        tx.finish(result).await
+    }
 
-            return Ok(());
+    async fn delete_files(
+        &self,
+        SpaceCtx {
+            role,
+            space_id,
+            actor,
+            ..
+        }: SpaceCtx,
+        file_ids: Vec<Uuid>,
+    ) -> AppResult<BulkFilesResponse> {
+        if let SpaceRole::Read = role {
+            return Err(ErrType::Unauthorized.msg("Cannot delete: Unauthorized read role"));
         }
 
-        Err(ErrType::NotFound.msg("File not found for deletion"))
+        let (permitted, forbidden) =
+            partition_owned_files(self.ds, &actor.user_id, role, &space_id, &file_ids, false).await?;
+
+        let tx = self.ds.begin().await?;
+
+        let result = async {
+            let trashed = tx.trash_files(&space_id, &permitted, &Utc::now()).await?;
+            audit(&tx, space_id, &actor, AuditAction::DeleteFiles, None, &trashed).await?;
+
+            Ok(trashed)
+        }
+        .await;
+
+        let trashed = tx.finish(result).await?;
+
+        Ok(bulk_response(&file_ids, &trashed, &forbidden))
+    }
+}
+
+impl<D: StorageDs + TransactionDs + AuditDs> ServiceWrapper<'_, D> {
+    /// Creates or revives the file row of a verified upload and queues it for processing
+    async fn queue_upload(
+        &self,
+        storage: &Storage,
+        interconnect: &ServiceInterconnect,
+        PendingUpload {
+            user_id,
+            space_id,
+            actor,
+            file_name,
+            hash,
+            updated_date,
+        }: PendingUpload,
+        object_key: String,
+    ) -> AppResult<()> {
+        let tx = self.ds.begin().await?;
+
+        let result = async {
+            let file = tx
+                .get_or_create_file(
+                    &user_id,
+                    &space_id,
+                    &hash,
+                    file_name,
+                    object_key,
+                    updated_date,
+                    FileData {
+                        file_name: String::new(),
+                        thumbnail: ImageData::default(),
+                        preview: ImageData::default(),
+                        metadata: MediaMetadata::default(),
+                        size: 0,
+                        media_type: smq_dto::MediaType::Image,
+                    },
+                )
+                .await?;
+            audit(&tx, space_id, &actor, AuditAction::UploadFile, None, &[file.id]).await?;
+
+            Ok(file)
+        }
+        .await;
+
+        let file = tx.finish(result).await?;
+        let remote_path = storage.get_remote_path(&space_id.to_string(), &file.object_key)?;
+
+        let payload_token = interconnect.get_sending_token()?;
+        let mq_url = interconnect.mq_uri("/v1/queue");
+
+        let response = request_mq_retry_until_ok(
+            &mq_url,
+            &payload_token,
+            ProcessMediaRequest {
+                file_id: file.id,
+                updated_date: MediaDatetime(updated_date),
+                space_id,
+                s3_file_path: remote_path,
+            },
+        )
+        .await?;
+
+        let status = response.status();
+        if status.is_success() {
+            Ok(())
+        } else {
+            Err(ErrType::ServerError
+                .msg(format!("Unable to queue media for processing: {:?}", status.canonical_reason())))
+        }
     }
 }
 
@@ -479,7 +1112,73 @@ async fn request_mq_retry_until_ok(
     }
 }
 
-fn sanitize_file_name(file_name: String) -> String {
+/// Maps files to archive entries named after `file_name`
+///
+/// Duplicate names get a counter before the extension: `a.jpg`, `a (1).jpg`.
+/// [`ARCHIVE_MANIFEST`] is taken up front so files never collide with the manifest.
+fn get_archive_entries(files: Vec<ArchiveFile>) -> Vec<ArchiveEntry> {
+    let mut taken = HashSet::with_capacity(files.len() + 1);
+    taken.insert(ARCHIVE_MANIFEST.to_lowercase());
+    files
+        .into_iter()
+        .map(|file| {
+            let name = Some(sanitize_file_name(file.file_name))
+                .filter(|name| !name.is_empty())
+                .unwrap_or_else(|| file.id.to_string());
+
+            let mut unique = name.clone();
+            let mut count = 0;
+            while !taken.insert(unique.to_lowercase()) {
+                count += 1;
+                unique = match name.rsplit_once('.') {
+                    Some((stem, ext)) if !stem.is_empty() => format!("{stem} ({count}).{ext}"),
+                    _ => format!("{name} ({count})"),
+                };
+            }
+
+            ArchiveEntry {
+                name: unique,
+                path: file.object_key,
+            }
+        })
+        .collect()
+}
+
+/// Resolves the cover thumbnail of the album, albums without thumbnailed files have no cover URL
+async fn album_view(storage: &Storage, space_id: &str, user_id: &Uuid, details: AlbumDetails) -> AppResult<AlbumView> {
+    let cover_thumbnail_url = match (details.cover_file_id, &details.cover_thumbnail_key) {
+        (Some(file_id), Some(key)) => {
+            Some(get_media_url(storage, space_id, user_id, file_id, MediaVariant::Thumbnail, key).await?)
+        }
+        _ => None,
+    };
+
+    Ok(AlbumView {
+        details,
+        cover_thumbnail_url,
+    })
+}
+
+/// Presigned storage URL or backend proxy URL depending on [`config::get_media_delivery`]
+///
+/// Proxy URLs are bound to `user_id` requesting them
+async fn get_media_url(
+    storage: &Storage,
+    space_id: &str,
+    user_id: &Uuid,
+    file_id: Uuid,
+    variant: MediaVariant,
+    key: &str,
+) -> AppResult<String> {
+    match config::get_media_delivery() {
+        MediaDelivery::Presign => storage.generate_stream_signed_url(space_id, key).await,
+        MediaDelivery::Proxy => {
+            storage.generate_content_signed_url(space_id, &user_id.to_string(), &file_id.to_string(), variant.as_str())
+        }
+    }
+}
+
+pub(super) fn sanitize_file_name(file_name: String) -> String {
     Path::new(&file_name)
         .file_name()
         .and_then(|n| n.to_str())
@@ -488,10 +1187,46 @@ fn sanitize_file_name(file_name: String) -> String {
         .unwrap_or(file_name)
 }
 
-fn get_canonical_object_key(hash: &str, file_name: &str) -> String {
+fn gallery_filter(query: GalleryFilterQuery) -> AppResult<GalleryFilter> {
+    let bounds = match (query.min_lat, query.max_lat, query.min_lon, query.max_lon) {
+        (None, None, None, None) => None,
+        (Some(min_lat), Some(max_lat), Some(min_lon), Some(max_lon)) => {
+            Some(geo_bounds(min_lat, max_lat, min_lon, max_lon)?)
+        }
+        _ => return Err(ErrType::BadRequest.msg("Bounding box requires min_lat, max_lat, min_lon and max_lon")),
+    };
+
+    if let (Some(after), Some(before)) = (query.taken_after, query.taken_before)
+        && after >= before
+    {
+        return Err(ErrType::BadRequest.msg("taken_after must be before taken_before"));
+    }
+
+    Ok(GalleryFilter {
+        taken_after: query.taken_after,
+        taken_before: query.taken_before,
+        media_type: query.media_type,
+        make: query.make,
+        model: query.model,
+        uploader: query.uploader,
+        orientation: query.orientation,
+        has_location: query.has_location,
+        bounds,
+        tag_id: query.tag_id,
+    })
+}
+
+pub(super) fn get_canonical_object_key(hash: &str, file_name: &str) -> String {
     format!("space/{}_{}", hash, file_name)
 }
 
+/// Key uploads are written to, promoted to the canonical key once their hash is verified
+///
+/// Scoped by uploader so a bad upload never touches objects of existing files.
+pub(super) fn get_staging_object_key(user_id: &Uuid, hash: &str, file_name: &str) -> String {
+    format!("staging/{}/{}_{}", user_id, hash, file_name)
+}
+
 fn join_key_dir(object_key: &str, file_name: &str) -> String {
     if let Some(parent) = Path::new(object_key).parent().and_then(|p| p.to_str())
         && !parent.is_empty()
@@ -502,3 +1237,112 @@ fn join_key_dir(object_key: &str, file_name: &str) -> String {
 
     file_name.to_owned()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn archive_names(file_names: &[&str]) -> Vec<String> {
+        let files = file_names
+            .iter()
+            .map(|file_name| ArchiveFile {
+                id: Uuid::nil(),
+                file_name: file_name.to_string(),
+                object_key: format!("space/{file_name}"),
+            })
+            .collect();
+        get_archive_entries(files).into_iter().map(|entry| entry.name).collect()
+    }
+
+    #[test]
+    fn archive_names_are_kept_when_unique() {
+        assert_eq!(archive_names(&["a.jpg", "b.jpg"]), ["a.jpg", "b.jpg"]);
+    }
+
+    #[test]
+    fn archive_duplicates_are_numbered_before_extension() {
+        assert_eq!(archive_names(&["a.jpg", "a.jpg", "a.jpg"]), ["a.jpg", "a (1).jpg", "a (2).jpg"]);
+        assert_eq!(archive_names(&["notes", "notes"]), ["notes", "notes (1)"]);
+        assert_eq!(archive_names(&[".env", ".env"]), [".env", ".env (1)"]);
+    }
+
+    #[test]
+    fn archive_duplicates_ignore_case() {
+        assert_eq!(archive_names(&["A.JPG", "a.jpg"]), ["A.JPG", "a (1).jpg"]);
+    }
+
+    #[test]
+    fn archive_numbering_skips_taken_names() {
+        assert_eq!(archive_names(&["a (1).jpg", "a.jpg", "a.jpg"]), ["a (1).jpg", "a.jpg", "a (2).jpg"]);
+    }
+
+    #[test]
+    fn archive_names_drop_directories() {
+        assert_eq!(archive_names(&["../../etc/passwd", "dir/a.jpg"]), ["passwd", "a.jpg"]);
+    }
+
+    #[test]
+    fn archive_names_avoid_manifest() {
+        let names = archive_names(&[ARCHIVE_MANIFEST, &ARCHIVE_MANIFEST.to_uppercase()]);
+        assert!(names.iter().all(|name| !name.eq_ignore_ascii_case(ARCHIVE_MANIFEST)));
+        assert_ne!(names[0].to_lowercase(), names[1].to_lowercase());
+    }
+
+    #[test]
+    fn archive_names_fall_back_to_id() {
+        assert_eq!(archive_names(&[""]), [Uuid::nil().to_string()]);
+    }
+
+    fn filter(query: serde_json::Value) -> AppResult<GalleryFilter> {
+        gallery_filter(serde_json::from_value(query).unwrap())
+    }
+
+    #[test]
+    fn gallery_filter_defaults_to_everything() {
+        let filter = filter(serde_json::json!({})).unwrap();
+        assert!(filter.bounds.is_none());
+        assert!(filter.taken_after.is_none() && filter.taken_before.is_none());
+    }
+
+    #[test]
+    fn gallery_filter_requires_full_bounding_box() {
+        let bounds = filter(serde_json::json!({"min_lat": 10.0, "max_lat": 20.0, "min_lon": 30.0, "max_lon": 40.0}))
+            .unwrap()
+            .bounds
+            .unwrap();
+        assert_eq!((bounds.min_lat, bounds.max_lat, bounds.min_lon, bounds.max_lon), (10.0, 20.0, 30.0, 40.0));
+
+        assert!(filter(serde_json::json!({"min_lat": 10.0, "max_lat": 20.0, "min_lon": 30.0})).is_err());
+        assert!(filter(serde_json::json!({"max_lon": 40.0})).is_err());
+    }
+
+    #[test]
+    fn gallery_filter_validates_bounding_box() {
+        assert!(
+            filter(serde_json::json!({"min_lat": 20.0, "max_lat": 10.0, "min_lon": 30.0, "max_lon": 40.0})).is_err()
+        );
+        assert!(
+            filter(serde_json::json!({"min_lat": -91.0, "max_lat": 10.0, "min_lon": 30.0, "max_lon": 40.0})).is_err()
+        );
+        assert!(
+            filter(serde_json::json!({"min_lat": 10.0, "max_lat": 20.0, "min_lon": 170.0, "max_lon": -170.0})).is_ok()
+        );
+    }
+
+    #[test]
+    fn gallery_filter_requires_ordered_taken_range() {
+        assert!(filter(
+            serde_json::json!({"taken_after": "2024-01-01T00:00:00Z", "taken_before": "2024-02-01T00:00:00Z"})
+        )
+        .is_ok());
+        assert!(filter(
+            serde_json::json!({"taken_after": "2024-02-01T00:00:00Z", "taken_before": "2024-01-01T00:00:00Z"})
+        )
+        .is_err());
+        assert!(filter(
+            serde_json::json!({"taken_after": "2024-01-01T00:00:00Z", "taken_before": "2024-01-01T00:00:00Z"})
+        )
+        .is_err());
+        assert!(filter(serde_json::json!({"taken_after": "2024-01-01T00:00:00Z"})).is_ok());
+    }
+}
@@ -1,12 +1,29 @@
 use crate::service::{
-    auth::AuthService, media::MediaService, space::SpaceService, user::UserService, user_space::UserSpaceService,
+    audit::AuditService, auth::AuthService, comment::CommentService, favorite::FavoriteService, geo::GeoService,
+    media::MediaService, reconcile::ReconcileService, search::SearchService, space::SpaceService, tag::TagService,
+    transfer::TransferService, trash::TrashService, upload::UploadService, usage::UsageService, user::UserService,
+    user_space::UserSpaceService,
 };
 
 use super::datastore::Datastore;
 
+pub mod audit;
 pub mod auth;
+mod bulk;
+pub mod comment;
+pub mod favorite;
+pub mod geo;
 pub mod media;
+mod pagination;
+pub mod reconcile;
+pub mod search;
 pub mod space;
+pub mod tag;
+pub mod transfer;
+pub mod trash;
+mod unit_of_work;
+pub mod upload;
+pub mod usage;
 pub mod user;
 pub mod user_space;
 
@@ -31,18 +48,84 @@ impl Service<Datastore> {
         &self.ds
     }
 
+    pub fn audit_service(&self) -> impl AuditService + Send + Sync {
+        ServiceWrapper {
+            ds: &self.ds,
+        }
+    }
+
     pub fn auth_service(&self) -> impl AuthService + Send + Sync {
         ServiceWrapper {
             ds: &self.ds,
         }
     }
 
+    pub fn comment_service(&self) -> impl CommentService + Send + Sync {
+        ServiceWrapper {
+            ds: &self.ds,
+        }
+    }
+
+    pub fn favorite_service(&self) -> impl FavoriteService + Send + Sync {
+        ServiceWrapper {
+            ds: &self.ds,
+        }
+    }
+
+    pub fn geo_service(&self) -> impl GeoService + Send + Sync {
+        ServiceWrapper {
+            ds: &self.ds,
+        }
+    }
+
     pub fn media_service(&self) -> impl MediaService + Send + Sync {
         ServiceWrapper {
             ds: &self.ds,
         }
     }
 
+    pub fn reconcile_service(&self) -> impl ReconcileService + Send + Sync {
+        ServiceWrapper {
+            ds: &self.ds,
+        }
+    }
+
+    pub fn search_service(&self) -> impl SearchService + Send + Sync {
+        ServiceWrapper {
+            ds: &self.ds,
+        }
+    }
+
+    pub fn tag_service(&self) -> impl TagService + Send + Sync {
+        ServiceWrapper {
+            ds: &self.ds,
+        }
+    }
+
+    pub fn trash_service(&self) -> impl TrashService + Send + Sync {
+        ServiceWrapper {
+            ds: &self.ds,
+        }
+    }
+
+    pub fn transfer_service(&self) -> impl TransferService + Send + Sync {
+        ServiceWrapper {
+            ds: &self.ds,
+        }
+    }
+
+    pub fn upload_service(&self) -> impl UploadService + Send + Sync {
+        ServiceWrapper {
+            ds: &self.ds,
+        }
+    }
+
+    pub fn usage_service(&self) -> impl UsageService + Send + Sync {
+        ServiceWrapper {
+            ds: &self.ds,
+        }
+    }
+
     pub fn user_service(&self) -> impl UserService + Send + Sync {
         ServiceWrapper {
             ds: &self.ds,
@@ -0,0 +1,152 @@
+use base64::Engine;
+use lib_core::{AppResult, ErrType};
+use serde::{de::DeserializeOwned, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    datastore::Keyset,
+    dto::{Page, PageQuery},
+};
+
+const DEFAULT_PAGE_SIZE: i64 = 100;
+const MAX_PAGE_SIZE: i64 = 500;
+
+/// Keyset and page size requested by `query`
+pub(super) fn page_params<K: DeserializeOwned>(query: PageQuery) -> AppResult<(Option<Keyset<K>>, i64)> {
+    let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+
+    let after = match query.cursor {
+        Some(cursor) => Some(decode_cursor(&cursor)?),
+        None => None,
+    };
+
+    Ok((after, limit))
+}
+
+fn decode_cursor<K: DeserializeOwned>(cursor: &str) -> AppResult<Keyset<K>> {
+    let bytes = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(cursor)
+        .map_err(|err| ErrType::BadRequest.err(err, "Invalid cursor"))?;
+    let (key, id) =
+        serde_json::from_slice::<(K, Uuid)>(&bytes).map_err(|err| ErrType::BadRequest.err(err, "Invalid cursor"))?;
+
+    Ok(Keyset {
+        key,
+        id,
+    })
+}
+
+/// Page of at most `limit` items out of the `limit + 1` fetched, with a cursor if more remain
+pub(super) fn into_page<T, R, K: Serialize>(
+    mut items: Vec<T>,
+    limit: i64,
+    keyset: impl Fn(&T) -> Keyset<K>,
+    map: impl Fn(T) -> R,
+) -> AppResult<Page<R>> {
+    let mut next_cursor = None;
+    if items.len() as i64 > limit {
+        items.truncate(limit as usize);
+
+        if let Some(last) = items.last() {
+            let Keyset {
+                key,
+                id,
+            } = keyset(last);
+            let bytes = serde_json::to_vec(&(key, id))
+                .map_err(|err| ErrType::ServerError.err(err, "Failed to serialize cursor"))?;
+            next_cursor = Some(base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes));
+        }
+    }
+
+    Ok(Page {
+        items: items.into_iter().map(map).collect(),
+        next_cursor,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Utc};
+
+    use super::*;
+
+    fn query(cursor: Option<String>, limit: Option<i64>) -> PageQuery {
+        PageQuery {
+            cursor,
+            limit,
+        }
+    }
+
+    fn page_of(count: usize, limit: i64) -> Page<Uuid> {
+        let ids = (0..count).map(|_| Uuid::now_v7()).collect::<Vec<_>>();
+        into_page(
+            ids,
+            limit,
+            |id| Keyset {
+                key: id.to_string(),
+                id: *id,
+            },
+            |id| id,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn cursor_round_trips() {
+        let page = page_of(3, 2);
+        assert_eq!(page.items.len(), 2);
+
+        let (after, _) = page_params::<String>(query(page.next_cursor, None)).unwrap();
+        let after = after.unwrap();
+        assert_eq!(after.id, page.items[1]);
+        assert_eq!(after.key, page.items[1].to_string());
+    }
+
+    #[test]
+    fn cursor_round_trips_timestamp_key() {
+        let taken_at = DateTime::from_timestamp(1_700_000_000, 123_456_000).unwrap();
+        let id = Uuid::now_v7();
+        let page = into_page(
+            vec![taken_at, taken_at],
+            1,
+            |_| Keyset {
+                key: taken_at,
+                id,
+            },
+            |item| item,
+        )
+        .unwrap();
+
+        let (after, _) = page_params::<DateTime<Utc>>(query(page.next_cursor, None)).unwrap();
+        let after = after.unwrap();
+        assert_eq!(after.key, taken_at);
+        assert_eq!(after.id, id);
+    }
+
+    #[test]
+    fn last_page_has_no_cursor() {
+        assert!(page_of(2, 2).next_cursor.is_none());
+        assert!(page_of(0, 2).next_cursor.is_none());
+        assert!(page_of(3, 2).next_cursor.is_some());
+    }
+
+    #[test]
+    fn limit_is_clamped() {
+        assert_eq!(page_params::<String>(query(None, None)).unwrap().1, DEFAULT_PAGE_SIZE);
+        assert_eq!(page_params::<String>(query(None, Some(0))).unwrap().1, 1);
+        assert_eq!(page_params::<String>(query(None, Some(-5))).unwrap().1, 1);
+        assert_eq!(page_params::<String>(query(None, Some(10_000))).unwrap().1, MAX_PAGE_SIZE);
+    }
+
+    #[test]
+    fn invalid_cursor_is_rejected() {
+        assert!(page_params::<String>(query(Some("not base64!".to_owned()), None)).is_err());
+
+        let not_json = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(b"garbage");
+        assert!(page_params::<String>(query(Some(not_json), None)).is_err());
+
+        let wrong_key = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .encode(serde_json::to_vec(&("not a timestamp", Uuid::nil())).unwrap());
+        assert!(page_params::<DateTime<Utc>>(query(Some(wrong_key), None)).is_err());
+    }
+}
@@ -0,0 +1,233 @@
+use std::collections::HashMap;
+
+use chrono::{TimeDelta, Utc};
+use lib_core::{
+    storage::{Storage, FOLDER_MARKER},
+    AppResult, ErrType, ErrorContext,
+};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::{
+    datastore::{
+        audit::{AuditAction, AuditDs},
+        job::{JobDs, JobKind, JobStatus},
+        storage::StorageDs,
+        transaction::TransactionDs,
+        user_space::SpaceRole,
+    },
+    dto::job::res::_JobResponse,
+    extension::{SpaceCtx, UserId},
+};
+
+use super::{audit::audit, ServiceWrapper};
+
+/// Orphan keys and broken files listed in the report, counts are always complete
+const MAX_REPORTED_ENTRIES: usize = 1000;
+
+pub trait ReconcileService: Send + Sync {
+    /// Queues reconciliation of the space, run later by [`ReconcileService::reconcile_space`]
+    fn start_reconcile(
+        &self,
+        user_id: UserId,
+        space_ctx: SpaceCtx,
+    ) -> impl Future<Output = AppResult<_JobResponse>> + Send;
+
+    /// Cross-checks space objects with `media_files`, reporting on the job
+    ///
+    /// Objects without row older than `grace` are orphans, rows with missing keys are broken.
+    /// With `enforce` orphans are deleted and broken rows flagged, otherwise only reported.
+    fn reconcile_space(
+        &self,
+        job_id: Uuid,
+        space_id: Uuid,
+        storage: &Storage,
+        enforce: bool,
+        grace: std::time::Duration,
+    ) -> impl Future<Output = AppResult<()>> + Send;
+
+    /// Fails reconciliations left pending or running by a restart, returns number of failed jobs
+    ///
+    /// The enforce mode is not stored on the job, so they are not resumed
+    fn fail_interrupted_reconciles(&self) -> impl Future<Output = AppResult<usize>> + Send;
+}
+
+#[derive(Serialize)]
+struct BrokenFile {
+    id: Uuid,
+    missing_keys: Vec<String>,
+}
+
+#[derive(Serialize, Default)]
+struct ReconcileReport {
+    enforce: bool,
+    scanned_objects: i64,
+    recent_objects: i64,
+    orphaned_objects: i64,
+    orphaned_bytes: i64,
+    deleted_objects: i64,
+    broken_files: i64,
+    orphans: Vec<String>,
+    broken: Vec<BrokenFile>,
+}
+
+impl<D: StorageDs + JobDs + TransactionDs + AuditDs> ReconcileService for ServiceWrapper<'_, D> {
+    async fn start_reconcile(
+        &self,
+        UserId(user_id): UserId,
+        SpaceCtx {
+            role,
+            space_id,
+            actor,
+            ..
+        }: SpaceCtx,
+    ) -> AppResult<_JobResponse> {
+        match role {
+            SpaceRole::Owner | SpaceRole::DefaultSpace => (),
+            _ => return Err(ErrType::Unauthorized.msg("Cannot reconcile space: Unauthorized non owner role")),
+        };
+
+        let tx = self.ds.begin().await?;
+
+        let result = async {
+            let job = tx.insert_job(&user_id, &space_id, JobKind::Reconcile).await?;
+            audit(&tx, space_id, &actor, AuditAction::ReconcileSpace, Some(job.id), &[]).await?;
+
+            Ok(_JobResponse(job))
+        }
+        .await;
+
+        tx.finish(result).await
+    }
+
+    async fn reconcile_space(
+        &self,
+        job_id: Uuid,
+        space_id: Uuid,
+        storage: &Storage,
+        enforce: bool,
+        grace: std::time::Duration,
+    ) -> AppResult<()> {
+        let space_id_str = space_id.to_string();
+        let started_at = Utc::now();
+
+        let mut report = ReconcileReport {
+            enforce,
+            ..Default::default()
+        };
+
+        let result = async {
+            let grace =
+                TimeDelta::from_std(grace).map_err(|err| ErrType::ServerError.err(err, "Invalid grace period"))?;
+            let orphan_before = started_at - grace;
+
+            // every row protects its keys, objects of rows added during the scan are kept by the grace period
+            let files = self.ds.list_space_media_keys(&space_id).await?;
+            let folder_marker = storage.get_remote_path(&space_id_str, FOLDER_MARKER)?;
+
+            let mut seen = HashMap::new();
+            let mut file_keys = Vec::with_capacity(files.len());
+            for file in files {
+                let mut keys = Vec::with_capacity(3);
+                for key in [Some(file.object_key), file.thumbnail_key, file.preview_key].into_iter().flatten() {
+                    let remote_key = storage.get_remote_path(&space_id_str, &key)?;
+                    seen.insert(remote_key.clone(), false);
+                    keys.push((key, remote_key));
+                }
+                file_keys.push((file.id, keys));
+            }
+
+            let mut continuation_token = None;
+            loop {
+                let page = storage.list_space_objects(&space_id_str, continuation_token).await?;
+
+                let mut orphans = Vec::new();
+                for object in page.objects {
+                    report.scanned_objects += 1;
+
+                    if let Some(found) = seen.get_mut(&object.key) {
+                        *found = true;
+                        continue;
+                    }
+
+                    if object.key == folder_marker {
+                        continue;
+                    }
+
+                    if object.last_modified.is_some_and(|modified| modified > orphan_before) {
+                        report.recent_objects += 1;
+                        continue;
+                    }
+
+                    report.orphaned_objects += 1;
+                    report.orphaned_bytes += object.size;
+                    if report.orphans.len() < MAX_REPORTED_ENTRIES {
+                        report.orphans.push(object.key.clone());
+                    }
+                    orphans.push(object.key);
+                }
+
+                if enforce && !orphans.is_empty() {
+                    report.deleted_objects += storage.delete_keys(orphans).await? as i64;
+                }
+
+                let progress = serde_json::to_value(&report)
+                    .map_err(|err| ErrType::ServerError.err(err, "Failed to serialize reconcile report"))?;
+                self.ds.update_job_progress(&job_id, JobStatus::Running, report.scanned_objects, &progress).await?;
+
+                continuation_token = page.continuation_token;
+                if continuation_token.is_none() {
+                    break;
+                }
+            }
+
+            let mut broken_ids = Vec::new();
+            for (file_id, keys) in file_keys {
+                let missing_keys = keys
+                    .into_iter()
+                    .filter(|(_, remote_key)| !seen.get(remote_key).copied().unwrap_or_default())
+                    .map(|(key, _)| key)
+                    .collect::<Vec<_>>();
+                if missing_keys.is_empty() {
+                    continue;
+                }
+
+                report.broken_files += 1;
+                if report.broken.len() < MAX_REPORTED_ENTRIES {
+                    report.broken.push(BrokenFile {
+                        id: file_id,
+                        missing_keys,
+                    });
+                }
+                broken_ids.push(file_id);
+            }
+
+            if enforce {
+                self.ds.set_broken_files(&space_id, &broken_ids, &started_at).await?;
+            }
+
+            serde_json::to_value(&report)
+                .map_err(|err| ErrType::ServerError.err(err, "Failed to serialize reconcile report"))
+        }
+        .await
+        .context("s:reconcile_space");
+
+        match result {
+            Ok(progress) => {
+                self.ds.update_job_progress(&job_id, JobStatus::Completed, report.scanned_objects, &progress).await
+            }
+            Err(err) => {
+                self.ds.fail_job(&job_id, err.to_string()).await?;
+                Err(err)
+            }
+        }
+    }
+
+    async fn fail_interrupted_reconciles(&self) -> AppResult<usize> {
+        let jobs = self.ds.list_jobs_by_status(JobKind::Reconcile, &[JobStatus::Pending, JobStatus::Running]).await?;
+        for job in jobs.iter() {
+            self.ds.fail_job(&job.id, "Interrupted by restart".to_owned()).await?;
+        }
+        Ok(jobs.len())
+    }
+}
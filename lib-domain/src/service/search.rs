@@ -0,0 +1,61 @@
+use lib_core::{AppResult, ErrType};
+
+use crate::{
+    datastore::{search::SearchDs, Keyset},
+    dto::{
+        search::{req::SearchQuery, res::_SearchFileResponse},
+        Page, PageQuery,
+    },
+    extension::{SpaceCtx, UserId},
+};
+
+use super::{
+    pagination::{into_page, page_params},
+    ServiceWrapper,
+};
+
+const MAX_QUERY_LEN: usize = 255;
+
+pub trait SearchService: Send + Sync {
+    /// Files of the space matching the query by name, camera metadata or album name, best ranked first
+    fn search_files(
+        &self,
+        user_id: UserId,
+        space_ctx: SpaceCtx,
+        query: SearchQuery,
+        page: PageQuery,
+    ) -> impl Future<Output = AppResult<Page<_SearchFileResponse>>> + Send;
+}
+
+impl<D: SearchDs> SearchService for ServiceWrapper<'_, D> {
+    async fn search_files(
+        &self,
+        UserId(user_id): UserId,
+        SpaceCtx {
+            space_id,
+            ..
+        }: SpaceCtx,
+        SearchQuery {
+            q,
+        }: SearchQuery,
+        page: PageQuery,
+    ) -> AppResult<Page<_SearchFileResponse>> {
+        let q = q.trim();
+        if q.is_empty() || q.len() > MAX_QUERY_LEN {
+            return Err(ErrType::BadRequest.msg(format!("Search query must be 1 to {MAX_QUERY_LEN} characters")));
+        }
+
+        let (after, limit) = page_params(page)?;
+        let files = self.ds.search_files(&space_id, &user_id, q, after, limit + 1).await?;
+
+        into_page(
+            files,
+            limit,
+            |file| Keyset {
+                key: file.rank,
+                id: file.file.id,
+            },
+            _SearchFileResponse,
+        )
+    }
+}
@@ -2,12 +2,24 @@ use lib_core::{storage::Storage, AppResult, ErrType, ErrorContext};
 use uuid::Uuid;
 
 use crate::{
-    datastore::{space::SpaceDs, storage::StorageDs, user::UserDs, user_space::UserSpaceDs},
-    dto::space::{req::SpaceCreateRequest, res::_SpaceResponse},
-    extension::UserId,
+    datastore::{
+        audit::{AuditAction, AuditDs},
+        job::{JobDs, JobKind, JobStatus},
+        space::SpaceDs,
+        storage::StorageDs,
+        transaction::TransactionDs,
+        upload_session::UploadSessionDs,
+        user::UserDs,
+        user_space::{SpaceRole, UserSpaceDs},
+    },
+    dto::{
+        job::res::_JobResponse,
+        space::{req::SpaceCreateRequest, res::_SpaceResponse},
+    },
+    extension::{SpaceCtx, UserId},
 };
 
-use super::ServiceWrapper;
+use super::{audit::audit, media::get_staging_object_key, unit_of_work::UnitOfWork, ServiceWrapper};
 
 pub trait SpaceService: Send + Sync {
     fn create_user_space(
@@ -22,26 +34,60 @@ pub trait SpaceService: Send + Sync {
         user_id: Uuid,
         storage: &Storage,
     ) -> impl Future<Output = AppResult<_SpaceResponse>> + Send;
+
+    /// Deletes space rows, storage is purged later by [`SpaceService::purge_space`]
+    ///
+    /// In-flight multipart uploads are aborted along with their sessions.
+    /// The audit entry outlives the space, but with no members left it is only readable from `audit_log` directly.
+    fn delete_space(
+        &self,
+        user_id: UserId,
+        space_ctx: SpaceCtx,
+        storage: &Storage,
+    ) -> impl Future<Output = AppResult<_JobResponse>> + Send;
+
+    /// Deletes every object of the space page by page, reporting progress on the job
+    fn purge_space(
+        &self,
+        job_id: Uuid,
+        space_id: Uuid,
+        storage: &Storage,
+    ) -> impl Future<Output = AppResult<()>> + Send;
+
+    fn get_job(&self, user_id: UserId, job_id: Uuid) -> impl Future<Output = AppResult<_JobResponse>> + Send;
+
+    /// Space purges left pending, running or failed, to be resumed on startup
+    ///
+    /// The space row is gone, so a failed purge is only ever finished by a retry
+    fn list_unfinished_purges(&self) -> impl Future<Output = AppResult<Vec<_JobResponse>>> + Send;
 }
 
-impl<D: UserDs + UserSpaceDs + SpaceDs + StorageDs> SpaceService for ServiceWrapper<'_, D> {
+impl<D: UserDs + UserSpaceDs + SpaceDs + StorageDs + JobDs + UploadSessionDs + TransactionDs + AuditDs> SpaceService
+    for ServiceWrapper<'_, D>
+{
     async fn create_user_space(
         &self,
         UserId(user_id): UserId,
         storage: &Storage,
         dto: SpaceCreateRequest,
     ) -> AppResult<_SpaceResponse> {
-        let space = self.ds.insert_space(&dto.name, &dto.description).await.context("s:create_user_space")?;
+        let uow = UnitOfWork::begin(self.ds).await?;
 
-        let member = self
-            .ds
-            .add_user_to_space(&user_id, &space.id, crate::datastore::user_space::SpaceRole::Owner)
-            .await
-            .context("s:create_user_space")?;
+        let result = async {
+            let space = uow.ds().insert_space(&dto.name, &dto.description).await?;
+
+            let member = uow.ds().add_user_to_space(&user_id, &space.id, SpaceRole::Owner).await?;
 
-        storage.create_space_folder(&member.space_id.to_string()).await.context("s:create_user_space")?;
+            let space_id = member.space_id.to_string();
+            storage.create_space_folder(&space_id).await?;
+            uow.compensate(async move { storage.delete_space_folder(&space_id).await });
 
-        Ok(_SpaceResponse(space))
+            Ok(_SpaceResponse(space))
+        }
+        .await
+        .context("s:create_user_space");
+
+        uow.finish(result).await
     }
 
     async fn get_or_setup_default_space(&self, user_id: Uuid, storage: &Storage) -> AppResult<_SpaceResponse> {
@@ -55,10 +101,112 @@ impl<D: UserDs + UserSpaceDs + SpaceDs + StorageDs> SpaceService for ServiceWrap
             return Ok(_SpaceResponse(space));
         }
 
-        let space = self.ds.set_default_space(&user_id).await?;
+        let uow = UnitOfWork::begin(self.ds).await?;
+
+        let result = async {
+            let space = uow.ds().set_default_space(&user_id).await?;
+
+            let space_id = space.id.to_string();
+            storage.create_space_folder(&space_id).await?;
+            uow.compensate(async move { storage.delete_space_folder(&space_id).await });
+
+            Ok(_SpaceResponse(space))
+        }
+        .await
+        .context("setting up default space");
+
+        uow.finish(result).await
+    }
+
+    async fn delete_space(
+        &self,
+        UserId(user_id): UserId,
+        SpaceCtx {
+            role,
+            space_id,
+            actor,
+            ..
+        }: SpaceCtx,
+        storage: &Storage,
+    ) -> AppResult<_JobResponse> {
+        match role {
+            SpaceRole::Owner | SpaceRole::DefaultSpace => (),
+            _ => return Err(ErrType::Unauthorized.msg("Cannot delete space: Unauthorized non owner role")),
+        };
+
+        let tx = self.ds.begin().await?;
+
+        let result = async {
+            // sessions opened meanwhile fail the space delete on their fk instead of leaking parts
+            let sessions = tx.delete_space_upload_sessions(&space_id).await?;
+            for session in sessions {
+                let object_key = get_staging_object_key(&session.user_id, &session.hash, &session.file_name);
+                storage.abort_multipart_upload(&space_id.to_string(), &object_key, &session.upload_id).await?;
+            }
+
+            tx.delete_space(&space_id).await?;
+
+            let job = tx.insert_job(&user_id, &space_id, JobKind::SpacePurge).await?;
+            audit(&tx, space_id, &actor, AuditAction::DeleteSpace, Some(job.id), &[]).await?;
+
+            Ok(_JobResponse(job))
+        }
+        .await
+        .context("s:delete_space");
+
+        tx.finish(result).await
+    }
+
+    async fn purge_space(&self, job_id: Uuid, space_id: Uuid, storage: &Storage) -> AppResult<()> {
+        let space_id_str = space_id.to_string();
+
+        let mut deleted = 0i64;
+        let mut pages = 0i64;
+        let mut continuation_token = None;
+
+        let result = async {
+            loop {
+                let page = storage.list_space_objects(&space_id_str, continuation_token).await?;
+                let keys = page.objects.into_iter().map(|obj| obj.key).collect::<Vec<_>>();
+
+                deleted += storage.delete_keys(keys).await? as i64;
+                pages += 1;
+
+                let report = serde_json::json!({ "deleted_objects": deleted, "pages": pages });
+                self.ds.update_job_progress(&job_id, JobStatus::Running, deleted, &report).await?;
+
+                continuation_token = page.continuation_token;
+                if continuation_token.is_none() {
+                    break;
+                }
+            }
+
+            storage.delete_space_folder(&space_id_str).await
+        }
+        .await
+        .context("s:purge_space");
+
+        match result {
+            Ok(()) => {
+                let report = serde_json::json!({ "deleted_objects": deleted, "pages": pages });
+                self.ds.update_job_progress(&job_id, JobStatus::Completed, deleted, &report).await
+            }
+            Err(err) => {
+                self.ds.fail_job(&job_id, err.to_string()).await?;
+                Err(err)
+            }
+        }
+    }
 
-        storage.create_space_folder(&space.id.to_string()).await.context("setting up default space")?;
+    async fn get_job(&self, UserId(user_id): UserId, job_id: Uuid) -> AppResult<_JobResponse> {
+        self.ds.get_job(&user_id, &job_id).await?.ok_or(ErrType::NotFound.msg("Job not found")).map(_JobResponse)
+    }
 
-        Ok(_SpaceResponse(space))
+    async fn list_unfinished_purges(&self) -> AppResult<Vec<_JobResponse>> {
+        let jobs = self
+            .ds
+            .list_jobs_by_status(JobKind::SpacePurge, &[JobStatus::Pending, JobStatus::Running, JobStatus::Failed])
+            .await?;
+        Ok(jobs.into_iter().map(_JobResponse).collect())
     }
 }
@@ -0,0 +1,337 @@
+use std::collections::HashSet;
+
+use lib_core::{AppResult, ErrType};
+use uuid::Uuid;
+
+use crate::{
+    datastore::{
+        audit::{AuditAction, AuditDs},
+        storage::{GalleryFilter, StorageDs},
+        tag::TagDs,
+        transaction::TransactionDs,
+        user_space::SpaceRole,
+        Keyset,
+    },
+    dto::{
+        cloud::res::{_FileMetaResponse, BulkFilesResponse},
+        tag::{
+            req::{CreateTagRequest, MergeTagRequest, UpdateTagRequest},
+            res::{_TagCountResponseVec, _TagResponse},
+        },
+        Page, PageQuery,
+    },
+    extension::{SpaceCtx, UserId},
+};
+
+use super::{
+    audit::audit,
+    bulk::bulk_response,
+    pagination::{into_page, page_params},
+    ServiceWrapper,
+};
+
+pub trait TagService: Send + Sync {
+    fn create_tag(
+        &self,
+        space_ctx: SpaceCtx,
+        dto: CreateTagRequest,
+    ) -> impl Future<Output = AppResult<_TagResponse>> + Send;
+
+    /// Tags of the space by name with their file counts
+    fn list_tags(&self, space_ctx: SpaceCtx) -> impl Future<Output = AppResult<_TagCountResponseVec>> + Send;
+
+    fn update_tag(
+        &self,
+        space_ctx: SpaceCtx,
+        tag_id: Uuid,
+        dto: UpdateTagRequest,
+    ) -> impl Future<Output = AppResult<_TagResponse>> + Send;
+
+    /// Moves files of the tag to `dto.target_tag_id` and deletes the tag
+    fn merge_tag(
+        &self,
+        space_ctx: SpaceCtx,
+        tag_id: Uuid,
+        dto: MergeTagRequest,
+    ) -> impl Future<Output = AppResult<_TagResponse>> + Send;
+
+    /// Deletes tag, files stay in the gallery
+    fn delete_tag(&self, space_ctx: SpaceCtx, tag_id: Uuid) -> impl Future<Output = AppResult<()>> + Send;
+
+    fn link_tag_files(
+        &self,
+        space_ctx: SpaceCtx,
+        tag_id: Uuid,
+        file_ids: Vec<Uuid>,
+    ) -> impl Future<Output = AppResult<BulkFilesResponse>> + Send;
+
+    fn unlink_tag_files(
+        &self,
+        space_ctx: SpaceCtx,
+        tag_id: Uuid,
+        file_ids: Vec<Uuid>,
+    ) -> impl Future<Output = AppResult<BulkFilesResponse>> + Send;
+
+    /// Files of the tag latest taken first
+    fn list_tag_files(
+        &self,
+        user_id: UserId,
+        space_ctx: SpaceCtx,
+        tag_id: Uuid,
+        page: PageQuery,
+    ) -> impl Future<Output = AppResult<Page<_FileMetaResponse>>> + Send;
+}
+
+impl<D: TagDs + StorageDs + TransactionDs + AuditDs> TagService for ServiceWrapper<'_, D> {
+    async fn create_tag(
+        &self,
+        SpaceCtx {
+            role,
+            space_id,
+            actor,
+            ..
+        }: SpaceCtx,
+        CreateTagRequest {
+            name,
+        }: CreateTagRequest,
+    ) -> AppResult<_TagResponse> {
+        if let SpaceRole::Read = role {
+            return Err(ErrType::Unauthorized.msg("Cannot create tag: Unauthorized read role"));
+        }
+
+        let name = tag_name(&name)?;
+        let tx = self.ds.begin().await?;
+
+        let result = async {
+            let tag = tx.create_tag(&space_id, name).await?;
+            audit(&tx, space_id, &actor, AuditAction::CreateTag, Some(tag.id), &[]).await?;
+
+            Ok(_TagResponse(tag))
+        }
+        .await;
+
+        tx.finish(result).await
+    }
+
+    async fn list_tags(
+        &self,
+        SpaceCtx {
+            space_id,
+            ..
+        }: SpaceCtx,
+    ) -> AppResult<_TagCountResponseVec> {
+        self.ds.list_tags(&space_id).await.map(_TagCountResponseVec)
+    }
+
+    async fn update_tag(
+        &self,
+        SpaceCtx {
+            role,
+            space_id,
+            actor,
+            ..
+        }: SpaceCtx,
+        tag_id: Uuid,
+        UpdateTagRequest {
+            name,
+        }: UpdateTagRequest,
+    ) -> AppResult<_TagResponse> {
+        match role {
+            SpaceRole::Read | SpaceRole::Upload => {
+                return Err(ErrType::Unauthorized.msg("Cannot update tag: Unauthorized read|upload role"))
+            }
+            _ => (),
+        };
+
+        let name = tag_name(&name)?;
+        let tx = self.ds.begin().await?;
+
+        let result = async {
+            let tag = tx.rename_tag(&space_id, &tag_id, name).await?.ok_or(ErrType::NotFound.msg("Tag not found"))?;
+            audit(&tx, space_id, &actor, AuditAction::UpdateTag, Some(tag_id), &[]).await?;
+
+            Ok(_TagResponse(tag))
+        }
+        .await;
+
+        tx.finish(result).await
+    }
+
+    async fn merge_tag(
+        &self,
+        SpaceCtx {
+            role,
+            space_id,
+            actor,
+            ..
+        }: SpaceCtx,
+        tag_id: Uuid,
+        MergeTagRequest {
+            target_tag_id,
+        }: MergeTagRequest,
+    ) -> AppResult<_TagResponse> {
+        match role {
+            SpaceRole::Read | SpaceRole::Upload => {
+                return Err(ErrType::Unauthorized.msg("Cannot merge tags: Unauthorized read|upload role"))
+            }
+            _ => (),
+        };
+
+        if target_tag_id == tag_id {
+            return Err(ErrType::BadRequest.msg("Target tag must differ from source tag"));
+        }
+
+        let target =
+            self.ds.get_tag(&space_id, &target_tag_id).await?.ok_or(ErrType::NotFound.msg("Target tag not found"))?;
+
+        let tx = self.ds.begin().await?;
+
+        let result = async {
+            if !tx.merge_tags(&space_id, &tag_id, &target_tag_id).await? {
+                return Err(ErrType::NotFound.msg("Tag not found"));
+            }
+
+            audit(&tx, space_id, &actor, AuditAction::MergeTag, Some(tag_id), &[]).await
+        }
+        .await;
+
+        tx.finish(result).await?;
+        Ok(_TagResponse(target))
+    }
+
+    async fn delete_tag(
+        &self,
+        SpaceCtx {
+            role,
+            space_id,
+            actor,
+            ..
+        }: SpaceCtx,
+        tag_id: Uuid,
+    ) -> AppResult<()> {
+        match role {
+            SpaceRole::Read | SpaceRole::Upload => {
+                return Err(ErrType::Unauthorized.msg("Cannot delete: Unauthorized read|upload role"))
+            }
+            _ => (),
+        };
+
+        let tx = self.ds.begin().await?;
+
+        let result = async {
+            if !tx.delete_tag(&space_id, &tag_id).await? {
+                return Err(ErrType::NotFound.msg("Tag not found for deletion"));
+            }
+
+            audit(&tx, space_id, &actor, AuditAction::DeleteTag, Some(tag_id), &[]).await
+        }
+        .await;
+
+        tx.finish(result).await
+    }
+
+    async fn link_tag_files(
+        &self,
+        SpaceCtx {
+            role,
+            space_id,
+            actor,
+            ..
+        }: SpaceCtx,
+        tag_id: Uuid,
+        file_ids: Vec<Uuid>,
+    ) -> AppResult<BulkFilesResponse> {
+        if let SpaceRole::Read = role {
+            return Err(ErrType::Unauthorized.msg("Cannot tag files: Unauthorized read role"));
+        }
+
+        let _ = self.ds.get_tag(&space_id, &tag_id).await?.ok_or(ErrType::NotFound.msg("Tag not found"))?;
+
+        let tx = self.ds.begin().await?;
+
+        let result = async {
+            let linked = tx.link_tag_files(&space_id, &tag_id, &file_ids).await?;
+            audit(&tx, space_id, &actor, AuditAction::TagFiles, Some(tag_id), &linked).await?;
+
+            Ok(linked)
+        }
+        .await;
+
+        let linked = tx.finish(result).await?;
+
+        Ok(bulk_response(&file_ids, &linked, &HashSet::new()))
+    }
+
+    async fn unlink_tag_files(
+        &self,
+        SpaceCtx {
+            role,
+            space_id,
+            actor,
+            ..
+        }: SpaceCtx,
+        tag_id: Uuid,
+        file_ids: Vec<Uuid>,
+    ) -> AppResult<BulkFilesResponse> {
+        if let SpaceRole::Read = role {
+            return Err(ErrType::Unauthorized.msg("Cannot untag files: Unauthorized read role"));
+        }
+
+        let _ = self.ds.get_tag(&space_id, &tag_id).await?.ok_or(ErrType::NotFound.msg("Tag not found"))?;
+
+        let tx = self.ds.begin().await?;
+
+        let result = async {
+            let unlinked = tx.unlink_tag_files(&space_id, &tag_id, &file_ids).await?;
+            audit(&tx, space_id, &actor, AuditAction::UntagFiles, Some(tag_id), &unlinked).await?;
+
+            Ok(unlinked)
+        }
+        .await;
+
+        let unlinked = tx.finish(result).await?;
+
+        Ok(bulk_response(&file_ids, &unlinked, &HashSet::new()))
+    }
+
+    async fn list_tag_files(
+        &self,
+        UserId(user_id): UserId,
+        SpaceCtx {
+            space_id,
+            ..
+        }: SpaceCtx,
+        tag_id: Uuid,
+        page: PageQuery,
+    ) -> AppResult<Page<_FileMetaResponse>> {
+        let _ = self.ds.get_tag(&space_id, &tag_id).await?.ok_or(ErrType::NotFound.msg("Tag not found"))?;
+
+        let filter = GalleryFilter {
+            tag_id: Some(tag_id),
+            ..Default::default()
+        };
+
+        let (after, limit) = page_params(page)?;
+        let files = self.ds.list_files_gallery(&space_id, &user_id, &filter, after, limit + 1).await?;
+
+        into_page(
+            files,
+            limit,
+            |file| Keyset {
+                key: file.taken_at,
+                id: file.id,
+            },
+            _FileMetaResponse,
+        )
+    }
+}
+
+/// Trimmed tag name, blank names are rejected
+fn tag_name(name: &str) -> AppResult<&str> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(ErrType::BadRequest.msg("Tag name cannot be blank"));
+    }
+
+    Ok(name)
+}
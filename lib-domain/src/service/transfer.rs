@@ -0,0 +1,248 @@
+use chrono::Utc;
+use lib_core::{smq_dto::MediaType, storage::Storage, AppResult, ErrType, ErrorContext};
+use uuid::Uuid;
+
+use crate::{
+    datastore::{
+        audit::{AuditAction, AuditDs},
+        space::SpaceDs,
+        storage::{MediaFile, StorageDs},
+        transaction::TransactionDs,
+        trash::TrashDs,
+        usage::UsageDs,
+        user_space::{SpaceRole, UserSpaceDs},
+    },
+    dto::cloud::{
+        req::TransferFilesRequest,
+        res::{TransferFilesResponse, TransferredFileResponse},
+    },
+    extension::{SpaceCtx, UserId},
+};
+
+use super::{audit::audit, unit_of_work::UnitOfWork, usage::reserve_space_quota, ServiceWrapper};
+
+/// Files per transfer, all of them are copied within one transaction
+pub const MAX_TRANSFER_FILES: u64 = 100;
+
+pub enum TransferMode {
+    /// Source files are kept
+    Copy,
+
+    /// Source files are moved to trash once copied, keeping their tags, favorites and comments
+    Move,
+}
+
+pub trait TransferService: Send + Sync {
+    /// Copies or moves files of the space into `target_space_id`
+    ///
+    /// Files whose hash already exists in the target are reused instead of copied.
+    /// Album memberships are not carried over, files are linked to `target_album_id` when set.
+    /// Files are transferred all or none, a failed file rolls back the ones before it.
+    fn transfer_files(
+        &self,
+        user_id: UserId,
+        space_ctx: SpaceCtx,
+        storage: &Storage,
+        dto: TransferFilesRequest,
+        mode: TransferMode,
+    ) -> impl Future<Output = AppResult<TransferFilesResponse>> + Send;
+}
+
+impl<D: StorageDs + SpaceDs + UserSpaceDs + UsageDs + TrashDs + TransactionDs + AuditDs> TransferService
+    for ServiceWrapper<'_, D>
+{
+    async fn transfer_files(
+        &self,
+        UserId(user_id): UserId,
+        SpaceCtx {
+            role,
+            space_id,
+            actor,
+            ..
+        }: SpaceCtx,
+        storage: &Storage,
+        TransferFilesRequest {
+            target_space_id,
+            file_ids,
+            target_album_id,
+        }: TransferFilesRequest,
+        mode: TransferMode,
+    ) -> AppResult<TransferFilesResponse> {
+        if target_space_id == space_id {
+            return Err(ErrType::BadRequest.msg("Target space must differ from source space"));
+        }
+        if file_ids.len() as u64 > MAX_TRANSFER_FILES {
+            return Err(
+                ErrType::BadRequest.msg(format!("Cannot transfer more than {MAX_TRANSFER_FILES} files at once"))
+            );
+        }
+
+        if let TransferMode::Move = mode
+            && let SpaceRole::Read | SpaceRole::Upload = role
+        {
+            return Err(ErrType::Unauthorized.msg("Cannot move files: Unauthorized read|upload role"));
+        }
+
+        let target_role = match self.ds.get_default_space(&user_id).await? {
+            Some(space) if space.id == target_space_id => SpaceRole::DefaultSpace,
+            _ => {
+                self.ds
+                    .get_user_space(&user_id, &target_space_id)
+                    .await?
+                    .ok_or(ErrType::Unauthorized.msg("User not member of target space"))?
+                    .role
+            }
+        };
+        if let SpaceRole::Read = target_role {
+            return Err(ErrType::Unauthorized.msg("Cannot transfer files: Unauthorized read role in target space"));
+        }
+
+        if let Some(album_id) = target_album_id {
+            let _ = self
+                .ds
+                .get_album(&target_space_id, &album_id)
+                .await?
+                .ok_or(ErrType::NotFound.msg("Target album not found"))?;
+        }
+
+        let mut files = Vec::with_capacity(file_ids.len());
+        for file_id in file_ids {
+            let file = self
+                .ds
+                .get_file(space_id, file_id)
+                .await?
+                .filter(|file| file.deleted_at.is_none())
+                .ok_or(ErrType::NotFound.msg(format!("File {file_id} not found")))?;
+            files.push(file);
+        }
+
+        // quota is reserved up front, each reservation commits on its own so the space usage lock
+        // is not held while objects are copied
+        let mut reserved = Vec::new();
+        for file in &files {
+            if self.ds.get_file_by_hash(&target_space_id, &file.hash).await?.is_some() {
+                continue;
+            }
+            let result =
+                reserve_space_quota(self.ds, &target_space_id, &user_id, &file.hash, Some(file.node_size)).await;
+            if let Err(err) = result {
+                release_reservations(self.ds, &target_space_id, &user_id, &reserved).await;
+                return Err(err).context("s:transfer_files");
+            }
+            reserved.push(file.hash.as_str());
+        }
+
+        let source_ids: Vec<Uuid> = files.iter().map(|file| file.id).collect();
+        let deleted_at = Utc::now();
+
+        // one unit of work, so a failed file rolls back the whole transfer with its audit entries
+        let uow = UnitOfWork::begin(self.ds).await?;
+
+        let result = async {
+            let mut transferred = Vec::with_capacity(files.len());
+            let mut target_ids = Vec::with_capacity(files.len());
+            for file in &files {
+                let (target, deduplicated) = match uow.ds().get_file_by_hash(&target_space_id, &file.hash).await? {
+                    Some(existing) => {
+                        if existing.deleted_at.is_some() {
+                            uow.ds().restore_file(&target_space_id, &existing.id).await?;
+                        }
+                        (existing, true)
+                    }
+                    None => copy_file(self.ds, &uow, storage, file, &user_id, &target_space_id).await?,
+                };
+                target_ids.push(target.id);
+
+                transferred.push(TransferredFileResponse {
+                    source_id: file.id.to_string(),
+                    target_id: target.id.to_string(),
+                    deduplicated,
+                });
+
+                if let TransferMode::Move = mode {
+                    uow.ds().trash_file(&space_id, &file.id, &deleted_at).await?;
+                }
+            }
+
+            if let Some(album_id) = target_album_id {
+                uow.ds().link_album_files(&target_space_id, &album_id, &target_ids).await?;
+            }
+
+            // Both spaces record the transfer, each pointing at the other space
+            let action = match mode {
+                TransferMode::Copy => AuditAction::CopyFiles,
+                TransferMode::Move => AuditAction::MoveFiles,
+            };
+            audit(uow.ds(), space_id, &actor, action, Some(target_space_id), &source_ids).await?;
+            audit(uow.ds(), target_space_id, &actor, action, Some(space_id), &target_ids).await?;
+
+            Ok(TransferFilesResponse {
+                files: transferred,
+            })
+        }
+        .await
+        .context("s:transfer_files");
+
+        let result = uow.finish(result).await;
+        if result.is_err() {
+            release_reservations(self.ds, &target_space_id, &user_id, &reserved).await;
+        }
+
+        result
+    }
+}
+
+/// Releases quota reserved for a transfer that did not complete, reservations also lapse with the upload ttl
+async fn release_reservations<D: UsageDs>(ds: &D, space_id: &Uuid, user_id: &Uuid, hashes: &[&str]) {
+    for hash in hashes {
+        if let Err(err) = ds.release_usage_reservation(space_id, user_id, hash).await {
+            tracing::warn!(space_id = %space_id, "Failed to release transfer reservation: {err}");
+        }
+    }
+}
+
+/// Copies file objects and row into the target space within `uow`, charging usage to `user_id`
+///
+/// Quota must already be reserved for the file. Returns the target file and whether it was
+/// deduplicated against a concurrent copy. Each copied object is deleted again if the unit of work
+/// rolls back, unless a file of the same hash added concurrently now owns it.
+async fn copy_file<'a, D: StorageDs + UsageDs + TransactionDs>(
+    ds: &'a D,
+    uow: &UnitOfWork<'a, D>,
+    storage: &'a Storage,
+    file: &MediaFile,
+    user_id: &Uuid,
+    target_space_id: &'a Uuid,
+) -> AppResult<(MediaFile, bool)> {
+    let (source, target) = (file.space_id.to_string(), target_space_id.to_string());
+    for key in [Some(&file.object_key), file.thumbnail_key.as_ref(), file.preview_key.as_ref()].into_iter().flatten() {
+        storage.copy_to_space(&source, &target, key).await.context("s:copy_file")?;
+
+        let (target, key, hash) = (target.clone(), key.clone(), file.hash.clone());
+        uow.compensate(async move {
+            if ds.get_file_by_hash(target_space_id, &hash).await?.is_some() {
+                return Ok(());
+            }
+            storage.delete_file(&target, key, None, None).await
+        });
+    }
+
+    let Some(copied) = uow.ds().copy_file(&file.id, &file.space_id, user_id, target_space_id).await? else {
+        // same hash was copied concurrently, the objects now belong to that file
+        let existing = uow
+            .ds()
+            .get_file_by_hash(target_space_id, &file.hash)
+            .await?
+            .ok_or(ErrType::BadRequest.msg("File with same hash was added to target space concurrently"))?;
+        uow.ds().release_usage_reservation(target_space_id, user_id, &file.hash).await?;
+        return Ok((existing, true));
+    };
+
+    if copied.node_size > 0 {
+        let media_type = copied.metadata.media_type.unwrap_or(MediaType::Image);
+        uow.ds().add_usage(target_space_id, user_id, media_type, copied.node_size, 1).await?;
+    }
+    uow.ds().release_usage_reservation(target_space_id, user_id, &copied.hash).await?;
+
+    Ok((copied, false))
+}
@@ -0,0 +1,309 @@
+use chrono::{TimeDelta, Utc};
+use lib_core::{smq_dto::MediaType, storage::Storage, AppResult, ErrType};
+use uuid::Uuid;
+
+use crate::{
+    datastore::{
+        audit::{AuditAction, AuditDs},
+        storage::{MediaFile, StorageDs},
+        transaction::TransactionDs,
+        trash::TrashDs,
+        usage::UsageDs,
+        user_space::SpaceRole,
+    },
+    dto::{
+        cloud::res::BulkFilesResponse,
+        trash::res::{_TrashedAlbumResponseVec, _TrashedFileResponseVec},
+    },
+    extension::SpaceCtx,
+};
+
+use super::{
+    audit::audit,
+    bulk::{bulk_response, partition_owned_files},
+    ServiceWrapper,
+};
+
+/// Expired files purged per batch by [`TrashService::purge_expired_trash`]
+const PURGE_BATCH_SIZE: i64 = 500;
+
+pub trait TrashService: Send + Sync {
+    fn list_trashed_files(
+        &self,
+        space_ctx: SpaceCtx,
+    ) -> impl Future<Output = AppResult<_TrashedFileResponseVec>> + Send;
+    fn list_trashed_albums(
+        &self,
+        space_ctx: SpaceCtx,
+    ) -> impl Future<Output = AppResult<_TrashedAlbumResponseVec>> + Send;
+
+    /// Restores trashed file, uploaders only restore files they uploaded
+    fn restore_file(&self, space_ctx: SpaceCtx, file_id: Uuid) -> impl Future<Output = AppResult<()>> + Send;
+    fn restore_album(&self, space_ctx: SpaceCtx, album_id: Uuid) -> impl Future<Output = AppResult<()>> + Send;
+
+    /// Restores trashed files, with the roles of [`Self::restore_file`]
+    ///
+    /// Files uploaded by someone else are reported forbidden instead of failing the request.
+    fn restore_files(
+        &self,
+        space_ctx: SpaceCtx,
+        file_ids: Vec<Uuid>,
+    ) -> impl Future<Output = AppResult<BulkFilesResponse>> + Send;
+
+    /// Permanently deletes trashed files and albums of the space
+    fn empty_trash(&self, space_ctx: SpaceCtx, storage: &Storage) -> impl Future<Output = AppResult<()>> + Send;
+
+    /// Permanently deletes files and albums trashed longer than `retention`, returns purged files
+    fn purge_expired_trash(
+        &self,
+        storage: &Storage,
+        retention: std::time::Duration,
+    ) -> impl Future<Output = AppResult<usize>> + Send;
+}
+
+impl<D: StorageDs + UsageDs + TrashDs + TransactionDs + AuditDs> TrashService for ServiceWrapper<'_, D> {
+    async fn list_trashed_files(
+        &self,
+        SpaceCtx {
+            space_id,
+            ..
+        }: SpaceCtx,
+    ) -> AppResult<_TrashedFileResponseVec> {
+        self.ds.list_trashed_files(&space_id).await.map(_TrashedFileResponseVec)
+    }
+
+    async fn list_trashed_albums(
+        &self,
+        SpaceCtx {
+            space_id,
+            ..
+        }: SpaceCtx,
+    ) -> AppResult<_TrashedAlbumResponseVec> {
+        self.ds.list_trashed_albums(&space_id).await.map(_TrashedAlbumResponseVec)
+    }
+
+    async fn restore_file(
+        &self,
+        SpaceCtx {
+            role,
+            space_id,
+            actor,
+            ..
+        }: SpaceCtx,
+        file_id: Uuid,
+    ) -> AppResult<()> {
+        if let SpaceRole::Read = role {
+            return Err(ErrType::Unauthorized.msg("Cannot restore: Unauthorized read role"));
+        }
+
+        let (_, forbidden) = partition_owned_files(self.ds, &actor.user_id, role, &space_id, &[file_id], true).await?;
+        if !forbidden.is_empty() {
+            return Err(ErrType::Unauthorized.msg("Cannot restore: Uploaders only restore their own files"));
+        }
+
+        let tx = self.ds.begin().await?;
+
+        let result = async {
+            if !tx.restore_file(&space_id, &file_id).await? {
+                return Err(ErrType::NotFound.msg("File not found in trash"));
+            }
+
+            audit(&tx, space_id, &actor, AuditAction::RestoreFiles, None, &[file_id]).await
+        }
+        .await;
+
+        tx.finish(result).await
+    }
+
+    async fn restore_album(
+        &self,
+        SpaceCtx {
+            role,
+            space_id,
+            actor,
+            ..
+        }: SpaceCtx,
+        album_id: Uuid,
+    ) -> AppResult<()> {
+        ensure_trash_role(role, "Cannot restore")?;
+
+        let tx = self.ds.begin().await?;
+
+        let result = async {
+            if !tx.restore_album(&space_id, &album_id).await? {
+                return Err(ErrType::NotFound.msg("Album not found in trash"));
+            }
+
+            audit(&tx, space_id, &actor, AuditAction::RestoreAlbum, Some(album_id), &[]).await
+        }
+        .await;
+
+        tx.finish(result).await
+    }
+
+    async fn restore_files(
+        &self,
+        SpaceCtx {
+            role,
+            space_id,
+            actor,
+            ..
+        }: SpaceCtx,
+        file_ids: Vec<Uuid>,
+    ) -> AppResult<BulkFilesResponse> {
+        if let SpaceRole::Read = role {
+            return Err(ErrType::Unauthorized.msg("Cannot restore: Unauthorized read role"));
+        }
+
+        let (permitted, forbidden) =
+            partition_owned_files(self.ds, &actor.user_id, role, &space_id, &file_ids, true).await?;
+
+        let tx = self.ds.begin().await?;
+
+        let result = async {
+            let restored = tx.restore_files(&space_id, &permitted).await?;
+            audit(&tx, space_id, &actor, AuditAction::RestoreFiles, None, &restored).await?;
+
+            Ok(restored)
+        }
+        .await;
+
+        let restored = tx.finish(result).await?;
+
+        Ok(bulk_response(&file_ids, &restored, &forbidden))
+    }
+
+    async fn empty_trash(
+        &self,
+        SpaceCtx {
+            role,
+            space_id,
+            actor,
+            ..
+        }: SpaceCtx,
+        storage: &Storage,
+    ) -> AppResult<()> {
+        ensure_trash_role(role, "Cannot empty trash")?;
+
+        let files = self.ds.list_trashed_files(&space_id).await?;
+        let tx = self.ds.begin().await?;
+
+        let result = async {
+            let mut purged = Vec::with_capacity(files.len());
+            for file in files {
+                if delete_file_row(&tx, &file).await? {
+                    purged.push(file);
+                }
+            }
+            tx.delete_trashed_albums(&space_id).await?;
+
+            let purged_ids = purged.iter().map(|file| file.id).collect::<Vec<_>>();
+            audit(&tx, space_id, &actor, AuditAction::EmptyTrash, None, &purged_ids).await?;
+
+            Ok(purged)
+        }
+        .await;
+
+        let purged = tx.finish(result).await?;
+
+        for file in purged {
+            delete_file_objects(storage, file).await;
+        }
+
+        Ok(())
+    }
+
+    async fn purge_expired_trash(&self, storage: &Storage, retention: std::time::Duration) -> AppResult<usize> {
+        let retention =
+            TimeDelta::from_std(retention).map_err(|err| ErrType::ServerError.err(err, "Invalid trash retention"))?;
+        let before = Utc::now() - retention;
+
+        let mut purged = 0;
+        loop {
+            let files = self.ds.list_expired_files(&before, PURGE_BATCH_SIZE).await?;
+            let size = files.len();
+
+            for file in files {
+                if purge_file(self.ds, storage, file).await? {
+                    purged += 1;
+                }
+            }
+
+            if (size as i64) < PURGE_BATCH_SIZE {
+                break;
+            }
+        }
+        self.ds.delete_expired_albums(&before).await?;
+
+        Ok(purged)
+    }
+}
+
+/// Only owners and modifiers manage trash
+fn ensure_trash_role(role: SpaceRole, action: &str) -> AppResult<()> {
+    match role {
+        SpaceRole::Owner | SpaceRole::Modify | SpaceRole::DefaultSpace => Ok(()),
+        SpaceRole::Read | SpaceRole::Upload => {
+            Err(ErrType::Unauthorized.msg(format!("{action}: Unauthorized read|upload role")))
+        }
+    }
+}
+
+/// File stored under the hash, rejected while in trash so an upload does not revive it
+///
+/// Trashed files are restored or wait for the purge, which would otherwise race the upload.
+pub(super) fn ensure_not_trashed(file: Option<MediaFile>) -> AppResult<Option<MediaFile>> {
+    match file {
+        Some(file) if file.deleted_at.is_some() => {
+            Err(ErrType::BadRequest.msg("File is in trash, restore it instead of uploading it again"))
+        }
+        file => Ok(file),
+    }
+}
+
+/// Deletes trashed file row releasing its usage, then its objects, `false` if no longer in trash
+///
+/// Objects left behind by a failed delete are collected by reconciliation.
+pub(super) async fn purge_file<D: StorageDs + UsageDs + TransactionDs>(
+    ds: &D,
+    storage: &Storage,
+    file: MediaFile,
+) -> AppResult<bool> {
+    let tx = ds.begin().await?;
+    let result = delete_file_row(&tx, &file).await;
+    let deleted = tx.finish(result).await?;
+
+    if deleted {
+        delete_file_objects(storage, file).await;
+    }
+
+    Ok(deleted)
+}
+
+/// Deletes trashed file row releasing its usage, run in the transaction of the caller
+///
+/// Usage is only released when the row was still in trash, a restored or already purged file is skipped.
+async fn delete_file_row<D: StorageDs + UsageDs>(ds: &D, file: &MediaFile) -> AppResult<bool> {
+    if !ds.delete_file(&file.id, &file.space_id).await? {
+        return Ok(false);
+    }
+
+    if file.node_size > 0 {
+        let media_type = file.metadata.media_type.unwrap_or(MediaType::Image);
+        ds.add_usage(&file.space_id, &file.user_id, media_type, -file.node_size, -1).await?;
+    }
+
+    Ok(true)
+}
+
+/// Deletes objects of a file once its row is gone
+///
+/// Failures are only logged, the row is already gone and reconciliation collects the objects.
+async fn delete_file_objects(storage: &Storage, file: MediaFile) {
+    let file_id = file.id;
+    let result =
+        storage.delete_file(&file.space_id.to_string(), file.object_key, file.thumbnail_key, file.preview_key).await;
+    if let Err(err) = result {
+        tracing::warn!(file_id = %file_id, "Failed to delete file objects: {err}");
+    }
+}
@@ -0,0 +1,47 @@
+use std::{pin::Pin, sync::Mutex};
+
+use lib_core::AppResult;
+
+use crate::datastore::transaction::TransactionDs;
+
+type Compensation<'a> = Pin<Box<dyn Future<Output = AppResult<()>> + Send + 'a>>;
+
+/// Datastore transaction with compensations undoing side effects outside the database
+pub(super) struct UnitOfWork<'a, D> {
+    ds: D,
+    compensations: Mutex<Vec<Compensation<'a>>>,
+}
+
+impl<'a, D: TransactionDs> UnitOfWork<'a, D> {
+    pub(super) async fn begin(ds: &D) -> AppResult<Self> {
+        Ok(Self {
+            ds: ds.begin().await?,
+            compensations: Mutex::new(Vec::new()),
+        })
+    }
+
+    pub(super) fn ds(&self) -> &D {
+        &self.ds
+    }
+
+    /// Registers `compensation` to run if the unit of work fails
+    pub(super) fn compensate(&self, compensation: impl Future<Output = AppResult<()>> + Send + 'a) {
+        self.compensations.lock().unwrap_or_else(|err| err.into_inner()).push(Box::pin(compensation));
+    }
+
+    /// Commits on `Ok`, otherwise rolls back and runs compensations in reverse order
+    pub(super) async fn finish<T: Send>(self, result: AppResult<T>) -> AppResult<T> {
+        let compensations = self.compensations.into_inner().unwrap_or_else(|err| err.into_inner());
+
+        let result = self.ds.finish(result).await;
+        if result.is_err() {
+            for compensation in compensations.into_iter().rev() {
+                if let Err(err) = compensation.await {
+                    tracing::warn!("Failed to compensate unit of work: {err}");
+                }
+            }
+        }
+
+        result
+    }
+}
@@ -0,0 +1,383 @@
+use std::time::Duration;
+
+use chrono::{TimeDelta, Utc};
+use lib_core::{
+    storage::{Storage, UploadedPart},
+    AppResult, ErrType,
+};
+use uuid::Uuid;
+
+use crate::{
+    datastore::{
+        space::SpaceDs,
+        storage::StorageDs,
+        transaction::TransactionDs,
+        upload_session::{UploadSession, UploadSessionDs, UploadSessionRecord},
+        usage::UsageDs,
+        user_space::SpaceRole,
+    },
+    dto::cloud::{
+        req::{CreateUploadSessionRequest, SignUploadPartsRequest},
+        res::{UploadPartUrlResponse, UploadPartUrlsResponse, UploadSessionResponse, UploadedPartResponse},
+    },
+    extension::{SpaceCtx, UserId},
+};
+
+use super::{
+    media::{get_staging_object_key, sanitize_file_name},
+    trash::ensure_not_trashed,
+    usage::reserve_space_quota,
+    ServiceWrapper,
+};
+
+/// Smallest part size, above the 5 MiB S3 minimum for every part but the last
+const MIN_PART_SIZE: i64 = 8 * 1024 * 1024;
+
+/// S3 limit on parts of a multipart upload
+const MAX_PARTS: i64 = 10_000;
+
+pub trait UploadService: Send + Sync {
+    fn create_upload_session(
+        &self,
+        user_id: UserId,
+        space_ctx: SpaceCtx,
+        storage: &Storage,
+        dto: CreateUploadSessionRequest,
+    ) -> impl Future<Output = AppResult<UploadSessionResponse>> + Send;
+
+    fn get_upload_session(
+        &self,
+        user_id: UserId,
+        space_ctx: SpaceCtx,
+        storage: &Storage,
+        session_id: Uuid,
+    ) -> impl Future<Output = AppResult<UploadSessionResponse>> + Send;
+
+    fn sign_upload_parts(
+        &self,
+        user_id: UserId,
+        space_ctx: SpaceCtx,
+        storage: &Storage,
+        session_id: Uuid,
+        dto: SignUploadPartsRequest,
+    ) -> impl Future<Output = AppResult<UploadPartUrlsResponse>> + Send;
+
+    fn complete_upload_session(
+        &self,
+        user_id: UserId,
+        space_ctx: SpaceCtx,
+        storage: &Storage,
+        session_id: Uuid,
+    ) -> impl Future<Output = AppResult<()>> + Send;
+
+    fn abort_upload_session(
+        &self,
+        user_id: UserId,
+        space_ctx: SpaceCtx,
+        storage: &Storage,
+        session_id: Uuid,
+    ) -> impl Future<Output = AppResult<()>> + Send;
+
+    /// Aborts sessions not updated within `ttl`, returns number of aborted sessions
+    fn abort_stale_upload_sessions(
+        &self,
+        storage: &Storage,
+        ttl: Duration,
+    ) -> impl Future<Output = AppResult<usize>> + Send;
+}
+
+impl<D: UploadSessionDs + StorageDs + SpaceDs + UsageDs + TransactionDs> UploadService for ServiceWrapper<'_, D> {
+    async fn create_upload_session(
+        &self,
+        UserId(user_id): UserId,
+        SpaceCtx {
+            role,
+            space_id,
+            ..
+        }: SpaceCtx,
+        storage: &Storage,
+        CreateUploadSessionRequest {
+            file_name,
+            hash,
+            file_size,
+        }: CreateUploadSessionRequest,
+    ) -> AppResult<UploadSessionResponse> {
+        if let SpaceRole::Read = role {
+            return Err(ErrType::Unauthorized.msg("Cannot upload: Unauthorized read role"));
+        }
+
+        if ensure_not_trashed(self.ds.get_file_by_hash(&space_id, &hash).await?)?.is_none() {
+            reserve_space_quota(self.ds, &space_id, &user_id, &hash, Some(file_size)).await?;
+        }
+
+        let file_name = sanitize_file_name(file_name);
+        let object_key = get_staging_object_key(&user_id, &hash, &file_name);
+        let part_size = get_part_size(file_size);
+
+        let upload_id = storage.create_multipart_upload(&space_id.to_string(), &object_key).await?;
+        let session = self
+            .ds
+            .insert_upload_session(UploadSessionRecord {
+                user_id,
+                space_id,
+                upload_id: &upload_id,
+                hash: &hash,
+                file_name: &file_name,
+                file_size,
+                part_size,
+            })
+            .await?;
+
+        Ok(session_response(session, Vec::new()))
+    }
+
+    async fn get_upload_session(
+        &self,
+        UserId(user_id): UserId,
+        SpaceCtx {
+            space_id,
+            ..
+        }: SpaceCtx,
+        storage: &Storage,
+        session_id: Uuid,
+    ) -> AppResult<UploadSessionResponse> {
+        let session = self.get_user_session(&user_id, &space_id, &session_id).await?;
+
+        let object_key = get_staging_object_key(&session.user_id, &session.hash, &session.file_name);
+        let parts = storage.list_uploaded_parts(&space_id.to_string(), &object_key, &session.upload_id).await?;
+
+        Ok(session_response(session, parts))
+    }
+
+    async fn sign_upload_parts(
+        &self,
+        UserId(user_id): UserId,
+        SpaceCtx {
+            space_id,
+            ..
+        }: SpaceCtx,
+        storage: &Storage,
+        session_id: Uuid,
+        SignUploadPartsRequest {
+            part_numbers,
+        }: SignUploadPartsRequest,
+    ) -> AppResult<UploadPartUrlsResponse> {
+        let session = self.get_user_session(&user_id, &space_id, &session_id).await?;
+
+        check_part_numbers(&part_numbers, get_part_count(session.file_size, session.part_size))?;
+
+        let object_key = get_staging_object_key(&session.user_id, &session.hash, &session.file_name);
+        let space_id_str = space_id.to_string();
+
+        let mut parts = Vec::with_capacity(part_numbers.len());
+        for part_number in part_numbers {
+            let url = storage
+                .generate_upload_part_signed_url(&space_id_str, &object_key, &session.upload_id, part_number)
+                .await?;
+            parts.push(UploadPartUrlResponse {
+                part_number,
+                url,
+            });
+        }
+
+        self.ds.touch_upload_session(&session.id).await?;
+
+        Ok(UploadPartUrlsResponse {
+            parts,
+        })
+    }
+
+    async fn complete_upload_session(
+        &self,
+        UserId(user_id): UserId,
+        SpaceCtx {
+            space_id,
+            ..
+        }: SpaceCtx,
+        storage: &Storage,
+        session_id: Uuid,
+    ) -> AppResult<()> {
+        let session = self.get_user_session(&user_id, &space_id, &session_id).await?;
+
+        let object_key = get_staging_object_key(&session.user_id, &session.hash, &session.file_name);
+        let space_id_str = space_id.to_string();
+
+        let parts = storage.list_uploaded_parts(&space_id_str, &object_key, &session.upload_id).await?;
+
+        check_uploaded_parts(&parts, session.file_size, session.part_size)?;
+
+        storage.complete_multipart_upload(&space_id_str, &object_key, &session.upload_id, parts).await?;
+
+        self.ds.delete_upload_session(&session.id).await
+    }
+
+    async fn abort_upload_session(
+        &self,
+        UserId(user_id): UserId,
+        SpaceCtx {
+            space_id,
+            ..
+        }: SpaceCtx,
+        storage: &Storage,
+        session_id: Uuid,
+    ) -> AppResult<()> {
+        let session = self.get_user_session(&user_id, &space_id, &session_id).await?;
+
+        let object_key = get_staging_object_key(&session.user_id, &session.hash, &session.file_name);
+        storage.abort_multipart_upload(&space_id.to_string(), &object_key, &session.upload_id).await?;
+        self.ds.release_usage_reservation(&space_id, &user_id, &session.hash).await?;
+
+        self.ds.delete_upload_session(&session.id).await
+    }
+
+    async fn abort_stale_upload_sessions(&self, storage: &Storage, ttl: Duration) -> AppResult<usize> {
+        let ttl =
+            TimeDelta::from_std(ttl).map_err(|err| ErrType::ServerError.err(err, "Invalid upload session ttl"))?;
+        let sessions = self.ds.list_stale_upload_sessions(Utc::now() - ttl).await?;
+
+        let mut aborted = 0;
+        for session in sessions {
+            let object_key = get_staging_object_key(&session.user_id, &session.hash, &session.file_name);
+            if let Err(err) =
+                storage.abort_multipart_upload(&session.space_id.to_string(), &object_key, &session.upload_id).await
+            {
+                tracing::warn!(session_id = %session.id, "Failed to abort stale upload session: {err}");
+                continue;
+            }
+
+            self.ds.delete_upload_session(&session.id).await?;
+            aborted += 1;
+        }
+
+        Ok(aborted)
+    }
+}
+
+impl<D: UploadSessionDs> ServiceWrapper<'_, D> {
+    /// Sessions are only visible to the user who created them
+    async fn get_user_session(&self, user_id: &Uuid, space_id: &Uuid, session_id: &Uuid) -> AppResult<UploadSession> {
+        self.ds
+            .get_upload_session(space_id, session_id)
+            .await?
+            .filter(|session| session.user_id == *user_id)
+            .ok_or(ErrType::NotFound.msg("Upload session not found"))
+    }
+}
+
+fn get_part_size(file_size: i64) -> i64 {
+    let part_size = (file_size + MAX_PARTS - 1) / MAX_PARTS;
+    part_size.max(MIN_PART_SIZE)
+}
+
+fn get_part_count(file_size: i64, part_size: i64) -> i32 {
+    ((file_size + part_size - 1) / part_size) as i32
+}
+
+/// Part numbers to sign must lie within the parts of the session
+fn check_part_numbers(part_numbers: &[i32], part_count: i32) -> AppResult<()> {
+    match part_numbers.iter().find(|&&n| n < 1 || n > part_count) {
+        Some(part_number) => {
+            Err(ErrType::BadRequest.msg(format!("Invalid part number {part_number}, expected 1..={part_count}")))
+        }
+        None => Ok(()),
+    }
+}
+
+/// Uploaded parts must cover every part of the session and add up to the declared size
+fn check_uploaded_parts(parts: &[UploadedPart], file_size: i64, part_size: i64) -> AppResult<()> {
+    let part_count = get_part_count(file_size, part_size);
+    let missing = (1..=part_count).filter(|n| !parts.iter().any(|p| p.part_number == *n)).count();
+    if missing > 0 {
+        return Err(ErrType::BadRequest.msg(format!("Cannot complete upload: {missing} parts missing")));
+    }
+
+    let uploaded_size: i64 = parts.iter().map(|p| p.size).sum();
+    if uploaded_size != file_size {
+        return Err(ErrType::BadRequest
+            .msg(format!("Cannot complete upload: uploaded {uploaded_size} bytes, expected {file_size}")));
+    }
+
+    Ok(())
+}
+
+fn session_response(session: UploadSession, parts: Vec<UploadedPart>) -> UploadSessionResponse {
+    UploadSessionResponse {
+        id: session.id.to_string(),
+        part_count: get_part_count(session.file_size, session.part_size),
+        file_name: session.file_name,
+        file_size: session.file_size,
+        part_size: session.part_size,
+        uploaded_parts: parts
+            .into_iter()
+            .map(|part| UploadedPartResponse {
+                part_number: part.part_number,
+                size: part.size,
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lib_core::storage::MAX_OBJECT_SIZE;
+
+    use super::*;
+
+    const MIB: i64 = 1024 * 1024;
+
+    fn parts(sizes: &[i64]) -> Vec<UploadedPart> {
+        sizes
+            .iter()
+            .enumerate()
+            .map(|(i, &size)| UploadedPart {
+                part_number: i as i32 + 1,
+                size,
+                e_tag: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn part_size_has_minimum() {
+        assert_eq!(get_part_size(1), MIN_PART_SIZE);
+        assert_eq!(get_part_size(MIN_PART_SIZE * MAX_PARTS), MIN_PART_SIZE);
+        assert_eq!(get_part_size(MIN_PART_SIZE * MAX_PARTS + 1), MIN_PART_SIZE + 1);
+    }
+
+    #[test]
+    fn part_count_stays_within_limit() {
+        for file_size in [1, MIN_PART_SIZE, MIN_PART_SIZE + 1, 100 * 1024 * MIB, MAX_OBJECT_SIZE] {
+            let part_count = get_part_count(file_size, get_part_size(file_size));
+            assert!((1..=MAX_PARTS as i32).contains(&part_count), "{file_size} bytes in {part_count} parts");
+        }
+    }
+
+    #[test]
+    fn part_count_rounds_up() {
+        assert_eq!(get_part_count(MIN_PART_SIZE, MIN_PART_SIZE), 1);
+        assert_eq!(get_part_count(MIN_PART_SIZE + 1, MIN_PART_SIZE), 2);
+        assert_eq!(get_part_count(1, MIN_PART_SIZE), 1);
+    }
+
+    #[test]
+    fn part_numbers_within_session() {
+        assert!(check_part_numbers(&[1, 2, 3], 3).is_ok());
+        assert!(check_part_numbers(&[0], 3).is_err());
+        assert!(check_part_numbers(&[4], 3).is_err());
+        assert!(check_part_numbers(&[-1], 3).is_err());
+    }
+
+    #[test]
+    fn uploaded_parts_complete() {
+        let file_size = 2 * MIN_PART_SIZE + 5;
+        assert!(check_uploaded_parts(&parts(&[MIN_PART_SIZE, MIN_PART_SIZE, 5]), file_size, MIN_PART_SIZE).is_ok());
+    }
+
+    #[test]
+    fn uploaded_parts_missing_or_wrong_size() {
+        let file_size = 2 * MIN_PART_SIZE + 5;
+        assert!(check_uploaded_parts(&parts(&[MIN_PART_SIZE, MIN_PART_SIZE]), file_size, MIN_PART_SIZE).is_err());
+        assert!(check_uploaded_parts(&parts(&[MIN_PART_SIZE, MIN_PART_SIZE, 4]), file_size, MIN_PART_SIZE).is_err());
+        assert!(check_uploaded_parts(&[], file_size, MIN_PART_SIZE).is_err());
+    }
+}
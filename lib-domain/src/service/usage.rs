@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+
+use chrono::{TimeDelta, Utc};
+use lib_core::{config, AppResult, ErrType};
+use uuid::Uuid;
+
+use crate::{
+    datastore::{
+        space::SpaceDs,
+        transaction::TransactionDs,
+        usage::{media_type_literal, UsageDs},
+    },
+    dto::usage::res::{MediaTypeUsageResponse, SpaceUsageResponse, UploaderUsageResponse},
+    extension::SpaceCtx,
+};
+
+use super::ServiceWrapper;
+
+pub trait UsageService: Send + Sync {
+    fn get_space_usage(&self, space_ctx: SpaceCtx) -> impl Future<Output = AppResult<SpaceUsageResponse>> + Send;
+}
+
+impl<D: SpaceDs + UsageDs> UsageService for ServiceWrapper<'_, D> {
+    async fn get_space_usage(
+        &self,
+        SpaceCtx {
+            space_id,
+            ..
+        }: SpaceCtx,
+    ) -> AppResult<SpaceUsageResponse> {
+        let quota = get_space_quota(self.ds, &space_id).await?;
+        let usage = self.ds.list_space_usage(&space_id).await?;
+
+        let mut by_uploader = HashMap::<Uuid, UploaderUsageResponse>::new();
+        let mut by_media_type = HashMap::<_, MediaTypeUsageResponse>::new();
+        for u in usage.iter() {
+            let uploader = by_uploader.entry(u.user_id).or_insert_with(|| UploaderUsageResponse {
+                user: u.user_id.to_string(),
+                bytes: 0,
+                file_count: 0,
+            });
+            uploader.bytes += u.bytes;
+            uploader.file_count += u.file_count;
+
+            let media_type =
+                by_media_type.entry(media_type_literal(u.media_type)).or_insert_with(|| MediaTypeUsageResponse {
+                    media_type: u.media_type,
+                    bytes: 0,
+                    file_count: 0,
+                });
+            media_type.bytes += u.bytes;
+            media_type.file_count += u.file_count;
+        }
+
+        let mut by_uploader: Vec<_> = by_uploader.into_values().collect();
+        by_uploader.sort_by_key(|u| std::cmp::Reverse(u.bytes));
+        let mut by_media_type: Vec<_> = by_media_type.into_values().collect();
+        by_media_type.sort_by_key(|u| std::cmp::Reverse(u.bytes));
+
+        Ok(SpaceUsageResponse {
+            quota,
+            used_bytes: usage.iter().map(|u| u.bytes).sum(),
+            file_count: usage.iter().map(|u| u.file_count).sum(),
+            by_uploader,
+            by_media_type,
+        })
+    }
+}
+
+/// Quota of space in bytes, `None` when unlimited
+pub(super) async fn get_space_quota<D: SpaceDs>(ds: &D, space_id: &Uuid) -> AppResult<Option<i64>> {
+    let space = ds.get_space_by_id(space_id).await?.ok_or(ErrType::NotFound.msg("Space not found"))?;
+    Ok(space.storage_quota.or_else(config::get_default_space_quota))
+}
+
+/// Reserves `file_size` bytes of the space quota for the upload of `hash` until it is processed
+///
+/// Check and reservation run under a lock of the space, so concurrent uploads cannot both pass.
+/// Uploads of unknown size reserve nothing and are only rejected once the space is full.
+pub(super) async fn reserve_space_quota<D: SpaceDs + UsageDs + TransactionDs>(
+    ds: &D,
+    space_id: &Uuid,
+    user_id: &Uuid,
+    hash: &str,
+    file_size: Option<i64>,
+) -> AppResult<()> {
+    let Some(quota) = get_space_quota(ds, space_id).await? else {
+        return Ok(());
+    };
+
+    let ttl = TimeDelta::hours(config::get_upload_session_ttl_hours() as i64);
+    let tx = ds.begin().await?;
+
+    let result = async {
+        tx.lock_space_usage(space_id).await?;
+        tx.delete_expired_reservations(space_id, Utc::now() - ttl).await?;
+
+        let used =
+            tx.get_space_used_bytes(space_id).await? + tx.get_space_reserved_bytes(space_id, user_id, hash).await?;
+        let exceeded = match file_size {
+            Some(file_size) => used + file_size > quota,
+            None => used >= quota,
+        };
+        if exceeded {
+            let needed = file_size.unwrap_or(0);
+            return Err(ErrType::QuotaExceeded
+                .msg(format!("Space quota exceeded: {used} of {quota} bytes used, upload needs {needed} bytes")));
+        }
+
+        if let Some(file_size) = file_size {
+            tx.reserve_usage(space_id, user_id, hash, file_size).await?;
+        }
+
+        Ok(())
+    }
+    .await;
+
+    tx.finish(result).await
+}
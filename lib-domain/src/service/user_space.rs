@@ -3,20 +3,33 @@ use uuid::Uuid;
 
 use crate::{
     datastore::{
+        audit::{AuditAction, AuditDs},
         space::SpaceDs,
+        transaction::TransactionDs,
         user_space::{SpaceRole, UserSpaceDs},
+        Keyset,
+    },
+    dto::{
+        space::res::{_SpaceResponse, _SpaceUserResponse, _UserSpaceResponseVec, UserSpacesResopnse},
+        Page, PageQuery,
     },
-    dto::space::res::{_SpaceResponse, _SpaceUserResponseVec, _UserSpaceResponseVec, UserSpacesResopnse},
     extension::{SpaceCtx, UserId},
 };
 
-use super::ServiceWrapper;
+use super::{
+    audit::audit,
+    pagination::{into_page, page_params},
+    ServiceWrapper,
+};
 
 pub trait UserSpaceService: Send + Sync {
     fn get_spaces_for_user(&self, user_id: UserId) -> impl Future<Output = AppResult<UserSpacesResopnse>> + Send;
 
-    fn get_users_for_space(&self, space_ctx: SpaceCtx)
-        -> impl Future<Output = AppResult<_SpaceUserResponseVec>> + Send;
+    fn get_users_for_space(
+        &self,
+        space_ctx: SpaceCtx,
+        page: PageQuery,
+    ) -> impl Future<Output = AppResult<Page<_SpaceUserResponse>>> + Send;
 
     fn add_user_to_space(&self, space_ctx: SpaceCtx, req_user_id: Uuid) -> impl Future<Output = AppResult<()>> + Send;
 
@@ -37,7 +50,7 @@ pub trait UserSpaceService: Send + Sync {
     fn leave_space(&self, space_ctx: SpaceCtx) -> impl Future<Output = AppResult<()>> + Send;
 }
 
-impl<D: UserSpaceDs + SpaceDs> UserSpaceService for ServiceWrapper<'_, D> {
+impl<D: UserSpaceDs + SpaceDs + TransactionDs + AuditDs> UserSpaceService for ServiceWrapper<'_, D> {
     async fn get_spaces_for_user(&self, UserId(user_id): UserId) -> AppResult<UserSpacesResopnse> {
         let default_space =
             self.ds.get_default_space(&user_id).await?.ok_or(ErrType::BadRequest.msg("No default space for user"))?;
@@ -55,8 +68,20 @@ impl<D: UserSpaceDs + SpaceDs> UserSpaceService for ServiceWrapper<'_, D> {
             space_id,
             ..
         }: SpaceCtx,
-    ) -> AppResult<_SpaceUserResponseVec> {
-        self.ds.get_all_users_for_space(&space_id).await.map(_SpaceUserResponseVec)
+        page: PageQuery,
+    ) -> AppResult<Page<_SpaceUserResponse>> {
+        let (after, limit) = page_params(page)?;
+        let users = self.ds.get_all_users_for_space(&space_id, after, limit + 1).await?;
+
+        into_page(
+            users,
+            limit,
+            |user| Keyset {
+                key: user.created_at,
+                id: user.id,
+            },
+            _SpaceUserResponse,
+        )
     }
 
     async fn add_user_to_space(
@@ -64,6 +89,7 @@ impl<D: UserSpaceDs + SpaceDs> UserSpaceService for ServiceWrapper<'_, D> {
         SpaceCtx {
             space_id,
             role,
+            actor,
             ..
         }: SpaceCtx,
         req_user_id: Uuid,
@@ -75,7 +101,15 @@ impl<D: UserSpaceDs + SpaceDs> UserSpaceService for ServiceWrapper<'_, D> {
             _ => (),
         };
 
-        self.ds.add_user_to_space(&req_user_id, &space_id, SpaceRole::Read).await.map(|_| ())
+        let tx = self.ds.begin().await?;
+
+        let result = async {
+            tx.add_user_to_space(&req_user_id, &space_id, SpaceRole::Read).await?;
+            audit(&tx, space_id, &actor, AuditAction::AddMember, Some(req_user_id), &[]).await
+        }
+        .await;
+
+        tx.finish(result).await
     }
 
     async fn update_user_space_role(
@@ -84,6 +118,7 @@ impl<D: UserSpaceDs + SpaceDs> UserSpaceService for ServiceWrapper<'_, D> {
         SpaceCtx {
             space_id,
             role,
+            actor,
             ..
         }: SpaceCtx,
         req_user_id: Uuid,
@@ -100,7 +135,15 @@ impl<D: UserSpaceDs + SpaceDs> UserSpaceService for ServiceWrapper<'_, D> {
 
         let space_member = self.ds.get_user_space(&req_user_id, &space_id).await?;
         if let Some(member) = space_member {
-            self.ds.update_space_user_role(member.id, req_role).await?;
+            let tx = self.ds.begin().await?;
+
+            let result = async {
+                tx.update_space_user_role(member.id, req_role).await?;
+                audit(&tx, space_id, &actor, AuditAction::UpdateMemberRole, Some(req_user_id), &[]).await
+            }
+            .await;
+
+            tx.finish(result).await?;
         }
 
         Ok(())
@@ -111,6 +154,7 @@ impl<D: UserSpaceDs + SpaceDs> UserSpaceService for ServiceWrapper<'_, D> {
         SpaceCtx {
             space_id,
             role,
+            actor,
             ..
         }: SpaceCtx,
         req_user_id: Uuid,
@@ -122,7 +166,15 @@ impl<D: UserSpaceDs + SpaceDs> UserSpaceService for ServiceWrapper<'_, D> {
 
         let space_member = self.ds.get_user_space(&req_user_id, &space_id).await?;
         if let Some(member) = space_member {
-            self.ds.remove_user_from_space(member.id).await?;
+            let tx = self.ds.begin().await?;
+
+            let result = async {
+                tx.remove_user_from_space(member.id).await?;
+                audit(&tx, space_id, &actor, AuditAction::RemoveMember, Some(req_user_id), &[]).await
+            }
+            .await;
+
+            tx.finish(result).await?;
         }
 
         Ok(())
@@ -132,9 +184,19 @@ impl<D: UserSpaceDs + SpaceDs> UserSpaceService for ServiceWrapper<'_, D> {
         &self,
         SpaceCtx {
             membership_id,
+            space_id,
+            actor,
             ..
         }: SpaceCtx,
     ) -> AppResult<()> {
-        self.ds.remove_user_from_space(membership_id).await
+        let tx = self.ds.begin().await?;
+
+        let result = async {
+            tx.remove_user_from_space(membership_id).await?;
+            audit(&tx, space_id, &actor, AuditAction::LeaveSpace, Some(actor.user_id), &[]).await
+        }
+        .await;
+
+        tx.finish(result).await
     }
 }
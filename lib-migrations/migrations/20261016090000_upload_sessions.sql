@@ -0,0 +1,28 @@
+-- Resumable multipart uploads
+--   upload_sessions (in-flight multipart uploads, removed on complete/abort)
+
+create table upload_sessions
+(
+    id         uuid         not null
+        constraint upload_sessions_pk
+            primary key,
+    created_at timestamptz  not null default now(),
+    updated_at timestamptz  not null default now(),
+    user_id    uuid         not null
+        constraint upload_sessions_users_id_fk
+            references users,
+    space_id   uuid         not null
+        constraint upload_sessions_spaces_id_fk
+            references spaces,
+    upload_id  varchar      not null,
+    hash       char(64)     not null,
+    file_name  varchar(255) not null,
+    file_size  bigint       not null,
+    part_size  bigint       not null
+);
+
+create index upload_sessions_space_id_index
+    on upload_sessions (space_id);
+
+create index upload_sessions_updated_at_index
+    on upload_sessions (updated_at);
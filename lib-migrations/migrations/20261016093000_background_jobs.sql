@@ -0,0 +1,23 @@
+-- Long running jobs tracked for progress reporting
+--   space_id has no fk, jobs outlive deleted spaces
+
+create table background_jobs
+(
+    id         uuid        not null
+        constraint background_jobs_pk
+            primary key,
+    created_at timestamptz not null default now(),
+    updated_at timestamptz not null default now(),
+    user_id    uuid        not null
+        constraint background_jobs_users_id_fk
+            references users,
+    space_id   uuid        not null,
+    kind       smallint    not null,
+    status     smallint    not null default 0,
+    progress   bigint      not null default 0,
+    report     jsonb       not null default '{}'::jsonb,
+    error      varchar
+);
+
+create index background_jobs_user_id_index
+    on background_jobs (user_id);
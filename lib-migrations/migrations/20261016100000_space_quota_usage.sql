@@ -0,0 +1,51 @@
+-- Per-space storage quota and usage counters
+--   spaces.storage_quota (bytes, null falls back to configured default)
+--   space_usage (aggregated media_files.node_size per uploader and media type)
+--   upload_reservations (quota reserved by uploads not processed yet, counted with space_usage
+--   so concurrent uploads cannot overshoot the quota, released once the file is processed,
+--   ignored after the upload session ttl)
+
+alter table spaces
+    add column storage_quota bigint;
+
+create table space_usage
+(
+    space_id   uuid        not null
+        constraint space_usage_spaces_id_fk
+            references spaces,
+    user_id    uuid        not null
+        constraint space_usage_users_id_fk
+            references users,
+    media_type varchar(16) not null,
+    bytes      bigint      not null default 0,
+    file_count bigint      not null default 0,
+    constraint space_usage_pk
+        primary key (space_id, user_id, media_type)
+);
+
+insert into space_usage (space_id, user_id, media_type, bytes, file_count)
+select space_id,
+       user_id,
+       coalesce(metadata->>'media_type', 'image'),
+       sum(node_size),
+       count(*)
+from media_files
+where node_size > 0
+group by space_id, user_id, coalesce(metadata->>'media_type', 'image');
+
+create table upload_reservations
+(
+    space_id   uuid        not null
+        constraint upload_reservations_spaces_id_fk
+            references spaces
+                on delete cascade,
+    user_id    uuid        not null
+        constraint upload_reservations_users_id_fk
+            references users
+                on delete cascade,
+    hash       char(64)    not null,
+    bytes      bigint      not null,
+    created_at timestamptz not null default now(),
+    constraint upload_reservations_pk
+        primary key (space_id, user_id, hash)
+);
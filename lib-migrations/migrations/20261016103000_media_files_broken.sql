@@ -0,0 +1,4 @@
+-- Set by storage reconciliation when object_key, thumbnail_key or preview_key is missing from storage
+
+alter table media_files
+    add broken_at timestamptz;
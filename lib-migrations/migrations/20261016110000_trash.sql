@@ -0,0 +1,16 @@
+-- Soft delete for media files and albums
+--   rows with deleted_at set are in trash, purged after the retention period
+
+alter table media_files
+    add deleted_at timestamptz;
+
+alter table albums
+    add deleted_at timestamptz;
+
+create index media_files_deleted_at_index
+    on media_files (deleted_at)
+    where deleted_at is not null;
+
+create index albums_deleted_at_index
+    on albums (deleted_at)
+    where deleted_at is not null;
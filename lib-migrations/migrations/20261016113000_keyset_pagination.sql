@@ -0,0 +1,12 @@
+-- Indexes backing keyset pagination on (sort key, id)
+
+create index media_files_space_id_updated_at_id_index
+    on media_files (space_id, updated_at desc, id desc)
+    where deleted_at is null;
+
+create index albums_space_id_name_id_index
+    on albums (space_id, name, id)
+    where deleted_at is null;
+
+create index users_spaces_space_id_created_at_id_index
+    on users_spaces (space_id, created_at, id);
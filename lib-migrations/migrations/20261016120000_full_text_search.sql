@@ -0,0 +1,57 @@
+-- Full-text search over media files and albums
+--   search_vector columns are maintained by triggers on insert and update
+--   file names weigh most, camera metadata less
+
+alter table media_files
+    add search_vector tsvector not null default ''::tsvector;
+
+alter table albums
+    add search_vector tsvector not null default ''::tsvector;
+
+create function media_files_search_vector() returns trigger
+    language plpgsql as
+$$
+begin
+    new.search_vector :=
+            setweight(to_tsvector('simple', coalesce(new.file_name, '')), 'A') ||
+            setweight(to_tsvector('simple', concat_ws(' ',
+                    new.metadata -> 'file_meta' ->> 'make',
+                    new.metadata -> 'file_meta' ->> 'model',
+                    new.metadata -> 'file_meta' ->> 'software')), 'C');
+    return new;
+end
+$$;
+
+create trigger media_files_search_vector_trigger
+    before insert or update of file_name, metadata
+    on media_files
+    for each row
+execute function media_files_search_vector();
+
+create function albums_search_vector() returns trigger
+    language plpgsql as
+$$
+begin
+    new.search_vector := setweight(to_tsvector('simple', coalesce(new.name, '')), 'A');
+    return new;
+end
+$$;
+
+create trigger albums_search_vector_trigger
+    before insert or update of name
+    on albums
+    for each row
+execute function albums_search_vector();
+
+-- backfill through the triggers
+update media_files
+set file_name = file_name;
+
+update albums
+set name = name;
+
+create index media_files_search_vector_index
+    on media_files using gin (search_vector);
+
+create index albums_search_vector_index
+    on albums using gin (search_vector);
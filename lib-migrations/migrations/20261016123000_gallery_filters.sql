@@ -0,0 +1,38 @@
+-- Expression indexes backing gallery filters over media metadata
+--   capture time is stored as an rfc3339 string with offset, so the cast does not depend on session timezone
+
+create function media_capture_time(metadata jsonb) returns timestamptz
+    language sql
+    immutable
+    parallel safe
+as
+$$
+select (metadata -> 'file_meta' ->> 'date_time')::timestamptz
+$$;
+
+create index media_files_space_id_capture_time_index
+    on media_files (space_id, media_capture_time(metadata))
+    where deleted_at is null;
+
+create index media_files_space_id_media_type_index
+    on media_files (space_id, (metadata ->> 'media_type'))
+    where deleted_at is null;
+
+create index media_files_space_id_make_index
+    on media_files (space_id, lower(metadata -> 'file_meta' ->> 'make'))
+    where deleted_at is null;
+
+create index media_files_space_id_model_index
+    on media_files (space_id, lower(metadata -> 'file_meta' ->> 'model'))
+    where deleted_at is null;
+
+create index media_files_space_id_user_id_index
+    on media_files (space_id, user_id)
+    where deleted_at is null;
+
+create index media_files_space_id_location_index
+    on media_files (space_id,
+                    ((metadata -> 'file_meta' ->> 'latitude')::float8),
+                    ((metadata -> 'file_meta' ->> 'longitude')::float8))
+    where deleted_at is null
+        and metadata -> 'file_meta' ->> 'latitude' is not null;
@@ -0,0 +1,7 @@
+-- Timeline buckets and gallery date filters key on capture time, falling back to update time
+
+drop index media_files_space_id_capture_time_index;
+
+create index media_files_space_id_taken_at_index
+    on media_files (space_id, coalesce(media_capture_time(metadata), updated_at))
+    where deleted_at is null;
@@ -0,0 +1,67 @@
+-- Typed columns for metadata read on every listing
+--   filled in by the backend with metadata, backfilled here from existing metadata
+--   the gallery sorts by taken_at, the capture time falling back to update time
+
+alter table media_files
+    add taken_at      timestamptz,
+    add media_type    varchar(16),
+    add width         int4,
+    add height        int4,
+    add duration_secs float8,
+    add latitude      float8,
+    add longitude     float8;
+
+-- exiftool durations, "12.5 s", "0:01:23" or "1:23", optionally suffixed with "(approx)"
+create function media_duration_secs(duration text) returns float8
+    language sql
+    immutable
+as
+$$
+select case
+           when d ~ '^\d+(\.\d+)?( s)?$' then regexp_replace(d, ' s$', '')::float8
+           when d ~ '^\d+:\d+(\.\d+)?$' then split_part(d, ':', 1)::float8 * 60 + split_part(d, ':', 2)::float8
+           when d ~ '^\d+:\d+:\d+(\.\d+)?$' then split_part(d, ':', 1)::float8 * 3600 +
+                                                  split_part(d, ':', 2)::float8 * 60 +
+                                                  split_part(d, ':', 3)::float8
+           end
+from (select regexp_replace(trim(duration), '\s*\(approx\)$', '') as d) as parsed
+$$;
+
+update media_files
+set taken_at      = coalesce(media_capture_time(metadata), updated_at),
+    media_type    = metadata ->> 'media_type',
+    width         = (metadata -> 'thumbnail_meta' ->> 'width')::int4,
+    height        = (metadata -> 'thumbnail_meta' ->> 'height')::int4,
+    duration_secs = coalesce(media_duration_secs(metadata -> 'file_meta' ->> 'duration'),
+                             media_duration_secs(metadata -> 'file_meta' ->> 'media_duration')),
+    latitude      = (metadata -> 'file_meta' ->> 'latitude')::float8,
+    longitude     = (metadata -> 'file_meta' ->> 'longitude')::float8;
+
+alter table media_files
+    alter taken_at set not null;
+
+drop function media_duration_secs(text);
+
+-- listings and filters move from metadata expressions to the columns
+drop index media_files_space_id_updated_at_id_index;
+drop index media_files_space_id_taken_at_index;
+drop index media_files_space_id_media_type_index;
+drop index media_files_space_id_location_index;
+
+drop function media_capture_time(jsonb);
+
+create index media_files_space_id_taken_at_id_index
+    on media_files (space_id, taken_at desc, id desc)
+    where deleted_at is null;
+
+create index media_files_space_id_media_type_index
+    on media_files (space_id, media_type)
+    where deleted_at is null;
+
+-- gist over point(longitude, latitude) answers map and gallery bounding box containment,
+-- space_id stays out of the index to avoid requiring btree_gist,
+-- the planner combines it with the space_id indexes instead
+create index media_files_location_index
+    on media_files using gist (point(longitude, latitude))
+    where deleted_at is null
+        and latitude is not null;
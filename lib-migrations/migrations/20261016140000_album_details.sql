@@ -0,0 +1,9 @@
+-- Album description and explicit cover
+--   albums without a cover, or whose cover left the album, show their latest taken file
+
+alter table albums
+    add description   varchar(2000),
+    add cover_file_id uuid
+        constraint albums_cover_file_id_fk
+            references media_files
+                on delete set null;
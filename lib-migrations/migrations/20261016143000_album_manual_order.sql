@@ -0,0 +1,26 @@
+-- Manual ordering of album files and per-album sort mode
+--   positions are spaced by 65536, a reorder rewrites the moved files only until the gap runs out
+--   sort_mode: 0 name, 1 capture date, 2 manual
+
+alter table albums
+    add sort_mode int2 not null default 0;
+
+alter table album_media_files
+    add position int8;
+
+-- start manual order from the current name order
+update album_media_files amf
+set position = ordered.position
+from (select amf.album_id,
+             amf.media_file_id,
+             row_number() over (partition by amf.album_id order by m.file_name, m.id) * 65536 as position
+      from album_media_files amf
+               inner join media_files m on m.id = amf.media_file_id) as ordered
+where amf.album_id = ordered.album_id
+  and amf.media_file_id = ordered.media_file_id;
+
+alter table album_media_files
+    alter position set not null;
+
+create index album_media_files_album_id_position_index
+    on album_media_files (album_id, position, media_file_id);
@@ -0,0 +1,23 @@
+-- Per-user favorite media files
+--   listed across every space the user belongs to, files of spaces left behind are hidden
+
+create table favorites
+(
+    user_id       uuid        not null
+        constraint favorites_users_id_fk
+            references users
+                on delete cascade,
+    media_file_id uuid        not null
+        constraint favorites_media_files_id_fk
+            references media_files
+                on delete cascade,
+    created_at    timestamptz not null default now(),
+    constraint favorites_pk
+        primary key (user_id, media_file_id)
+);
+
+create index favorites_user_id_created_at_index
+    on favorites (user_id, created_at desc, media_file_id desc);
+
+create index favorites_media_file_id_index
+    on favorites (media_file_id);
@@ -0,0 +1,36 @@
+-- Free-form tags scoped by space
+--   names are unique per space ignoring case
+
+create table tags
+(
+    id         uuid        not null
+        constraint tags_pk
+            primary key,
+    created_at timestamptz not null default now(),
+    updated_at timestamptz not null default now(),
+    space_id   uuid        not null
+        constraint tags_spaces_id_fk
+            references spaces,
+    name       varchar(64) not null
+);
+
+create unique index tags_space_id_name_uindex
+    on tags (space_id, lower(name));
+
+create table media_file_tags
+(
+    tag_id        uuid        not null
+        constraint media_file_tags_tags_id_fk
+            references tags
+                on delete cascade,
+    media_file_id uuid        not null
+        constraint media_file_tags_media_files_id_fk
+            references media_files
+                on delete cascade,
+    created_at    timestamptz not null default now(),
+    constraint media_file_tags_pk
+        primary key (tag_id, media_file_id)
+);
+
+create index media_file_tags_media_file_id_index
+    on media_file_tags (media_file_id);
@@ -0,0 +1,22 @@
+-- Comment threads on media files
+--   comments stay while the file is in trash and are deleted with it once purged
+
+create table comments
+(
+    id            uuid          not null
+        constraint comments_pk
+            primary key,
+    created_at    timestamptz   not null default now(),
+    updated_at    timestamptz   not null default now(),
+    media_file_id uuid          not null
+        constraint comments_media_files_id_fk
+            references media_files
+                on delete cascade,
+    user_id       uuid          not null
+        constraint comments_users_id_fk
+            references users,
+    body          varchar(2000) not null
+);
+
+create index comments_media_file_id_created_at_index
+    on comments (media_file_id, created_at, id);
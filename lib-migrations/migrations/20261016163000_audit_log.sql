@@ -0,0 +1,36 @@
+-- Append-only log of mutations made by space members
+--   entries outlive the space and its files, so space_id and file_ids are not foreign keys
+--   action literals are mapped by AuditAction
+
+create table audit_log
+(
+    id         uuid        not null
+        constraint audit_log_pk
+            primary key,
+    created_at timestamptz not null default now(),
+    space_id   uuid        not null,
+    actor_id   uuid        not null
+        constraint audit_log_users_id_fk
+            references users,
+    req_id     varchar(64) not null,
+    action     int2        not null,
+    target_id  uuid,
+    file_ids   uuid[]      not null default '{}'
+);
+
+create index audit_log_space_id_created_at_index
+    on audit_log (space_id, created_at desc, id desc);
+
+create function audit_log_append_only() returns trigger
+    language plpgsql as
+$$
+begin
+    raise exception 'audit_log is append-only';
+end
+$$;
+
+create trigger audit_log_append_only_trigger
+    before update or delete
+    on audit_log
+    for each row
+execute function audit_log_append_only();
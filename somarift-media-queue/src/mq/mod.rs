@@ -3,7 +3,9 @@ use std::{path::PathBuf, sync::Arc};
 use axum::response::sse;
 use futures_util::TryFutureExt;
 use lib_core::{
-    interconnect::ServiceInterconnect, storage::s3::S3Storage, AppError, AppResult, ErrType, X_SPACE_HEADER,
+    interconnect::ServiceInterconnect,
+    storage::{Backend, StorageBackend},
+    AppError, AppResult, ErrType, X_SPACE_HEADER,
 };
 use smq_dto::{
     req::ProcessMediaRequest,
@@ -47,7 +49,7 @@ impl QueueEvent {
 pub struct MediaQueue {
     pool: Arc<pool::ThreadPool<AppResult<(MediaMetadata, i64, ProcessedImage)>>>,
     broadcaster: Arc<tokio::sync::Mutex<broadcast::Broadcaster<QueueEvent>>>,
-    s3: Arc<S3Storage>,
+    storage: Arc<Backend>,
     interconnect: Arc<ServiceInterconnect>,
     backend_client: Arc<reqwest::Client>,
 }
@@ -57,7 +59,7 @@ impl Clone for MediaQueue {
         Self {
             pool: self.pool.clone(),
             broadcaster: self.broadcaster.clone(),
-            s3: self.s3.clone(),
+            storage: self.storage.clone(),
             interconnect: self.interconnect.clone(),
             backend_client: self.backend_client.clone(),
         }
@@ -70,7 +72,7 @@ impl MediaQueue {
         Self {
             pool: Arc::new(pool::ThreadPool::new(8)),
             broadcaster: Arc::new(tokio::sync::Mutex::new(broadcast::Broadcaster::new())),
-            s3: Arc::new(S3Storage::new()),
+            storage: Arc::new(Backend::new()),
             interconnect: Arc::new(ServiceInterconnect::new()),
             backend_client: Arc::new(client),
         }
@@ -116,7 +118,7 @@ impl MediaQueue {
 
         // spawn job
         let broadcaster = self.broadcaster.clone();
-        let s3 = self.s3.clone();
+        let storage = self.storage.clone();
         let _file_name = file_name.clone();
         let mut recv = self.pool.execute(move || {
             // send started event
@@ -128,17 +130,17 @@ impl MediaQueue {
             });
 
             // extract metadata
-            let _s3 = s3.clone();
+            let _storage = storage.clone();
             let _s3_file_path = s3_file_path.clone();
             let metadata_result = tokio::runtime::Handle::current().block_on(async move {
-                let file_size = _s3
+                let file_size = _storage
                     .head_object(&_s3_file_path)
                     .await
                     .and_then(|head| head.content_length.ok_or(ErrType::S3Error.msg("Failed to get size of file")));
 
                 let size_and_url = match file_size {
                     Ok(file_size) => {
-                        let url = _s3.generate_stream_signed_url(&_s3_file_path).await;
+                        let url = _storage.generate_stream_signed_url(&_s3_file_path).await;
                         url.map(|u| (file_size, u))
                     }
                     Err(err) => Err(err),
@@ -151,7 +153,7 @@ impl MediaQueue {
             });
 
             // process thumbnail and preview
-            let _s3 = s3.clone();
+            let _storage = storage.clone();
             let _s3_file_path = s3_file_path.clone();
             let result = match metadata_result {
                 Ok((metadata, file_size, url)) => {
@@ -163,7 +165,7 @@ impl MediaQueue {
                     let bytes = match media_ty {
                         MediaType::Image => tokio::runtime::Handle::current()
                             .block_on(async move {
-                                let bs = _s3.download_media(&_s3_file_path).await;
+                                let bs = _storage.download_media(&_s3_file_path).await;
                                 match bs {
                                     Ok(bs) => bs
                                         .collect()
@@ -214,8 +216,8 @@ impl MediaQueue {
 
                     tokio::runtime::Handle::current()
                         .block_on(async move {
-                            let th = s3.upload_photo(thumbnail_path.as_str(), thumbnail.buf).await;
-                            let pr = s3.upload_photo(preview_path.as_str(), preview.buf).await;
+                            let th = storage.upload_photo(thumbnail_path.as_str(), thumbnail.buf).await;
+                            let pr = storage.upload_photo(preview_path.as_str(), preview.buf).await;
                             th.and_then(|_| pr)
                         })
                         .map(|_| {
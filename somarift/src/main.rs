@@ -4,6 +4,7 @@ use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 mod app;
 mod routes;
 mod server;
+mod tasks;
 
 async fn run() {
     // initialize tracing
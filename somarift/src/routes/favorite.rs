@@ -0,0 +1,51 @@
+use axum::{
+    extract::{Query, State},
+    routing::{get, Router},
+    Extension,
+};
+use lib_core::{ApiError, ApiResult, Json, ReqId};
+use lib_domain::{
+    dto::{
+        favorite::{
+            req::FavoritesQuery,
+            res::{_FavoriteFileResponse, FavoriteFileResponse},
+        },
+        Page, PageQuery,
+    },
+    extension::UserId,
+    service::favorite::FavoriteService,
+};
+
+use crate::app::AppState;
+
+use super::middleware;
+
+pub fn bind_routes(app: AppState, router: Router<AppState>) -> Router<AppState> {
+    let routes = Router::new()
+        .route("/", get(list_favorites))
+        .layer(axum::middleware::from_fn_with_state(app, middleware::auth::authenticate));
+
+    router.nest("/favorites", routes)
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/favorites",
+    responses((status=200, body=Page<FavoriteFileResponse>)),
+    tag = "Favorites",
+    security(("api_key" = []))
+)]
+pub async fn list_favorites(
+    State(app): State<AppState>,
+    Extension(req_id): Extension<ReqId>,
+    Extension(user_id): Extension<UserId>,
+    Query(favorites): Query<FavoritesQuery>,
+    Query(query): Query<PageQuery>,
+) -> ApiResult<Page<_FavoriteFileResponse>> {
+    app.services()
+        .favorite_service()
+        .list_favorites(user_id, favorites, query)
+        .await
+        .map(Json)
+        .map_err(|err| ApiError(err, req_id))
+}
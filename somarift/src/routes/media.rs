@@ -1,52 +1,115 @@
 use std::str::FromStr;
 
 use axum::{
-    extract::{Path, State},
-    http::{HeaderMap, StatusCode},
-    routing::{delete, get, post, Router},
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{delete, get, patch, post, Router},
     Extension,
 };
-use lib_core::{smq_dto::res::MediaData, ApiError, ApiResult, EmptyResponse, ErrType, Json, ReqId, X_SPACE_HEADER};
+use lib_core::{
+    smq_dto::res::MediaData,
+    storage::{ArchiveEntry, CONTENT_URL_EXPIRY},
+    ApiError, ApiResult, EmptyResponse, ErrType, Json, ReqId, X_SPACE_HEADER,
+};
 use lib_domain::{
     dto::cloud::{
-        req::{CreateAlbumRequest, InitiateUploadRequest, QueueMediaProcessRequest, UpdateAlbumFilesRequest},
+        req::{
+            ArchiveFilesRequest, BulkFilesRequest, CreateAlbumRequest, CreateUploadSessionRequest, GalleryFilterQuery,
+            InitiateUploadRequest, MediaContentQuery, MoveAlbumFilesRequest, QueueMediaProcessRequest,
+            ReorderAlbumFilesRequest, SignUploadPartsRequest, TimelineQuery, TransferFilesRequest,
+            UpdateAlbumFilesRequest, UpdateAlbumRequest,
+        },
         res::{
-            _AlbumResponse, _AlbumResponseVec, _FileMetaResponseVec, AlbumResponse, DownloadUrlResponse,
-            FileMetaResponse, InitiateUploadResponse, StreamedUrlResponse,
+            _AlbumResponse, _FileMetaResponse, _TimelineBucketResponseVec, AlbumResponse, BulkFilesResponse,
+            DownloadUrlResponse, FileMetaResponse, InitiateUploadResponse, StreamedUrlResponse, TimelineBucketResponse,
+            TransferFilesResponse, UploadPartUrlsResponse, UploadSessionResponse,
         },
     },
+    dto::comment::{
+        req::{CreateCommentRequest, UpdateCommentRequest},
+        res::{_CommentResponse, CommentResponse},
+    },
+    dto::geo::{
+        req::{MapBoundsQuery, MapZoomQuery},
+        res::{_MapClusterResponseVec, MapClusterResponse},
+    },
+    dto::search::{req::SearchQuery, res::_SearchFileResponse, res::SearchFileResponse},
+    dto::trash::res::{_TrashedAlbumResponseVec, _TrashedFileResponseVec, TrashedAlbumResponse, TrashedFileResponse},
+    dto::{Page, PageQuery},
     extension::{SpaceCtx, UserId},
-    service::media::MediaService,
+    service::{
+        comment::CommentService,
+        favorite::FavoriteService,
+        geo::GeoService,
+        media::MediaService,
+        search::SearchService,
+        transfer::{TransferMode, TransferService},
+        trash::TrashService,
+        upload::UploadService,
+    },
 };
 use uuid::Uuid;
 
-use crate::app::AppState;
+use crate::{app::AppState, tasks};
 
 use super::middleware;
 
 pub fn bind_routes(app: AppState, router: Router<AppState>) -> Router<AppState> {
     let routes = Router::new()
         .route("/files/gallery", get(list_files_gallery))
+        .route("/search", get(search_files))
+        .route("/timeline", get(get_timeline))
+        .route("/map", get(list_map_clusters))
+        .route("/map/files", get(list_map_files))
         .route("/albums", post(create_album))
         .route("/albums", get(list_albums))
         .route("/albums/{id}", get(get_album))
+        .route("/albums/{id}", patch(update_album))
         .route("/albums/{id}", delete(delete_album))
         .route("/albums/{id}/files", get(list_files))
         .route("/albums/{id}/files/link", post(link_album_files))
         .route("/albums/{id}/files/unlink", post(unlink_album_files))
+        .route("/albums/{id}/files/move", post(move_album_files))
+        .route("/albums/{id}/files/reorder", post(reorder_album_files))
+        .route("/albums/{id}/archive", get(download_album_archive))
+        .route("/archive", post(download_files_archive))
         .route("/files/{id}", delete(delete_file))
+        .route("/files/{id}/favorite", post(star_file))
+        .route("/files/{id}/favorite", delete(unstar_file))
+        .route("/files/{id}/comments", get(list_comments))
+        .route("/files/{id}/comments", post(create_comment))
+        .route("/files/{id}/comments/{comment_id}", patch(update_comment))
+        .route("/files/{id}/comments/{comment_id}", delete(delete_comment))
+        .route("/files/delete", post(delete_files))
+        .route("/files/copy", post(copy_files))
+        .route("/files/move", post(move_files))
         .route("/stream/{id}", get(generate_thumbnail_preview_signed_urls))
         .route("/download/{id}", get(generate_download_signed_url))
         .route("/upload", post(initiate_upload))
+        .route("/upload/sessions", post(create_upload_session))
+        .route("/upload/sessions/{id}", get(get_upload_session))
+        .route("/upload/sessions/{id}", delete(abort_upload_session))
+        .route("/upload/sessions/{id}/parts", post(sign_upload_parts))
+        .route("/upload/sessions/{id}/complete", post(complete_upload_session))
         .route("/queue", post(media_queue))
+        .route("/trash", delete(empty_trash))
+        .route("/trash/files", get(list_trashed_files))
+        .route("/trash/files/{id}/restore", post(restore_file))
+        .route("/trash/files/restore", post(restore_files))
+        .route("/trash/albums", get(list_trashed_albums))
+        .route("/trash/albums/{id}/restore", post(restore_album))
         .layer(axum::middleware::from_fn_with_state(app.clone(), middleware::space::validate_user_space))
         .layer(axum::middleware::from_fn_with_state(app.clone(), middleware::auth::authenticate));
 
+    // media elements cannot send auth headers, content URLs are signed instead
+    let signed_routes = Router::new().route("/content/{id}", get(stream_media_content));
+
     let interconnect_routes = Router::new()
         .route("/queue/complete", post(complete_media_queue))
         .layer(axum::middleware::from_fn_with_state(app, middleware::auth::authenticate_interconnect));
 
-    router.nest("/media", routes).nest("/media", interconnect_routes)
+    router.nest("/media", routes).nest("/media", signed_routes).nest("/media", interconnect_routes)
 }
 
 #[utoipa::path(
@@ -65,7 +128,7 @@ pub async fn delete_album(
         .media_service()
         .delete_album(space_ctx, album_id)
         .await
-        .map(|_| Json(EmptyResponse::new(StatusCode::OK, "Album deleted")))
+        .map(|_| Json(EmptyResponse::new(StatusCode::OK, "Album moved to trash")))
         .map_err(|err| ApiError(err, req_id))
 }
 
@@ -83,53 +146,344 @@ pub async fn delete_file(
 ) -> ApiResult<EmptyResponse> {
     app.services()
         .media_service()
-        .delete_file(space_ctx, app.storage(), file_id)
+        .delete_file(space_ctx, file_id)
+        .await
+        .map(|_| Json(EmptyResponse::new(StatusCode::OK, "File moved to trash")))
+        .map_err(|err| ApiError(err, req_id))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/media/files/{id}/favorite",
+    responses((status=200, body=EmptyResponse)),
+    tag = "Cloud"
+)]
+pub async fn star_file(
+    State(app): State<AppState>,
+    Extension(req_id): Extension<ReqId>,
+    Extension(user_id): Extension<UserId>,
+    Extension(space_ctx): Extension<SpaceCtx>,
+    Path(file_id): Path<Uuid>,
+) -> ApiResult<EmptyResponse> {
+    app.services()
+        .favorite_service()
+        .star_file(user_id, space_ctx, file_id)
+        .await
+        .map(|_| Json(EmptyResponse::new(StatusCode::OK, "File added to favorites")))
+        .map_err(|err| ApiError(err, req_id))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/media/files/{id}/favorite",
+    responses((status=200, body=EmptyResponse)),
+    tag = "Cloud"
+)]
+pub async fn unstar_file(
+    State(app): State<AppState>,
+    Extension(req_id): Extension<ReqId>,
+    Extension(user_id): Extension<UserId>,
+    Extension(space_ctx): Extension<SpaceCtx>,
+    Path(file_id): Path<Uuid>,
+) -> ApiResult<EmptyResponse> {
+    app.services()
+        .favorite_service()
+        .unstar_file(user_id, space_ctx, file_id)
+        .await
+        .map(|_| Json(EmptyResponse::new(StatusCode::OK, "File removed from favorites")))
+        .map_err(|err| ApiError(err, req_id))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/media/files/{id}/comments",
+    responses((status=200, body=Page<CommentResponse>)),
+    tag = "Cloud"
+)]
+pub async fn list_comments(
+    State(app): State<AppState>,
+    Extension(req_id): Extension<ReqId>,
+    Extension(space_ctx): Extension<SpaceCtx>,
+    Path(file_id): Path<Uuid>,
+    Query(page): Query<PageQuery>,
+) -> ApiResult<Page<_CommentResponse>> {
+    app.services()
+        .comment_service()
+        .list_comments(space_ctx, file_id, page)
+        .await
+        .map(Json)
+        .map_err(|err| ApiError(err, req_id))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/media/files/{id}/comments",
+    responses((status=200, body=CommentResponse)),
+    tag = "Cloud"
+)]
+pub async fn create_comment(
+    State(app): State<AppState>,
+    Extension(req_id): Extension<ReqId>,
+    Extension(user_id): Extension<UserId>,
+    Extension(space_ctx): Extension<SpaceCtx>,
+    Path(file_id): Path<Uuid>,
+    Json(body): Json<CreateCommentRequest>,
+) -> ApiResult<_CommentResponse> {
+    app.services()
+        .comment_service()
+        .create_comment(user_id, space_ctx, file_id, body)
+        .await
+        .map(Json)
+        .map_err(|err| ApiError(err, req_id))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/v1/media/files/{id}/comments/{comment_id}",
+    responses((status=200, body=CommentResponse)),
+    tag = "Cloud"
+)]
+pub async fn update_comment(
+    State(app): State<AppState>,
+    Extension(req_id): Extension<ReqId>,
+    Extension(user_id): Extension<UserId>,
+    Extension(space_ctx): Extension<SpaceCtx>,
+    Path((file_id, comment_id)): Path<(Uuid, Uuid)>,
+    Json(body): Json<UpdateCommentRequest>,
+) -> ApiResult<_CommentResponse> {
+    app.services()
+        .comment_service()
+        .update_comment(user_id, space_ctx, file_id, comment_id, body)
+        .await
+        .map(Json)
+        .map_err(|err| ApiError(err, req_id))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/media/files/{id}/comments/{comment_id}",
+    responses((status=200, body=EmptyResponse)),
+    tag = "Cloud"
+)]
+pub async fn delete_comment(
+    State(app): State<AppState>,
+    Extension(req_id): Extension<ReqId>,
+    Extension(user_id): Extension<UserId>,
+    Extension(space_ctx): Extension<SpaceCtx>,
+    Path((file_id, comment_id)): Path<(Uuid, Uuid)>,
+) -> ApiResult<EmptyResponse> {
+    app.services()
+        .comment_service()
+        .delete_comment(user_id, space_ctx, file_id, comment_id)
+        .await
+        .map(|_| Json(EmptyResponse::new(StatusCode::OK, "Comment deleted")))
+        .map_err(|err| ApiError(err, req_id))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/media/files/delete",
+    responses((status=200, body=BulkFilesResponse)),
+    tag = "Cloud"
+)]
+pub async fn delete_files(
+    State(app): State<AppState>,
+    Extension(req_id): Extension<ReqId>,
+    Extension(space_ctx): Extension<SpaceCtx>,
+    Json(body): Json<BulkFilesRequest>,
+) -> ApiResult<BulkFilesResponse> {
+    app.services()
+        .media_service()
+        .delete_files(space_ctx, body.file_ids)
+        .await
+        .map(Json)
+        .map_err(|err| ApiError(err, req_id))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/media/files/copy",
+    responses((status=200, body=TransferFilesResponse)),
+    tag = "Cloud"
+)]
+pub async fn copy_files(
+    State(app): State<AppState>,
+    Extension(req_id): Extension<ReqId>,
+    Extension(user_id): Extension<UserId>,
+    Extension(space_ctx): Extension<SpaceCtx>,
+    Json(body): Json<TransferFilesRequest>,
+) -> ApiResult<TransferFilesResponse> {
+    app.services()
+        .transfer_service()
+        .transfer_files(user_id, space_ctx, app.storage(), body, TransferMode::Copy)
         .await
-        .map(|_| Json(EmptyResponse::new(StatusCode::OK, "File deleted")))
+        .map(Json)
+        .map_err(|err| ApiError(err, req_id))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/media/files/move",
+    responses((status=200, body=TransferFilesResponse)),
+    tag = "Cloud"
+)]
+pub async fn move_files(
+    State(app): State<AppState>,
+    Extension(req_id): Extension<ReqId>,
+    Extension(user_id): Extension<UserId>,
+    Extension(space_ctx): Extension<SpaceCtx>,
+    Json(body): Json<TransferFilesRequest>,
+) -> ApiResult<TransferFilesResponse> {
+    app.services()
+        .transfer_service()
+        .transfer_files(user_id, space_ctx, app.storage(), body, TransferMode::Move)
+        .await
+        .map(Json)
         .map_err(|err| ApiError(err, req_id))
 }
 
 #[utoipa::path(
     get,
     path = "/v1/media/albums/{id}/files",
-    responses((status=200, body=Vec<FileMetaResponse>)),
+    responses((status=200, body=Page<FileMetaResponse>)),
     tag = "Cloud"
 )]
 pub async fn list_files(
     State(app): State<AppState>,
     Extension(req_id): Extension<ReqId>,
+    Extension(user_id): Extension<UserId>,
     Extension(space_ctx): Extension<SpaceCtx>,
     Path(album_id): Path<Uuid>,
-) -> ApiResult<_FileMetaResponseVec> {
-    app.services().media_service().list_files(space_ctx, album_id).await.map(Json).map_err(|err| ApiError(err, req_id))
+    Query(query): Query<PageQuery>,
+) -> ApiResult<Page<_FileMetaResponse>> {
+    app.services()
+        .media_service()
+        .list_files(user_id, space_ctx, album_id, query)
+        .await
+        .map(Json)
+        .map_err(|err| ApiError(err, req_id))
 }
 
 #[utoipa::path(
     get,
     path = "/v1/media/files/gallery",
-    responses((status=200, body=Vec<FileMetaResponse>)),
+    responses((status=200, body=Page<FileMetaResponse>)),
     tag = "Cloud"
 )]
 pub async fn list_files_gallery(
     State(app): State<AppState>,
     Extension(req_id): Extension<ReqId>,
+    Extension(user_id): Extension<UserId>,
+    Extension(space_ctx): Extension<SpaceCtx>,
+    Query(filter): Query<GalleryFilterQuery>,
+    Query(query): Query<PageQuery>,
+) -> ApiResult<Page<_FileMetaResponse>> {
+    app.services()
+        .media_service()
+        .list_files_gallery(user_id, space_ctx, filter, query)
+        .await
+        .map(Json)
+        .map_err(|err| ApiError(err, req_id))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/media/search",
+    responses((status=200, body=Page<SearchFileResponse>)),
+    tag = "Cloud"
+)]
+pub async fn search_files(
+    State(app): State<AppState>,
+    Extension(req_id): Extension<ReqId>,
+    Extension(user_id): Extension<UserId>,
     Extension(space_ctx): Extension<SpaceCtx>,
-) -> ApiResult<_FileMetaResponseVec> {
-    app.services().media_service().list_files_gallery(space_ctx).await.map(Json).map_err(|err| ApiError(err, req_id))
+    Query(search): Query<SearchQuery>,
+    Query(query): Query<PageQuery>,
+) -> ApiResult<Page<_SearchFileResponse>> {
+    app.services()
+        .search_service()
+        .search_files(user_id, space_ctx, search, query)
+        .await
+        .map(Json)
+        .map_err(|err| ApiError(err, req_id))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/media/timeline",
+    responses((status=200, body=Vec<TimelineBucketResponse>)),
+    tag = "Cloud"
+)]
+pub async fn get_timeline(
+    State(app): State<AppState>,
+    Extension(req_id): Extension<ReqId>,
+    Extension(space_ctx): Extension<SpaceCtx>,
+    Query(query): Query<TimelineQuery>,
+) -> ApiResult<_TimelineBucketResponseVec> {
+    app.services().media_service().get_timeline(space_ctx, query).await.map(Json).map_err(|err| ApiError(err, req_id))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/media/map",
+    responses((status=200, body=Vec<MapClusterResponse>)),
+    tag = "Cloud"
+)]
+pub async fn list_map_clusters(
+    State(app): State<AppState>,
+    Extension(req_id): Extension<ReqId>,
+    Extension(space_ctx): Extension<SpaceCtx>,
+    Query(bounds): Query<MapBoundsQuery>,
+    Query(zoom): Query<MapZoomQuery>,
+) -> ApiResult<_MapClusterResponseVec> {
+    app.services()
+        .geo_service()
+        .list_map_clusters(space_ctx, bounds, zoom)
+        .await
+        .map(Json)
+        .map_err(|err| ApiError(err, req_id))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/media/map/files",
+    responses((status=200, body=Page<FileMetaResponse>)),
+    tag = "Cloud"
+)]
+pub async fn list_map_files(
+    State(app): State<AppState>,
+    Extension(req_id): Extension<ReqId>,
+    Extension(user_id): Extension<UserId>,
+    Extension(space_ctx): Extension<SpaceCtx>,
+    Query(bounds): Query<MapBoundsQuery>,
+    Query(query): Query<PageQuery>,
+) -> ApiResult<Page<_FileMetaResponse>> {
+    app.services()
+        .geo_service()
+        .list_map_files(user_id, space_ctx, bounds, query)
+        .await
+        .map(Json)
+        .map_err(|err| ApiError(err, req_id))
 }
 
 #[utoipa::path(
     get,
     path = "/v1/media/albums",
-    responses((status=200, body=Vec<AlbumResponse>)),
+    responses((status=200, body=Page<AlbumResponse>)),
     tag = "Cloud"
 )]
 pub async fn list_albums(
     State(app): State<AppState>,
     Extension(req_id): Extension<ReqId>,
     Extension(space_ctx): Extension<SpaceCtx>,
-) -> ApiResult<_AlbumResponseVec> {
-    app.services().media_service().list_albums(space_ctx).await.map(Json).map_err(|err| ApiError(err, req_id))
+    Query(query): Query<PageQuery>,
+) -> ApiResult<Page<_AlbumResponse>> {
+    app.services()
+        .media_service()
+        .list_albums(space_ctx, app.storage(), query)
+        .await
+        .map(Json)
+        .map_err(|err| ApiError(err, req_id))
 }
 
 #[utoipa::path(
@@ -144,7 +498,33 @@ pub async fn get_album(
     Extension(space_ctx): Extension<SpaceCtx>,
     Path(album_id): Path<Uuid>,
 ) -> ApiResult<_AlbumResponse> {
-    app.services().media_service().get_album(space_ctx, album_id).await.map(Json).map_err(|err| ApiError(err, req_id))
+    app.services()
+        .media_service()
+        .get_album(space_ctx, app.storage(), album_id)
+        .await
+        .map(Json)
+        .map_err(|err| ApiError(err, req_id))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/v1/media/albums/{id}",
+    responses((status=200, body=AlbumResponse)),
+    tag = "Cloud"
+)]
+pub async fn update_album(
+    State(app): State<AppState>,
+    Extension(req_id): Extension<ReqId>,
+    Extension(space_ctx): Extension<SpaceCtx>,
+    Path(album_id): Path<Uuid>,
+    Json(body): Json<UpdateAlbumRequest>,
+) -> ApiResult<_AlbumResponse> {
+    app.services()
+        .media_service()
+        .update_album(space_ctx, app.storage(), album_id, body)
+        .await
+        .map(Json)
+        .map_err(|err| ApiError(err, req_id))
 }
 
 #[utoipa::path(
@@ -167,6 +547,112 @@ pub async fn initiate_upload(
         .map_err(|err| ApiError(err, req_id))
 }
 
+#[utoipa::path(
+    post,
+    path = "/v1/media/upload/sessions",
+    responses((status=200, body=UploadSessionResponse)),
+    tag = "Cloud"
+)]
+pub async fn create_upload_session(
+    State(app): State<AppState>,
+    Extension(req_id): Extension<ReqId>,
+    Extension(user_id): Extension<UserId>,
+    Extension(space_ctx): Extension<SpaceCtx>,
+    Json(body): Json<CreateUploadSessionRequest>,
+) -> ApiResult<UploadSessionResponse> {
+    app.services()
+        .upload_service()
+        .create_upload_session(user_id, space_ctx, app.storage(), body)
+        .await
+        .map(Json)
+        .map_err(|err| ApiError(err, req_id))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/media/upload/sessions/{id}",
+    responses((status=200, body=UploadSessionResponse)),
+    tag = "Cloud"
+)]
+pub async fn get_upload_session(
+    State(app): State<AppState>,
+    Extension(req_id): Extension<ReqId>,
+    Extension(user_id): Extension<UserId>,
+    Extension(space_ctx): Extension<SpaceCtx>,
+    Path(session_id): Path<Uuid>,
+) -> ApiResult<UploadSessionResponse> {
+    app.services()
+        .upload_service()
+        .get_upload_session(user_id, space_ctx, app.storage(), session_id)
+        .await
+        .map(Json)
+        .map_err(|err| ApiError(err, req_id))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/media/upload/sessions/{id}/parts",
+    responses((status=200, body=UploadPartUrlsResponse)),
+    tag = "Cloud"
+)]
+pub async fn sign_upload_parts(
+    State(app): State<AppState>,
+    Extension(req_id): Extension<ReqId>,
+    Extension(user_id): Extension<UserId>,
+    Extension(space_ctx): Extension<SpaceCtx>,
+    Path(session_id): Path<Uuid>,
+    Json(body): Json<SignUploadPartsRequest>,
+) -> ApiResult<UploadPartUrlsResponse> {
+    app.services()
+        .upload_service()
+        .sign_upload_parts(user_id, space_ctx, app.storage(), session_id, body)
+        .await
+        .map(Json)
+        .map_err(|err| ApiError(err, req_id))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/media/upload/sessions/{id}/complete",
+    responses((status=200, body=EmptyResponse)),
+    tag = "Cloud"
+)]
+pub async fn complete_upload_session(
+    State(app): State<AppState>,
+    Extension(req_id): Extension<ReqId>,
+    Extension(user_id): Extension<UserId>,
+    Extension(space_ctx): Extension<SpaceCtx>,
+    Path(session_id): Path<Uuid>,
+) -> ApiResult<EmptyResponse> {
+    app.services()
+        .upload_service()
+        .complete_upload_session(user_id, space_ctx, app.storage(), session_id)
+        .await
+        .map(|_| Json(EmptyResponse::new(StatusCode::OK, "Upload completed")))
+        .map_err(|err| ApiError(err, req_id))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/media/upload/sessions/{id}",
+    responses((status=200, body=EmptyResponse)),
+    tag = "Cloud"
+)]
+pub async fn abort_upload_session(
+    State(app): State<AppState>,
+    Extension(req_id): Extension<ReqId>,
+    Extension(user_id): Extension<UserId>,
+    Extension(space_ctx): Extension<SpaceCtx>,
+    Path(session_id): Path<Uuid>,
+) -> ApiResult<EmptyResponse> {
+    app.services()
+        .upload_service()
+        .abort_upload_session(user_id, space_ctx, app.storage(), session_id)
+        .await
+        .map(|_| Json(EmptyResponse::new(StatusCode::OK, "Upload aborted")))
+        .map_err(|err| ApiError(err, req_id))
+}
+
 #[utoipa::path(
     get,
     path = "/v1/media/stream/{id}",
@@ -207,10 +693,223 @@ pub async fn generate_download_signed_url(
         .map_err(|err| ApiError(err, req_id))
 }
 
+/// Proxies media bytes from storage, honouring `Range` and `If-None-Match`
+///
+/// Served URLs point here when `MEDIA_DELIVERY=proxy`, authorized by their signature
+#[utoipa::path(
+    get,
+    path = "/v1/media/content/{id}",
+    responses(
+        (status=200, description="Media bytes"),
+        (status=206, description="Requested byte range"),
+        (status=304, description="Not modified"),
+        (status=416, description="Range not satisfiable"),
+    ),
+    tag = "Cloud"
+)]
+pub async fn stream_media_content(
+    State(app): State<AppState>,
+    Extension(req_id): Extension<ReqId>,
+    Path(file_id): Path<Uuid>,
+    Query(query): Query<MediaContentQuery>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    app.storage()
+        .verify_content_signature(
+            &query.space_id.to_string(),
+            &query.user_id.to_string(),
+            &file_id.to_string(),
+            query.variant.as_str(),
+            query.expires,
+            &query.signature,
+        )
+        .map_err(|err| ApiError(err, req_id.clone()))?;
+
+    // URL outlives removal of the member, so membership is checked again
+    let space_ctx =
+        middleware::space::get_space_ctx(&app, req_id.clone(), UserId(query.user_id), query.space_id).await?;
+
+    let range = headers.get(header::RANGE).and_then(|v| v.to_str().ok()).map(str::to_owned);
+    let if_none_match = headers.get(header::IF_NONE_MATCH).and_then(|v| v.to_str().ok()).map(str::to_owned);
+
+    let object = app
+        .services()
+        .media_service()
+        .get_media_stream(space_ctx.space_id, app.storage(), file_id, query.variant, range, if_none_match)
+        .await
+        .map_err(|err| ApiError(err, req_id.clone()))?;
+
+    super::storage::object_response(object, Some(format!("private, max-age={CONTENT_URL_EXPIRY}")), req_id)
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/media/albums/{id}/archive",
+    responses((status=200, description="ZIP of album files", content_type="application/zip")),
+    tag = "Cloud"
+)]
+pub async fn download_album_archive(
+    State(app): State<AppState>,
+    Extension(req_id): Extension<ReqId>,
+    Extension(space_ctx): Extension<SpaceCtx>,
+    Path(album_id): Path<Uuid>,
+) -> Result<Response, ApiError> {
+    let space_id = space_ctx.space_id;
+    let (name, entries) = app
+        .services()
+        .media_service()
+        .get_album_archive(space_ctx, album_id)
+        .await
+        .map_err(|err| ApiError(err, req_id))?;
+
+    Ok(archive_response(app, space_id, name, entries))
+}
+
 #[utoipa::path(
     post,
-    path = "/v1/media/queue",
+    path = "/v1/media/archive",
+    responses((status=200, description="ZIP of selected files", content_type="application/zip")),
+    tag = "Cloud"
+)]
+pub async fn download_files_archive(
+    State(app): State<AppState>,
+    Extension(req_id): Extension<ReqId>,
+    Extension(space_ctx): Extension<SpaceCtx>,
+    Json(body): Json<ArchiveFilesRequest>,
+) -> Result<Response, ApiError> {
+    let space_id = space_ctx.space_id;
+    let (name, entries) = app
+        .services()
+        .media_service()
+        .get_files_archive(space_ctx, body.file_ids)
+        .await
+        .map_err(|err| ApiError(err, req_id))?;
+
+    Ok(archive_response(app, space_id, name, entries))
+}
+
+fn archive_response(app: AppState, space_id: Uuid, name: String, entries: Vec<ArchiveEntry>) -> Response {
+    let disposition = format!("attachment; filename=\"{}.zip\"", name.replace(['"', '\\'], "_"));
+    let body = tasks::spawn_archive(app, space_id, entries);
+
+    ([(header::CONTENT_TYPE, "application/zip".to_owned()), (header::CONTENT_DISPOSITION, disposition)], body)
+        .into_response()
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/media/trash/files",
+    responses((status=200, body=Vec<TrashedFileResponse>)),
+    tag = "Trash"
+)]
+pub async fn list_trashed_files(
+    State(app): State<AppState>,
+    Extension(req_id): Extension<ReqId>,
+    Extension(space_ctx): Extension<SpaceCtx>,
+) -> ApiResult<_TrashedFileResponseVec> {
+    app.services().trash_service().list_trashed_files(space_ctx).await.map(Json).map_err(|err| ApiError(err, req_id))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/media/trash/albums",
+    responses((status=200, body=Vec<TrashedAlbumResponse>)),
+    tag = "Trash"
+)]
+pub async fn list_trashed_albums(
+    State(app): State<AppState>,
+    Extension(req_id): Extension<ReqId>,
+    Extension(space_ctx): Extension<SpaceCtx>,
+) -> ApiResult<_TrashedAlbumResponseVec> {
+    app.services().trash_service().list_trashed_albums(space_ctx).await.map(Json).map_err(|err| ApiError(err, req_id))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/media/trash/files/{id}/restore",
+    responses((status=200, body=EmptyResponse)),
+    tag = "Trash"
+)]
+pub async fn restore_file(
+    State(app): State<AppState>,
+    Extension(req_id): Extension<ReqId>,
+    Extension(space_ctx): Extension<SpaceCtx>,
+    Path(file_id): Path<Uuid>,
+) -> ApiResult<EmptyResponse> {
+    app.services()
+        .trash_service()
+        .restore_file(space_ctx, file_id)
+        .await
+        .map(|_| Json(EmptyResponse::new(StatusCode::OK, "File restored")))
+        .map_err(|err| ApiError(err, req_id))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/media/trash/files/restore",
+    responses((status=200, body=BulkFilesResponse)),
+    tag = "Trash"
+)]
+pub async fn restore_files(
+    State(app): State<AppState>,
+    Extension(req_id): Extension<ReqId>,
+    Extension(space_ctx): Extension<SpaceCtx>,
+    Json(body): Json<BulkFilesRequest>,
+) -> ApiResult<BulkFilesResponse> {
+    app.services()
+        .trash_service()
+        .restore_files(space_ctx, body.file_ids)
+        .await
+        .map(Json)
+        .map_err(|err| ApiError(err, req_id))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/media/trash/albums/{id}/restore",
+    responses((status=200, body=EmptyResponse)),
+    tag = "Trash"
+)]
+pub async fn restore_album(
+    State(app): State<AppState>,
+    Extension(req_id): Extension<ReqId>,
+    Extension(space_ctx): Extension<SpaceCtx>,
+    Path(album_id): Path<Uuid>,
+) -> ApiResult<EmptyResponse> {
+    app.services()
+        .trash_service()
+        .restore_album(space_ctx, album_id)
+        .await
+        .map(|_| Json(EmptyResponse::new(StatusCode::OK, "Album restored")))
+        .map_err(|err| ApiError(err, req_id))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/media/trash",
     responses((status=200, body=EmptyResponse)),
+    tag = "Trash"
+)]
+pub async fn empty_trash(
+    State(app): State<AppState>,
+    Extension(req_id): Extension<ReqId>,
+    Extension(space_ctx): Extension<SpaceCtx>,
+) -> ApiResult<EmptyResponse> {
+    app.services()
+        .trash_service()
+        .empty_trash(space_ctx, app.storage())
+        .await
+        .map(|_| Json(EmptyResponse::new(StatusCode::OK, "Trash emptied")))
+        .map_err(|err| ApiError(err, req_id))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/media/queue",
+    responses(
+        (status=200, body=EmptyResponse),
+        (status=202, body=EmptyResponse, description="Upload is hashed before it is queued"),
+    ),
     tag = "Cloud"
 )]
 pub async fn media_queue(
@@ -220,12 +919,20 @@ pub async fn media_queue(
     Extension(space_ctx): Extension<SpaceCtx>,
     Json(body): Json<QueueMediaProcessRequest>,
 ) -> ApiResult<EmptyResponse> {
-    app.services()
+    let pending = app
+        .services()
         .media_service()
         .queue_media_process(user_id, space_ctx, app.storage(), app.interconnect(), body)
         .await
-        .map(|_| Json(EmptyResponse::new(StatusCode::OK, "Media queued")))
-        .map_err(|err| ApiError(err, req_id))
+        .map_err(|err| ApiError(err, req_id))?;
+
+    match pending {
+        Some(upload) => {
+            tasks::spawn_upload_verification(app, upload);
+            Ok(Json(EmptyResponse::new(StatusCode::ACCEPTED, "Media queued for verification")))
+        }
+        None => Ok(Json(EmptyResponse::new(StatusCode::OK, "Media queued"))),
+    }
 }
 
 #[utoipa::path(
@@ -281,7 +988,7 @@ pub async fn create_album(
 #[utoipa::path(
     post,
     path = "/v1/media/albums/{id}/files/link",
-    responses((status=200, body=EmptyResponse)),
+    responses((status=200, body=BulkFilesResponse)),
     tag = "Cloud"
 )]
 pub async fn link_album_files(
@@ -290,19 +997,19 @@ pub async fn link_album_files(
     Extension(space_ctx): Extension<SpaceCtx>,
     Path(album_id): Path<Uuid>,
     Json(body): Json<UpdateAlbumFilesRequest>,
-) -> ApiResult<EmptyResponse> {
+) -> ApiResult<BulkFilesResponse> {
     app.services()
         .media_service()
         .link_album_files(space_ctx, album_id, body.file_ids)
         .await
-        .map(|_| Json(EmptyResponse::new(StatusCode::OK, "Files linked")))
+        .map(Json)
         .map_err(|err| ApiError(err, req_id))
 }
 
 #[utoipa::path(
     post,
     path = "/v1/media/albums/{id}/files/unlink",
-    responses((status=200, body=EmptyResponse)),
+    responses((status=200, body=BulkFilesResponse)),
     tag = "Cloud"
 )]
 pub async fn unlink_album_files(
@@ -311,11 +1018,53 @@ pub async fn unlink_album_files(
     Extension(space_ctx): Extension<SpaceCtx>,
     Path(album_id): Path<Uuid>,
     Json(body): Json<UpdateAlbumFilesRequest>,
-) -> ApiResult<EmptyResponse> {
+) -> ApiResult<BulkFilesResponse> {
     app.services()
         .media_service()
         .unlink_album_files(space_ctx, album_id, body.file_ids)
         .await
-        .map(|_| Json(EmptyResponse::new(StatusCode::OK, "Files unlinked")))
+        .map(Json)
+        .map_err(|err| ApiError(err, req_id))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/media/albums/{id}/files/move",
+    responses((status=200, body=BulkFilesResponse)),
+    tag = "Cloud"
+)]
+pub async fn move_album_files(
+    State(app): State<AppState>,
+    Extension(req_id): Extension<ReqId>,
+    Extension(space_ctx): Extension<SpaceCtx>,
+    Path(album_id): Path<Uuid>,
+    Json(body): Json<MoveAlbumFilesRequest>,
+) -> ApiResult<BulkFilesResponse> {
+    app.services()
+        .media_service()
+        .move_album_files(space_ctx, album_id, body)
+        .await
+        .map(Json)
+        .map_err(|err| ApiError(err, req_id))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/media/albums/{id}/files/reorder",
+    responses((status=200, body=BulkFilesResponse)),
+    tag = "Cloud"
+)]
+pub async fn reorder_album_files(
+    State(app): State<AppState>,
+    Extension(req_id): Extension<ReqId>,
+    Extension(space_ctx): Extension<SpaceCtx>,
+    Path(album_id): Path<Uuid>,
+    Json(body): Json<ReorderAlbumFilesRequest>,
+) -> ApiResult<BulkFilesResponse> {
+    app.services()
+        .media_service()
+        .reorder_album_files(space_ctx, album_id, body)
+        .await
+        .map(Json)
         .map_err(|err| ApiError(err, req_id))
 }
@@ -10,7 +10,7 @@ use axum::{
 use lib_core::{ApiError, ErrType, ReqId, X_SPACE_HEADER};
 use lib_domain::{
     datastore::{space::SpaceDs, user_space::UserSpaceDs},
-    extension::{SpaceCtx, UserId},
+    extension::{Actor, SpaceCtx, UserId},
 };
 use uuid::Uuid;
 
@@ -33,7 +33,24 @@ pub async fn validate_user_space(
     let space_id = Uuid::from_str(space_id)
         .map_err(|err| ApiError(ErrType::BadRequest.err(err, "Invalid space id format"), req_id.clone()))?;
 
+    let space_ctx = get_space_ctx(&app, req_id, user_id, space_id).await?;
+    req.extensions_mut().insert(space_ctx);
+
+    Ok(next.run(req).await)
+}
+
+/// Resolves role of user in space, rejecting users who are not members
+pub async fn get_space_ctx(
+    app: &AppState,
+    req_id: ReqId,
+    user_id: UserId,
+    space_id: Uuid,
+) -> Result<SpaceCtx, ApiError> {
     let default_space = app.services().ds().get_default_space(&user_id.0).await.ok().flatten();
+    let actor = Actor {
+        user_id: user_id.0,
+        req_id: req_id.clone(),
+    };
 
     let space_ctx = if let Some(space) = default_space
         && space.id == space_id
@@ -42,6 +59,7 @@ pub async fn validate_user_space(
             membership_id: Uuid::nil(),
             space_id,
             role: lib_domain::datastore::user_space::SpaceRole::DefaultSpace,
+            actor,
         }
     } else {
         let space_member = app
@@ -56,10 +74,9 @@ pub async fn validate_user_space(
             membership_id: space_member.id,
             space_id: space_member.space_id,
             role: space_member.role,
+            actor,
         }
     };
 
-    req.extensions_mut().insert(space_ctx);
-
-    Ok(next.run(req).await)
+    Ok(space_ctx)
 }
@@ -7,10 +7,13 @@ use utoipa::{
 use crate::app::AppState;
 
 mod auth;
+mod favorite;
 mod health;
 mod media;
 mod middleware;
 mod space;
+mod storage;
+mod tag;
 mod user;
 
 /// Function to bind routes from:
@@ -24,7 +27,10 @@ pub fn bind_routes(app: AppState, router: Router<AppState>) -> Router<AppState>
     let r = auth::bind_routes(app.clone(), Router::new());
     let r = user::bind_routes(app.clone(), r);
     let r = space::bind_routes(app.clone(), r);
-    let r = media::bind_routes(app, r);
+    let r = media::bind_routes(app.clone(), r);
+    let r = favorite::bind_routes(app.clone(), r);
+    let r = tag::bind_routes(app, r);
+    let r = storage::bind_routes(r);
 
     router.merge(health).nest("/v1", r)
 }
@@ -47,40 +53,134 @@ pub fn bind_routes(app: AppState, router: Router<AppState>) -> Router<AppState>
 
         space::create_space,
         space::get_user_spaces,
+        space::get_space_usage,
+        space::delete_space,
+        space::reconcile_space,
+        space::list_audit_entries,
+        space::get_job,
+
+        favorite::list_favorites,
 
         media::initiate_upload,
+        media::create_upload_session,
+        media::get_upload_session,
+        media::sign_upload_parts,
+        media::complete_upload_session,
+        media::abort_upload_session,
         media::generate_thumbnail_preview_signed_urls,
+        media::stream_media_content,
         media::media_queue,
         media::list_files,
         media::list_files_gallery,
+        media::search_files,
+        media::get_timeline,
+        media::list_map_clusters,
+        media::list_map_files,
         media::create_album,
         media::list_albums,
         media::get_album,
+        media::update_album,
         media::link_album_files,
         media::unlink_album_files,
+        media::move_album_files,
+        media::reorder_album_files,
+        media::download_album_archive,
+        media::download_files_archive,
         media::delete_album,
         media::delete_file,
+        media::star_file,
+        media::unstar_file,
+        media::list_comments,
+        media::create_comment,
+        media::update_comment,
+        media::delete_comment,
+        media::delete_files,
+        media::copy_files,
+        media::move_files,
+        media::list_trashed_files,
+        media::list_trashed_albums,
+        media::restore_file,
+        media::restore_files,
+        media::restore_album,
+        media::empty_trash,
+
+        tag::list_tags,
+        tag::create_tag,
+        tag::update_tag,
+        tag::delete_tag,
+        tag::merge_tag,
+        tag::list_tag_files,
+        tag::link_tag_files,
+        tag::unlink_tag_files,
+
+        storage::stream_object,
+        storage::upload_object,
     ),
     components(schemas(
         lib_core::EmptyResponse,
 
         lib_domain::datastore::user_space::SpaceRole,
+        lib_domain::datastore::storage::Orientation,
+        lib_domain::datastore::storage::TimelineGranularity,
+        lib_domain::datastore::storage::AlbumSortMode,
+        lib_domain::datastore::storage::AlbumPlacement,
         lib_domain::dto::Datetime,
 
         lib_domain::dto::user::res::UserResponse,
 
         lib_domain::dto::space::req::SpaceCreateRequest,
+        lib_domain::dto::space::req::ReconcileSpaceRequest,
         lib_domain::dto::space::res::SpaceResponse,
         lib_domain::dto::space::res::UserSpaceResponse,
 
+        lib_domain::datastore::job::JobKind,
+        lib_domain::datastore::job::JobStatus,
+        lib_domain::dto::job::res::JobResponse,
+        lib_domain::datastore::audit::AuditAction,
+        lib_domain::dto::audit::res::AuditEntryResponse,
+        lib_domain::dto::usage::res::SpaceUsageResponse,
+
         lib_domain::dto::cloud::req::InitiateUploadRequest,
+        lib_domain::dto::cloud::req::CreateUploadSessionRequest,
+        lib_domain::dto::cloud::req::SignUploadPartsRequest,
         lib_domain::dto::cloud::req::QueueMediaProcessRequest,
         lib_domain::dto::cloud::req::CreateAlbumRequest,
+        lib_domain::dto::cloud::req::UpdateAlbumRequest,
+        lib_domain::dto::cloud::req::GalleryFilterQuery,
+        lib_domain::dto::cloud::req::TimelineQuery,
         lib_domain::dto::cloud::req::UpdateAlbumFilesRequest,
+        lib_domain::dto::cloud::req::ArchiveFilesRequest,
+        lib_domain::dto::cloud::req::MoveAlbumFilesRequest,
+        lib_domain::dto::cloud::req::ReorderAlbumFilesRequest,
+        lib_domain::dto::cloud::req::BulkFilesRequest,
+        lib_domain::dto::cloud::req::TransferFilesRequest,
         lib_domain::dto::cloud::res::InitiateUploadResponse,
+        lib_domain::dto::cloud::res::UploadSessionResponse,
+        lib_domain::dto::cloud::res::UploadPartUrlsResponse,
         lib_domain::dto::cloud::res::FileResponse,
         lib_domain::dto::cloud::res::AlbumResponse,
+        lib_domain::dto::cloud::res::TimelineBucketResponse,
         lib_domain::dto::cloud::res::FileMetadataResponse,
+        lib_domain::dto::cloud::res::BulkFilesResponse,
+        lib_domain::dto::cloud::res::BulkFailureResponse,
+        lib_domain::dto::cloud::res::BulkFailureReason,
+        lib_domain::dto::cloud::res::TransferFilesResponse,
+        lib_domain::dto::cloud::res::TransferredFileResponse,
+        lib_domain::dto::trash::res::TrashedFileResponse,
+        lib_domain::dto::trash::res::TrashedAlbumResponse,
+        lib_domain::dto::search::res::SearchFileResponse,
+        lib_domain::dto::favorite::res::FavoriteFileResponse,
+        lib_domain::dto::tag::req::CreateTagRequest,
+        lib_domain::dto::tag::req::UpdateTagRequest,
+        lib_domain::dto::tag::req::MergeTagRequest,
+        lib_domain::dto::tag::res::TagResponse,
+        lib_domain::dto::tag::res::TagCountResponse,
+        lib_domain::dto::comment::req::CreateCommentRequest,
+        lib_domain::dto::comment::req::UpdateCommentRequest,
+        lib_domain::dto::comment::res::CommentResponse,
+        lib_domain::dto::geo::req::MapBoundsQuery,
+        lib_domain::dto::geo::req::MapZoomQuery,
+        lib_domain::dto::geo::res::MapClusterResponse,
     )),
     servers()
 )]
@@ -1,24 +1,36 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     routing::{delete, get, post, put, Router},
     Extension,
 };
 use lib_core::{ApiError, ApiResult, EmptyResponse, Json, ReqId};
 use lib_domain::{
-    dto::space::{
-        req::{SpaceCreateRequest, SpaceMemberRequest, UpdateSpaceMemberRoleRequest},
-        res::{
-            _SpaceResponse, _SpaceUserResponseVec, SpaceResponse, SpaceUserResponse, UserSpaceResponse,
-            UserSpacesResopnse,
+    dto::{
+        audit::{
+            req::AuditQuery,
+            res::{_AuditEntryResponse, AuditEntryResponse},
         },
+        job::res::{_JobResponse, JobResponse},
+        space::{
+            req::{ReconcileSpaceRequest, SpaceCreateRequest, SpaceMemberRequest, UpdateSpaceMemberRoleRequest},
+            res::{
+                _SpaceResponse, _SpaceUserResponse, SpaceResponse, SpaceUserResponse, UserSpaceResponse,
+                UserSpacesResopnse,
+            },
+        },
+        usage::res::SpaceUsageResponse,
+        Page, PageQuery,
     },
     extension::{SpaceCtx, UserId},
-    service::{space::SpaceService, user_space::UserSpaceService},
+    service::{
+        audit::AuditService, reconcile::ReconcileService, space::SpaceService, usage::UsageService,
+        user_space::UserSpaceService,
+    },
 };
 use uuid::Uuid;
 
-use crate::app::AppState;
+use crate::{app::AppState, tasks};
 
 use super::middleware;
 
@@ -29,9 +41,14 @@ pub fn bind_routes(app: AppState, router: Router<AppState>) -> Router<AppState>
         .route("/users", delete(remove_user_from_space))
         .route("/users", put(update_user_space_role))
         .route("/users/self", delete(leave_space))
+        .route("/usage", get(get_space_usage))
+        .route("/", delete(delete_space))
+        .route("/reconcile", post(reconcile_space))
+        .route("/audit", get(list_audit_entries))
         .layer(axum::middleware::from_fn_with_state(app.clone(), middleware::space::validate_user_space))
         .route("/", post(create_space))
         .route("/", get(get_user_spaces))
+        .route("/jobs/{id}", get(get_job))
         .layer(axum::middleware::from_fn_with_state(app.clone(), middleware::auth::authenticate));
 
     let special_routes = Router::new()
@@ -85,7 +102,7 @@ pub async fn get_user_spaces(
 #[utoipa::path(
     get,
     path = "/v1/space/users",
-    responses((status=200, body=Vec<SpaceUserResponse>)),
+    responses((status=200, body=Page<SpaceUserResponse>)),
     tag = "Space",
     security(("api_key" = []))
 )]
@@ -93,10 +110,11 @@ pub async fn get_space_users(
     State(app): State<AppState>,
     Extension(req_id): Extension<ReqId>,
     Extension(space_ctx): Extension<SpaceCtx>,
-) -> ApiResult<_SpaceUserResponseVec> {
+    Query(query): Query<PageQuery>,
+) -> ApiResult<Page<_SpaceUserResponse>> {
     app.services()
         .user_space_service()
-        .get_users_for_space(space_ctx)
+        .get_users_for_space(space_ctx, query)
         .await
         .map(Json)
         .map_err(|err| ApiError(err, req_id))
@@ -186,6 +204,110 @@ pub async fn leave_space(
         .map_err(|err| ApiError(err, req_id))
 }
 
+#[utoipa::path(
+    get,
+    path = "/v1/space/usage",
+    responses((status=200, body=SpaceUsageResponse)),
+    tag = "Space",
+    security(("api_key" = []))
+)]
+pub async fn get_space_usage(
+    State(app): State<AppState>,
+    Extension(req_id): Extension<ReqId>,
+    Extension(space_ctx): Extension<SpaceCtx>,
+) -> ApiResult<SpaceUsageResponse> {
+    app.services().usage_service().get_space_usage(space_ctx).await.map(Json).map_err(|err| ApiError(err, req_id))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/space",
+    responses((status=200, body=JobResponse)),
+    tag = "Space",
+    security(("api_key" = []))
+)]
+pub async fn delete_space(
+    State(app): State<AppState>,
+    Extension(req_id): Extension<ReqId>,
+    Extension(user_id): Extension<UserId>,
+    Extension(space_ctx): Extension<SpaceCtx>,
+) -> ApiResult<_JobResponse> {
+    let job = app
+        .services()
+        .space_service()
+        .delete_space(user_id, space_ctx, app.storage())
+        .await
+        .map_err(|err| ApiError(err, req_id))?;
+
+    tasks::spawn_space_purge(app.clone(), job.0.id, job.0.space_id);
+
+    Ok(Json(job))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/space/reconcile",
+    responses((status=200, body=JobResponse)),
+    tag = "Space",
+    security(("api_key" = []))
+)]
+pub async fn reconcile_space(
+    State(app): State<AppState>,
+    Extension(req_id): Extension<ReqId>,
+    Extension(user_id): Extension<UserId>,
+    Extension(space_ctx): Extension<SpaceCtx>,
+    Json(dto): Json<ReconcileSpaceRequest>,
+) -> ApiResult<_JobResponse> {
+    let job = app
+        .services()
+        .reconcile_service()
+        .start_reconcile(user_id, space_ctx)
+        .await
+        .map_err(|err| ApiError(err, req_id))?;
+
+    tasks::spawn_space_reconcile(app.clone(), job.0.id, job.0.space_id, dto.enforce);
+
+    Ok(Json(job))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/space/audit",
+    responses((status=200, body=Page<AuditEntryResponse>)),
+    tag = "Space",
+    security(("api_key" = []))
+)]
+pub async fn list_audit_entries(
+    State(app): State<AppState>,
+    Extension(req_id): Extension<ReqId>,
+    Extension(space_ctx): Extension<SpaceCtx>,
+    Query(filter): Query<AuditQuery>,
+    Query(query): Query<PageQuery>,
+) -> ApiResult<Page<_AuditEntryResponse>> {
+    app.services()
+        .audit_service()
+        .list_audit_entries(space_ctx, filter, query)
+        .await
+        .map(Json)
+        .map_err(|err| ApiError(err, req_id))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/space/jobs/{id}",
+    responses((status=200, body=JobResponse)),
+    tag = "Space",
+    security(("api_key" = []))
+)]
+pub async fn get_job(
+    State(app): State<AppState>,
+    Extension(req_id): Extension<ReqId>,
+    Extension(user_id): Extension<UserId>,
+    Path(job_id): Path<Uuid>,
+) -> ApiResult<_JobResponse> {
+    app.services().space_service().get_job(user_id, job_id).await.map(Json).map_err(|err| ApiError(err, req_id))
+}
+
 #[utoipa::path(
     get,
     path = "/v1/space/default/{user_id}",
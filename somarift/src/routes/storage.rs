@@ -0,0 +1,122 @@
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, Router},
+    Extension,
+};
+use lib_core::{
+    storage::{get_object_content, local::SignedUrlQuery, ObjectContent},
+    ApiError, EmptyResponse, ErrType, Json, ReqId,
+};
+
+use crate::app::AppState;
+
+/// Routes serving presigned URLs of [`lib_core::storage::local::LocalStorage`]
+///
+/// Requests are authorized by URL signature instead of bearer token
+pub fn bind_routes(router: Router<AppState>) -> Router<AppState> {
+    let routes = Router::new().route("/{*key}", get(stream_object).put(upload_object));
+
+    router.nest("/storage", routes)
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/storage/{key}",
+    responses(
+        (status=200, description="Object bytes"),
+        (status=206, description="Requested byte range"),
+        (status=416, description="Range not satisfiable"),
+    ),
+    tag = "Storage"
+)]
+pub async fn stream_object(
+    State(app): State<AppState>,
+    Extension(req_id): Extension<ReqId>,
+    Path(key): Path<String>,
+    Query(query): Query<SignedUrlQuery>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    let local =
+        app.storage().local().ok_or(ApiError(ErrType::NotFound.msg("Local storage not enabled"), req_id.clone()))?;
+
+    local.verify_signature("GET", &key, &query).map_err(|err| ApiError(err, req_id.clone()))?;
+
+    let range = headers.get(header::RANGE).and_then(|v| v.to_str().ok());
+    let object = get_object_content(local, &key, range).await.map_err(|err| ApiError(err, req_id.clone()))?;
+
+    object_response(object, None, req_id)
+}
+
+/// Response streaming object bytes, partial content when a range was served
+///
+/// Unsatisfiable ranges answer 416 with the object size in `Content-Range`
+pub fn object_response(
+    object: ObjectContent,
+    cache_control: Option<String>,
+    req_id: ReqId,
+) -> Result<Response, ApiError> {
+    let object = match object {
+        ObjectContent::NotModified(e_tag) => {
+            return Ok((StatusCode::NOT_MODIFIED, [(header::ETAG, e_tag)]).into_response());
+        }
+        ObjectContent::RangeNotSatisfiable(size) => {
+            let content_range = format!("bytes */{size}");
+            return Ok((StatusCode::RANGE_NOT_SATISFIABLE, [(header::CONTENT_RANGE, content_range)]).into_response());
+        }
+        ObjectContent::Stream(object) => object,
+    };
+
+    let status = match object.content_range {
+        Some(_) => StatusCode::PARTIAL_CONTENT,
+        None => StatusCode::OK,
+    };
+
+    let mut res = Response::builder()
+        .status(status)
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header(header::CONTENT_LENGTH, object.content_length)
+        .header(header::CONTENT_TYPE, object.content_type.as_deref().unwrap_or("application/octet-stream"));
+    if let Some(cache_control) = cache_control {
+        res = res.header(header::CACHE_CONTROL, cache_control);
+    }
+    if let Some(content_range) = &object.content_range {
+        res = res.header(header::CONTENT_RANGE, content_range);
+    }
+    if let Some(e_tag) = &object.e_tag {
+        res = res.header(header::ETAG, e_tag);
+    }
+    if let Some(last_modified) = object.last_modified {
+        res = res.header(header::LAST_MODIFIED, last_modified.format("%a, %d %b %Y %H:%M:%S GMT").to_string());
+    }
+
+    res.body(object.into_body())
+        .map_err(|err| ApiError(ErrType::ServerError.err(err, "Failed to build media response"), req_id))
+}
+
+#[utoipa::path(
+    put,
+    path = "/v1/storage/{key}",
+    responses((status=200, body=EmptyResponse)),
+    tag = "Storage"
+)]
+pub async fn upload_object(
+    State(app): State<AppState>,
+    Extension(req_id): Extension<ReqId>,
+    Path(key): Path<String>,
+    Query(query): Query<SignedUrlQuery>,
+    body: Body,
+) -> Result<Json<EmptyResponse>, ApiError> {
+    let local =
+        app.storage().local().ok_or(ApiError(ErrType::NotFound.msg("Local storage not enabled"), req_id.clone()))?;
+
+    local.verify_signature("PUT", &key, &query).map_err(|err| ApiError(err, req_id.clone()))?;
+
+    local
+        .write_stream(&key, body)
+        .await
+        .map(|_| Json(EmptyResponse::new(StatusCode::OK, "Object uploaded")))
+        .map_err(|err| ApiError(err, req_id))
+}
@@ -0,0 +1,193 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    routing::{delete, get, patch, post, Router},
+    Extension,
+};
+use lib_core::{ApiError, ApiResult, EmptyResponse, Json, ReqId};
+use lib_domain::{
+    dto::{
+        cloud::{
+            req::BulkFilesRequest,
+            res::{_FileMetaResponse, BulkFilesResponse, FileMetaResponse},
+        },
+        tag::{
+            req::{CreateTagRequest, MergeTagRequest, UpdateTagRequest},
+            res::{_TagCountResponseVec, _TagResponse, TagCountResponse, TagResponse},
+        },
+        Page, PageQuery,
+    },
+    extension::{SpaceCtx, UserId},
+    service::tag::TagService,
+};
+use uuid::Uuid;
+
+use crate::app::AppState;
+
+use super::middleware;
+
+pub fn bind_routes(app: AppState, router: Router<AppState>) -> Router<AppState> {
+    let routes = Router::new()
+        .route("/", get(list_tags))
+        .route("/", post(create_tag))
+        .route("/{id}", patch(update_tag))
+        .route("/{id}", delete(delete_tag))
+        .route("/{id}/merge", post(merge_tag))
+        .route("/{id}/files", get(list_tag_files))
+        .route("/{id}/files/link", post(link_tag_files))
+        .route("/{id}/files/unlink", post(unlink_tag_files))
+        .layer(axum::middleware::from_fn_with_state(app.clone(), middleware::space::validate_user_space))
+        .layer(axum::middleware::from_fn_with_state(app, middleware::auth::authenticate));
+
+    router.nest("/media/tags", routes)
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/media/tags",
+    responses((status=200, body=Vec<TagCountResponse>)),
+    tag = "Cloud"
+)]
+pub async fn list_tags(
+    State(app): State<AppState>,
+    Extension(req_id): Extension<ReqId>,
+    Extension(space_ctx): Extension<SpaceCtx>,
+) -> ApiResult<_TagCountResponseVec> {
+    app.services().tag_service().list_tags(space_ctx).await.map(Json).map_err(|err| ApiError(err, req_id))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/media/tags",
+    responses((status=200, body=TagResponse)),
+    tag = "Cloud"
+)]
+pub async fn create_tag(
+    State(app): State<AppState>,
+    Extension(req_id): Extension<ReqId>,
+    Extension(space_ctx): Extension<SpaceCtx>,
+    Json(body): Json<CreateTagRequest>,
+) -> ApiResult<_TagResponse> {
+    app.services().tag_service().create_tag(space_ctx, body).await.map(Json).map_err(|err| ApiError(err, req_id))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/v1/media/tags/{id}",
+    responses((status=200, body=TagResponse)),
+    tag = "Cloud"
+)]
+pub async fn update_tag(
+    State(app): State<AppState>,
+    Extension(req_id): Extension<ReqId>,
+    Extension(space_ctx): Extension<SpaceCtx>,
+    Path(tag_id): Path<Uuid>,
+    Json(body): Json<UpdateTagRequest>,
+) -> ApiResult<_TagResponse> {
+    app.services()
+        .tag_service()
+        .update_tag(space_ctx, tag_id, body)
+        .await
+        .map(Json)
+        .map_err(|err| ApiError(err, req_id))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/media/tags/{id}",
+    responses((status=200, body=EmptyResponse)),
+    tag = "Cloud"
+)]
+pub async fn delete_tag(
+    State(app): State<AppState>,
+    Extension(req_id): Extension<ReqId>,
+    Extension(space_ctx): Extension<SpaceCtx>,
+    Path(tag_id): Path<Uuid>,
+) -> ApiResult<EmptyResponse> {
+    app.services()
+        .tag_service()
+        .delete_tag(space_ctx, tag_id)
+        .await
+        .map(|_| Json(EmptyResponse::new(StatusCode::OK, "Tag deleted")))
+        .map_err(|err| ApiError(err, req_id))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/media/tags/{id}/merge",
+    responses((status=200, body=TagResponse)),
+    tag = "Cloud"
+)]
+pub async fn merge_tag(
+    State(app): State<AppState>,
+    Extension(req_id): Extension<ReqId>,
+    Extension(space_ctx): Extension<SpaceCtx>,
+    Path(tag_id): Path<Uuid>,
+    Json(body): Json<MergeTagRequest>,
+) -> ApiResult<_TagResponse> {
+    app.services().tag_service().merge_tag(space_ctx, tag_id, body).await.map(Json).map_err(|err| ApiError(err, req_id))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/media/tags/{id}/files",
+    responses((status=200, body=Page<FileMetaResponse>)),
+    tag = "Cloud"
+)]
+pub async fn list_tag_files(
+    State(app): State<AppState>,
+    Extension(req_id): Extension<ReqId>,
+    Extension(user_id): Extension<UserId>,
+    Extension(space_ctx): Extension<SpaceCtx>,
+    Path(tag_id): Path<Uuid>,
+    Query(query): Query<PageQuery>,
+) -> ApiResult<Page<_FileMetaResponse>> {
+    app.services()
+        .tag_service()
+        .list_tag_files(user_id, space_ctx, tag_id, query)
+        .await
+        .map(Json)
+        .map_err(|err| ApiError(err, req_id))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/media/tags/{id}/files/link",
+    responses((status=200, body=BulkFilesResponse)),
+    tag = "Cloud"
+)]
+pub async fn link_tag_files(
+    State(app): State<AppState>,
+    Extension(req_id): Extension<ReqId>,
+    Extension(space_ctx): Extension<SpaceCtx>,
+    Path(tag_id): Path<Uuid>,
+    Json(body): Json<BulkFilesRequest>,
+) -> ApiResult<BulkFilesResponse> {
+    app.services()
+        .tag_service()
+        .link_tag_files(space_ctx, tag_id, body.file_ids)
+        .await
+        .map(Json)
+        .map_err(|err| ApiError(err, req_id))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/media/tags/{id}/files/unlink",
+    responses((status=200, body=BulkFilesResponse)),
+    tag = "Cloud"
+)]
+pub async fn unlink_tag_files(
+    State(app): State<AppState>,
+    Extension(req_id): Extension<ReqId>,
+    Extension(space_ctx): Extension<SpaceCtx>,
+    Path(tag_id): Path<Uuid>,
+    Json(body): Json<BulkFilesRequest>,
+) -> ApiResult<BulkFilesResponse> {
+    app.services()
+        .tag_service()
+        .unlink_tag_files(space_ctx, tag_id, body.file_ids)
+        .await
+        .map(Json)
+        .map_err(|err| ApiError(err, req_id))
+}
@@ -6,13 +6,16 @@ use utoipa_swagger_ui::SwaggerUi;
 
 use crate::{
     app::{App, AppState},
-    routes,
+    routes, tasks,
 };
 
 /// Serves axum backend server
 pub async fn serve() {
     let app = App::new().await;
 
+    // background maintenance
+    tasks::spawn(app.clone());
+
     // build our application with a route
     // bind routes
     let router = get_router(app).await;
@@ -0,0 +1,157 @@
+use std::time::Duration;
+
+use axum::body::Body;
+use lib_core::{config, storage::ArchiveEntry};
+use lib_domain::service::{
+    media::{MediaService, PendingUpload},
+    reconcile::ReconcileService,
+    space::SpaceService,
+    trash::TrashService,
+    upload::UploadService,
+};
+use uuid::Uuid;
+
+use crate::app::AppState;
+
+/// Bytes buffered between archive writer and response body
+const ARCHIVE_BUFFER_SIZE: usize = 256 * 1024;
+
+/// Interval between stale upload session sweeps
+const UPLOAD_SESSION_SWEEP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Interval between expired trash sweeps
+const TRASH_SWEEP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Delays before retrying a failed space purge, afterwards it is retried on the next startup
+const PURGE_RETRY_DELAYS: [Duration; 4] = [
+    Duration::from_secs(60),
+    Duration::from_secs(5 * 60),
+    Duration::from_secs(30 * 60),
+    Duration::from_secs(2 * 60 * 60),
+];
+
+/// Spawns periodic maintenance tasks, resumes purges and fails reconciliations interrupted by a restart
+pub fn spawn(app: AppState) {
+    tokio::spawn(resume_space_purges(app.clone()));
+    tokio::spawn(fail_interrupted_reconciles(app.clone()));
+    tokio::spawn(abort_stale_upload_sessions(app.clone()));
+    tokio::spawn(purge_expired_trash(app));
+}
+
+/// Spawns storage purge of a deleted space
+///
+/// Progress and failures are recorded on the job, failed purges are retried after [`PURGE_RETRY_DELAYS`]
+pub fn spawn_space_purge(app: AppState, job_id: Uuid, space_id: Uuid) {
+    tokio::spawn(async move {
+        let mut delays = PURGE_RETRY_DELAYS.iter();
+        loop {
+            let Err(err) = app.services().space_service().purge_space(job_id, space_id, app.storage()).await else {
+                return;
+            };
+
+            let Some(delay) = delays.next() else {
+                tracing::error!(job_id = %job_id, "Failed to purge space, retrying on next startup: {err}");
+                return;
+            };
+            tracing::warn!(job_id = %job_id, retry_in = ?delay, "Failed to purge space: {err}");
+            tokio::time::sleep(*delay).await;
+        }
+    });
+}
+
+/// Spawns hashing of an upload without stored checksum, it is queued for processing once verified
+pub fn spawn_upload_verification(app: AppState, upload: PendingUpload) {
+    tokio::spawn(async move {
+        let hash = upload.hash.clone();
+        let result =
+            app.services().media_service().verify_pending_upload(app.storage(), app.interconnect(), upload).await;
+        if let Err(err) = result {
+            tracing::warn!(hash, "Failed to verify upload: {err}");
+        }
+    });
+}
+
+/// Spawns storage reconciliation of a space
+///
+/// Orphans younger than `ORPHAN_GRACE_HOURS` are kept
+pub fn spawn_space_reconcile(app: AppState, job_id: Uuid, space_id: Uuid, enforce: bool) {
+    let grace = Duration::from_secs(config::get_orphan_grace_hours() * 60 * 60);
+    tokio::spawn(async move {
+        let result =
+            app.services().reconcile_service().reconcile_space(job_id, space_id, app.storage(), enforce, grace).await;
+        if let Err(err) = result {
+            tracing::error!(job_id = %job_id, "Failed to reconcile space: {err}");
+        }
+    });
+}
+
+/// Spawns ZIP writer of entries, returns body streaming the archive
+///
+/// Writer stops once the response body is dropped
+pub fn spawn_archive(app: AppState, space_id: Uuid, entries: Vec<ArchiveEntry>) -> Body {
+    let (writer, reader) = tokio::io::duplex(ARCHIVE_BUFFER_SIZE);
+    tokio::spawn(async move {
+        if let Err(err) = app.storage().write_zip(&space_id.to_string(), entries, writer).await {
+            tracing::warn!(space_id = %space_id, "Archive stream ended early: {err}");
+        }
+    });
+
+    Body::from_stream(tokio_util::io::ReaderStream::new(reader))
+}
+
+/// Respawns space purges left pending, running or failed, purging is idempotent so they restart from scratch
+async fn resume_space_purges(app: AppState) {
+    let jobs = match app.services().space_service().list_unfinished_purges().await {
+        Ok(jobs) => jobs,
+        Err(err) => {
+            tracing::error!("Failed to list unfinished space purges: {err}");
+            return;
+        }
+    };
+
+    for job in jobs {
+        tracing::info!(job_id = %job.0.id, "Resuming space purge");
+        spawn_space_purge(app.clone(), job.0.id, job.0.space_id);
+    }
+}
+
+/// Fails reconciliations left pending or running, their report would never complete
+async fn fail_interrupted_reconciles(app: AppState) {
+    match app.services().reconcile_service().fail_interrupted_reconciles().await {
+        Ok(0) => (),
+        Ok(failed) => tracing::info!(failed, "Marked reconciliations interrupted by restart as failed"),
+        Err(err) => tracing::error!("Failed to mark interrupted reconciliations as failed: {err}"),
+    }
+}
+
+/// Aborts multipart uploads left idle for longer than `UPLOAD_SESSION_TTL_HOURS`
+async fn abort_stale_upload_sessions(app: AppState) {
+    let ttl = Duration::from_secs(config::get_upload_session_ttl_hours() * 60 * 60);
+    let mut interval = tokio::time::interval(UPLOAD_SESSION_SWEEP_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        match app.services().upload_service().abort_stale_upload_sessions(app.storage(), ttl).await {
+            Ok(0) => (),
+            Ok(aborted) => tracing::info!(aborted, "Aborted stale upload sessions"),
+            Err(err) => tracing::error!("Failed to abort stale upload sessions: {err}"),
+        }
+    }
+}
+
+/// Purges files and albums trashed longer than `TRASH_RETENTION_DAYS`
+async fn purge_expired_trash(app: AppState) {
+    let retention = Duration::from_secs(config::get_trash_retention_days() * 24 * 60 * 60);
+    let mut interval = tokio::time::interval(TRASH_SWEEP_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        match app.services().trash_service().purge_expired_trash(app.storage(), retention).await {
+            Ok(0) => (),
+            Ok(purged) => tracing::info!(purged, "Purged expired trash"),
+            Err(err) => tracing::error!("Failed to purge expired trash: {err}"),
+        }
+    }
+}